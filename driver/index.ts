@@ -8,7 +8,7 @@ async function main() {
 
 	if (args.length === 2) {
 		syntheticRepl();
-	} else if (args.length === 3 && args[2].includes(".js")) {
+	} else if (args.length === 3 && /\.m?js$/.test(args[2])) {
 		await syntheticCli(args);
 	} else {
 		console.error("Usage: yarn [start|dev] <filename.js>");
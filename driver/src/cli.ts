@@ -1,5 +1,6 @@
-import { parse } from "parser";
+import { parseFile } from "parser";
 import * as fs from "fs";
+import * as path from "path";
 
 import { evaluate } from "./evaluator";
 
@@ -18,8 +19,8 @@ export async function syntheticCli(args: string[]) {
 		process.exit(1);
 	}
 
-	const ast = parse(SOURCE_CODE);
+	const ast = parseFile(SOURCE_CODE);
 	const serializedAst = JSON.stringify(ast);
 
-	await evaluate(serializedAst);
+	await evaluate(serializedAst, path.resolve(filename));
 }
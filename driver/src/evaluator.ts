@@ -1,12 +1,18 @@
+import { fileLoader } from "./loader";
 import { loadWasm } from "./loadWasm";
 
-export async function evaluate(serializedAst: string) {
+// Programs run from a file can import the modules next to it
+export async function evaluate(serializedAst: string, path?: string) {
 	try {
-		const { DEBUG } = process.env;
+		const { DEBUG, REAL_TIME } = process.env;
 		DEBUG && console.log(`Parsed AST:\n\n${serializedAst}\n`);
 		// evaluate using rust
 		const wasm = await loadWasm();
-		const x = wasm.evaluate(serializedAst);
+		const clock = REAL_TIME ? wasm.Clock.RealTime : wasm.Clock.Virtual;
+		const x =
+			path === undefined
+				? wasm.evaluate(serializedAst)
+				: await wasm.evaluate_file(serializedAst, path, fileLoader, clock);
 		console.log(`Evaluator Result: ${x}`);
 	} catch (e) {
 		console.error("Error evaluating", e);
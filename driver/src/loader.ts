@@ -0,0 +1,32 @@
+import * as fs from "fs";
+import * as path from "path";
+import { parseFile } from "parser";
+
+// Finds the modules imported or required by a program on the local filesystem. Modules are known
+// by their absolute path, relative specifiers are resolved against the path of the importing
+// file. Like require in node, a specifier may leave out the .js extension or name a directory
+// with an index.js.
+export const fileLoader = {
+	resolve(specifier: string, referrer: string): string {
+		if (!/^\.{1,2}\//.test(specifier) && !path.isAbsolute(specifier)) {
+			throw new Error(`Cannot find package '${specifier}' imported from ${referrer}`);
+		}
+		const file = path.resolve(path.dirname(referrer), specifier);
+		for (const candidate of [file, `${file}.js`, path.join(file, "index.js")]) {
+			if (fs.existsSync(candidate) && fs.statSync(candidate).isFile()) {
+				return candidate;
+			}
+		}
+		return file;
+	},
+
+	load(file: string): string {
+		let sourceCode;
+		try {
+			sourceCode = fs.readFileSync(file, { encoding: "utf-8" });
+		} catch (e) {
+			throw new Error(`Cannot find module '${file}'`);
+		}
+		return JSON.stringify(parseFile(sourceCode));
+	},
+};
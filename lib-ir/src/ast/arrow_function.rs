@@ -9,4 +9,6 @@ pub struct ArrowFunctionExpression {
     pub body: Box<Node>,
     pub expression: bool,
     generator: bool, // false
+    #[serde(rename = "async", default)]
+    pub is_async: bool,
 }
@@ -0,0 +1,233 @@
+use super::{ForInLeftValue, Node, NodeKind, ObjectExpressionProperty, ObjectPatternProperty};
+
+impl Node {
+    // The nodes directly nested in this node, in source order. Analyses that need to look
+    // through the whole tree (early errors, declaration scanning) are built on top of this.
+    pub fn children(&self) -> Vec<&Node> {
+        let mut children: Vec<&Node> = Vec::new();
+        match &self.kind {
+            NodeKind::Identifier(_)
+            | NodeKind::Literal(_)
+            | NodeKind::Function(_)
+            | NodeKind::Directive(_)
+            | NodeKind::EmptyStatement(_)
+            | NodeKind::DebuggerStatement(_)
+            | NodeKind::BreakStatement(_)
+            | NodeKind::ContinueStatement(_)
+            | NodeKind::UnaryOperator(_)
+            | NodeKind::BinaryOperator(_)
+            | NodeKind::AssignmentOperator(_)
+            | NodeKind::LogicalOperator(_)
+            | NodeKind::TemplateElement(_)
+            | NodeKind::MetaProperty(_)
+            | NodeKind::ModuleSpecifier(_)
+            | NodeKind::ImportDeclaration(_)
+            | NodeKind::ImportSpecifier(_)
+            | NodeKind::ImportDefaultSpecifier(_)
+            | NodeKind::ImportNamespaceSpecifier(_)
+            | NodeKind::ExportSpecifier(_)
+            | NodeKind::ExportAllDeclaration(_) => {}
+            NodeKind::Program(p) => children.extend(p.body.iter()),
+            NodeKind::ExpressionStatement(s) => children.push(&s.expression),
+            NodeKind::BlockStatement(b) | NodeKind::FunctionBody(b) => {
+                children.extend(b.body.iter())
+            }
+            NodeKind::WithStatement(s) => {
+                children.push(&s.object);
+                children.push(&s.body);
+            }
+            NodeKind::ReturnStatement(s) => children.extend(s.argument.as_deref()),
+            NodeKind::LabeledStatement(s) => children.push(&s.body),
+            NodeKind::IfStatement(s) => {
+                children.push(&s.test);
+                children.push(&s.consequent);
+                children.extend(s.alternate.as_deref());
+            }
+            NodeKind::SwitchStatement(s) => {
+                children.push(&s.discriminant);
+                for case in &s.cases {
+                    children.extend(case.test.as_deref());
+                    children.extend(case.consequent.iter().map(|c| c.as_ref()));
+                }
+            }
+            NodeKind::SwitchCase(case) => {
+                children.extend(case.test.as_deref());
+                children.extend(case.consequent.iter().map(|c| c.as_ref()));
+            }
+            NodeKind::ThrowStatement(s) => children.push(&s.argument),
+            NodeKind::TryStatement(s) => {
+                children.extend(s.block.body.iter());
+                if let Some(handler) = &s.handler {
+                    children.extend(handler.param.as_deref());
+                    children.extend(handler.body.body.iter());
+                }
+                if let Some(finalizer) = &s.finalizer {
+                    children.extend(finalizer.body.iter());
+                }
+            }
+            NodeKind::CatchClause(c) => {
+                children.extend(c.param.as_deref());
+                children.extend(c.body.body.iter());
+            }
+            NodeKind::WhileStatement(s) => {
+                children.push(&s.test);
+                children.push(&s.body);
+            }
+            NodeKind::DoWhileStatement(s) => {
+                children.push(&s.body);
+                children.push(&s.test);
+            }
+            NodeKind::ForStatement(s) => {
+                children.extend(s.init.as_deref());
+                children.extend(s.test.as_deref());
+                children.extend(s.update.as_deref());
+                children.push(&s.body);
+            }
+            NodeKind::ForInStatement(s) | NodeKind::ForOfStatement(s) => {
+                match &s.left {
+                    ForInLeftValue::Pattern(p) => children.push(p),
+                    ForInLeftValue::VariableDeclaration(d) => {
+                        for declarator in &d.declarations {
+                            children.push(&declarator.id);
+                            children.extend(declarator.init.as_deref());
+                        }
+                    }
+                }
+                children.push(&s.right);
+                children.push(&s.body);
+            }
+            NodeKind::FunctionDeclaration(f) => {
+                children.extend(f.params.iter().map(|p| p.as_ref()));
+                children.extend(f.body.body.iter());
+            }
+            NodeKind::FunctionExpression(f) => {
+                children.extend(f.params.iter().map(|p| p.as_ref()));
+                children.extend(f.body.body.iter());
+            }
+            NodeKind::ArrowFunctionExpression(f) => {
+                children.extend(f.params.iter().map(|p| p.as_ref()));
+                children.push(&f.body);
+            }
+            NodeKind::VariableDeclaration(d) => {
+                for declarator in &d.declarations {
+                    children.push(&declarator.id);
+                    children.extend(declarator.init.as_deref());
+                }
+            }
+            NodeKind::VariableDeclarator(d) => {
+                children.push(&d.id);
+                children.extend(d.init.as_deref());
+            }
+            NodeKind::ThisExpression(_)
+            | NodeKind::Super(_)
+            | NodeKind::PrivateIdentifier(_)
+            | NodeKind::ModuleDeclaration(_) => {}
+            NodeKind::ArrayExpression(a) => {
+                children.extend(a.elements.iter().flatten().map(|e| e.as_ref()))
+            }
+            NodeKind::ObjectExpression(o) => {
+                for property in &o.properties {
+                    match property {
+                        ObjectExpressionProperty::Property(p) => {
+                            children.push(&p.key);
+                            children.push(&p.value);
+                        }
+                        ObjectExpressionProperty::SpreadElement(s) => children.push(&s.argument),
+                    }
+                }
+            }
+            NodeKind::Property(p) => {
+                children.push(&p.key);
+                children.push(&p.value);
+            }
+            NodeKind::UnaryExpression(e) => children.push(&e.argument),
+            NodeKind::UpdateExpression(e) => children.push(&e.argument),
+            NodeKind::BinaryExpression(e) => {
+                children.push(&e.left);
+                children.push(&e.right);
+            }
+            NodeKind::AssignmentExpression(e) => {
+                children.push(&e.left);
+                children.push(&e.right);
+            }
+            NodeKind::LogicalExpression(e) => {
+                children.push(&e.left);
+                children.push(&e.right);
+            }
+            NodeKind::MemberExpression(e) => {
+                children.push(&e.object);
+                children.push(&e.property);
+            }
+            NodeKind::ConditionalExpression(e) => {
+                children.push(&e.test);
+                children.push(&e.consequent);
+                children.push(&e.alternate);
+            }
+            NodeKind::CallExpression(c) => {
+                children.push(&c.callee);
+                children.extend(c.arguments.iter().map(|a| a.as_ref()));
+            }
+            NodeKind::NewExpression(n) => {
+                children.push(&n.callee);
+                children.extend(n.arguments.iter().map(|a| a.as_ref()));
+            }
+            NodeKind::SequenceExpression(s) => children.extend(s.expressions.iter()),
+            NodeKind::SpreadElement(s) => children.push(&s.argument),
+            NodeKind::YieldExpression(y) => children.extend(y.argument.as_deref()),
+            NodeKind::AwaitExpression(a) => children.push(&a.argument),
+            NodeKind::TemplateLiteral(t) => {
+                children.extend(t.expressions.iter().map(|e| e.as_ref()))
+            }
+            NodeKind::TaggedTemplateExpression(t) => {
+                children.push(&t.tag);
+                children.extend(t.quasi.expressions.iter().map(|e| e.as_ref()));
+            }
+            NodeKind::AssignmentProperty(p) => {
+                children.push(&p.key);
+                children.push(&p.value);
+            }
+            NodeKind::ObjectPattern(p) => {
+                for property in &p.properties {
+                    match property {
+                        ObjectPatternProperty::AssignmentProperty(p) => {
+                            children.push(&p.key);
+                            children.push(&p.value);
+                        }
+                        ObjectPatternProperty::RestElement(r) => children.push(&r.argument),
+                    }
+                }
+            }
+            NodeKind::ArrayPattern(p) => {
+                children.extend(p.elements.iter().flatten().map(|e| e.as_ref()))
+            }
+            NodeKind::RestElement(r) => children.push(&r.argument),
+            NodeKind::AssignmentPattern(p) => {
+                children.push(&p.left);
+                children.push(&p.right);
+            }
+            NodeKind::Class(c) | NodeKind::ClassExpression(c) => {
+                children.extend(c.super_class.as_deref());
+                children.extend(c.body.body.iter());
+            }
+            NodeKind::ClassBody(b) => children.extend(b.body.iter()),
+            NodeKind::MethodDefinition(m) => {
+                children.push(&m.key);
+                children.extend(m.value.params.iter().map(|p| p.as_ref()));
+                children.extend(m.value.body.body.iter());
+            }
+            NodeKind::PropertyDefinition(p) => {
+                children.push(&p.key);
+                children.extend(p.value.as_deref());
+            }
+            NodeKind::StaticBlock(b) => children.extend(b.body.iter()),
+            NodeKind::ClassDeclaration(c) => {
+                children.extend(c.super_class.as_deref());
+                children.extend(c.body.body.iter());
+            }
+            NodeKind::ExportNamedDeclaration(e) => children.extend(e.declaration.as_deref()),
+            NodeKind::ExportDefaultDeclaration(e) => children.push(&e.declaration),
+            NodeKind::ImportExpression(i) => children.push(&i.source),
+        }
+        children
+    }
+}
@@ -3,11 +3,35 @@ use serde::Deserialize;
 use super::literal_value::{de_from_literal, LiteralValue};
 
 #[derive(Deserialize, Clone, Debug)]
+#[serde(from = "EstreeLiteral")]
 pub struct Literal {
-    #[serde(deserialize_with = "de_from_literal")]
     pub value: LiteralValue,
 }
 
+// https://github.com/estree/estree/blob/master/es5.md#regexpliteral
+#[derive(Deserialize)]
+struct EstreeLiteral {
+    #[serde(deserialize_with = "de_from_literal")]
+    value: LiteralValue,
+    regex: Option<RegExpLiteral>,
+}
+
+#[derive(Deserialize)]
+struct RegExpLiteral {
+    pattern: String,
+    flags: String,
+}
+
+impl From<EstreeLiteral> for Literal {
+    fn from(literal: EstreeLiteral) -> Self {
+        let value = match literal.regex {
+            Some(RegExpLiteral { pattern, flags }) => LiteralValue::RegExp { pattern, flags },
+            None => literal.value,
+        };
+        Literal { value }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum JsNumber {
     Number(f64),
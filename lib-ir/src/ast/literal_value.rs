@@ -1,9 +1,9 @@
 use super::literal::JsNumber;
-use serde::{Deserialize, Deserializer};
+use serde::{de::IgnoredAny, Deserialize, Deserializer};
 
 use super::{
     coerced_eq::CoercedEq,
-    math::{Additive, BitwiseBinary, BitwiseShift, Multiplicative},
+    math::{Additive, BitwiseBinary, BitwiseShift, Exponential, Multiplicative},
 };
 
 #[derive(Clone, Debug)]
@@ -12,7 +12,8 @@ pub enum LiteralValue {
     Boolean(bool),
     Null,
     Number(JsNumber),
-    RegExp,
+    // A regular expression literal, each evaluation of it creates a new RegExp object
+    RegExp { pattern: String, flags: String },
     Undefined,
 }
 
@@ -30,6 +31,9 @@ where
         String(Option<String>), // string or null
         F64(f64),
         Bool(bool),
+        // The value of a regular expression literal is an empty object, its pattern is kept in
+        // the regex field of the literal
+        Object(IgnoredAny),
     }
 
     let res = match JsonValue::deserialize(deserializer)? {
@@ -39,6 +43,7 @@ where
         },
         JsonValue::F64(f) => LiteralValue::Number(JsNumber::Number(f)),
         JsonValue::Bool(b) => LiteralValue::Boolean(b),
+        JsonValue::Object(_) => LiteralValue::Null,
     };
     Ok(res)
 }
@@ -68,15 +73,52 @@ impl Into<String> for LiteralValue {
             }
             LiteralValue::Null => String::from("null"),
             LiteralValue::Number(f) => match f {
-                JsNumber::Number(f) => f.to_string(),
+                JsNumber::Number(f) => number_to_string(f),
                 JsNumber::Nan => String::from("NaN"),
             },
-            LiteralValue::RegExp => unimplemented!(),
+            LiteralValue::RegExp { pattern, flags } => format!("/{}/{}", pattern, flags),
             LiteralValue::Undefined => String::from("undefined"),
         }
     }
 }
 
+// https://262.ecma-international.org/5.1/#sec-9.8.1
+// Rust already finds the shortest digit string that round trips, only the layout differs from JS
+fn number_to_string(f: f64) -> String {
+    if f == 0.0 {
+        return String::from("0");
+    }
+    if f.is_infinite() {
+        return String::from(if f > 0.0 { "Infinity" } else { "-Infinity" });
+    }
+    if f < 0.0 {
+        return format!("-{}", number_to_string(-f));
+    }
+
+    // f = 0.digits * 10^n
+    let scientific = format!("{:e}", f);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        let fraction = if k > 1 {
+            format!(".{}", &digits[1..])
+        } else {
+            String::new()
+        };
+        format!("{}{}e{}{}", &digits[..1], fraction, sign, (n - 1).abs())
+    }
+}
+
 // https://262.ecma-international.org/5.1/#sec-9.3.1
 #[allow(clippy::from_over_into)]
 impl Into<JsNumber> for LiteralValue {
@@ -95,7 +137,7 @@ impl Into<JsNumber> for LiteralValue {
             }
             LiteralValue::Null => JsNumber::Number(0.0),
             LiteralValue::Number(f) => f,
-            LiteralValue::RegExp => unimplemented!(),
+            LiteralValue::RegExp { .. } => JsNumber::Nan,
             LiteralValue::Undefined => JsNumber::Nan,
         }
     }
@@ -112,7 +154,7 @@ impl Into<bool> for LiteralValue {
                 JsNumber::Number(n) => n != 0.0,
                 JsNumber::Nan => false,
             },
-            LiteralValue::RegExp => unimplemented!(),
+            LiteralValue::RegExp { .. } => true,
             LiteralValue::Null | LiteralValue::Undefined => false,
         }
     }
@@ -149,10 +191,13 @@ macro_rules! binary_op_math {
 }
 
 // https://262.ecma-international.org/5.1/#sec-11.6
+#[allow(clippy::needless_return)]
 impl Additive for LiteralValue {
     fn add(&self, other: &Self) -> Self {
         match (self, other) {
-            (LiteralValue::String(s1), LiteralValue::String(s2)) => {
+            (LiteralValue::String(_), _) | (_, LiteralValue::String(_)) => {
+                let s1: String = self.to_owned().into();
+                let s2: String = other.to_owned().into();
                 LiteralValue::from(format!("{}{}", s1, s2))
             }
             _ => {
@@ -173,6 +218,7 @@ impl Additive for LiteralValue {
     }
 }
 
+#[allow(clippy::needless_return)]
 impl Multiplicative for LiteralValue {
     fn mul(&self, other: &Self) -> Self {
         // Safety: read only, we can clone
@@ -190,18 +236,23 @@ impl Multiplicative for LiteralValue {
     }
 }
 
+// https://262.ecma-international.org/5.1/#sec-9.5
+fn to_int32(value: &LiteralValue) -> i32 {
+    to_uint32(value) as i32
+}
+
+// https://262.ecma-international.org/5.1/#sec-9.6
+fn to_uint32(value: &LiteralValue) -> u32 {
+    match value.to_owned().into() {
+        // NaN and the infinities are left as NaN by the remainder, and cast to 0
+        JsNumber::Number(n) => ((n.trunc() % 4294967296.0) as i64) as u32,
+        JsNumber::Nan => 0,
+    }
+}
+
 macro_rules! bitwise_op {
 	($e1: expr, $e2: expr, $op: tt) => {{
-		let n1: JsNumber = $e1.to_owned().into();
-		let n2: JsNumber = $e2.to_owned().into();
-		match (n1, n2) {
-			(JsNumber::Number(n1), JsNumber::Number(n2)) => {
-				let i1 = n1 as i64;
-				let i2 = n2 as i64;
-				LiteralValue::from((i1 $op i2) as f64)
-			}
-			_ => LiteralValue::Number(JsNumber::Nan),
-		}
+		LiteralValue::from((to_int32($e1) $op to_int32($e2)) as f64)
 	}};
 }
 
@@ -221,36 +272,35 @@ impl BitwiseBinary for LiteralValue {
 }
 
 // https://262.ecma-international.org/5.1/#sec-11.7
+// Only the low 5 bits of the shift count are used
 impl BitwiseShift for LiteralValue {
     fn left_shift(&self, other: &Self) -> Self {
-        bitwise_op!(self, other, <<)
+        LiteralValue::from(to_int32(self).wrapping_shl(to_uint32(other) & 0x1F) as f64)
     }
 
     fn signed_right_shift(&self, other: &Self) -> Self {
-        bitwise_op!(self, other, >>)
+        LiteralValue::from((to_int32(self) >> (to_uint32(other) & 0x1F)) as f64)
     }
 
     fn unsigned_right_shift(&self, other: &Self) -> Self {
-        let n1: JsNumber = self.to_owned().into();
-        let n2: JsNumber = other.to_owned().into();
-        match (n1, n2) {
-            (JsNumber::Number(n1), JsNumber::Number(n2)) => {
-                let i1 = if n1 < 0.0 {
-                    let b = n1.to_ne_bytes();
-                    u64::from_ne_bytes(b) as i64
-                } else {
-                    n1 as i64
-                };
-                let i2 = if n2 < 0.0 {
-                    let b = n2.to_ne_bytes();
-                    u64::from_ne_bytes(b) as i64
-                } else {
-                    n2 as i64
-                };
-                LiteralValue::from((i1 >> i2) as f64)
-            }
-            _ => LiteralValue::Number(JsNumber::Nan),
+        LiteralValue::from((to_uint32(self) >> (to_uint32(other) & 0x1F)) as f64)
+    }
+}
+
+// https://tc39.es/ecma262/#sec-numeric-types-number-exponentiate
+impl Exponential for LiteralValue {
+    fn pow(&self, other: &Self) -> Self {
+        let as_f64 = |n: JsNumber| match n {
+            JsNumber::Number(n) => n,
+            JsNumber::Nan => f64::NAN,
+        };
+        let base = as_f64(self.to_owned().into());
+        let exponent = as_f64(other.to_owned().into());
+        // Unlike powf, 1 ** NaN and 1 ** Infinity are NaN
+        if exponent.is_nan() || (base.abs() == 1.0 && exponent.is_infinite()) {
+            return LiteralValue::Number(JsNumber::Nan);
         }
+        LiteralValue::from(base.powf(exponent))
     }
 }
 
@@ -275,7 +325,7 @@ impl CoercedEq for LiteralValue {
                 left_value.eq(&right_value)
             }
 
-            (_, LiteralValue::RegExp) | (LiteralValue::RegExp, _) => unimplemented!(),
+            (_, LiteralValue::RegExp { .. }) | (LiteralValue::RegExp { .. }, _) => false,
             (LiteralValue::Undefined, _) | (LiteralValue::Null, _) => true,
             _ => false,
         }
@@ -284,6 +334,9 @@ impl CoercedEq for LiteralValue {
 
 impl From<f64> for LiteralValue {
     fn from(f: f64) -> Self {
+        if f.is_nan() {
+            return LiteralValue::Number(JsNumber::Nan);
+        }
         LiteralValue::Number(JsNumber::Number(f))
     }
 }
@@ -12,6 +12,10 @@ pub trait Multiplicative<Rhs = Self> {
     fn modulo(&self, other: &Rhs) -> Rhs;
 }
 
+pub trait Exponential<Rhs = Self> {
+    fn pow(&self, other: &Rhs) -> Rhs;
+}
+
 pub trait BitwiseBinary<Rhs = Self> {
     fn bitwise_and(&self, other: &Rhs) -> Rhs;
 
@@ -2,8 +2,11 @@
 
 use self::{arrow_function::ArrowFunctionExpression, literal::Literal};
 use serde::Deserialize;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub mod arrow_function;
+mod children;
 pub mod coerced_eq;
 pub mod literal;
 pub mod literal_value;
@@ -11,15 +14,26 @@ pub mod math;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Position {
-    line: usize,
-    column: usize,
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct SourceLocation {
-    source: Option<String>,
-    start: Position,
-    end: Position,
+    pub source: Option<String>,
+    pub start: Position,
+    pub end: Position,
+}
+
+// Lines are numbered from 1 and columns from 0 by the parser, positions are reported the way
+// node does in stack traces: source:line:column with both counted from 1
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{}:", source)?;
+        }
+        write!(f, "{}:{}", self.start.line, self.start.column + 1)
+    }
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -34,12 +48,10 @@ pub type Statement = Box<Node>;
 pub type Expression = Box<Node>;
 pub type Declaration = Statement;
 pub type FunctionBody = BlockStatement;
-pub type ThisExpression = Expression;
 
 // es6
 pub type Pattern = Box<Node>;
 pub type ForOfStatement = ForInStatement;
-pub type Super = Box<Node>;
 pub type ModuleDeclaration = Box<Node>;
 pub type ClassExpression = Class;
 pub type ImportDefaultSpecifier = ModuleSpecifier;
@@ -85,6 +97,7 @@ pub enum NodeKind {
     FunctionExpression(FunctionExpression),
     UnaryExpression(UnaryExpression),
     UnaryOperator(UnaryOperator),
+    UpdateExpression(UpdateExpression),
     BinaryExpression(BinaryExpression),
     BinaryOperator(BinaryOperator),
     AssignmentExpression(AssignmentExpression),
@@ -112,6 +125,9 @@ pub enum NodeKind {
     Class(Class),
     ClassBody(ClassBody),
     MethodDefinition(MethodDefinition),
+    PropertyDefinition(PropertyDefinition),
+    PrivateIdentifier(PrivateIdentifier),
+    StaticBlock(StaticBlock),
     ClassDeclaration(ClassDeclaration),
     ClassExpression(ClassExpression),
     MetaProperty(MetaProperty),
@@ -125,6 +141,10 @@ pub enum NodeKind {
     ExportSpecifier(ExportSpecifier),
     ExportDefaultDeclaration(ExportDefaultDeclaration),
     ExportAllDeclaration(ExportAllDeclaration),
+    // es2017
+    AwaitExpression(AwaitExpression),
+    // es2020
+    ImportExpression(ImportExpression),
 }
 
 #[derive(Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
@@ -134,13 +154,18 @@ pub struct Identifier {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Program {
-    body: Vec<ProgramBody>,
+    pub body: Vec<Node>,
+    #[serde(rename = "sourceType", default)]
+    pub source_type: SourceType,
 }
 
-#[derive(Deserialize, Clone, Debug)]
-pub enum ProgramBody {
-    ModuleDeclaration(ModuleDeclaration),
-    Statement(Statement),
+// Modules are always strict mode code, and are the only programs that may import and export
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceType {
+    #[default]
+    Script,
+    Module,
 }
 
 // TODO: Change this into trait?
@@ -154,8 +179,15 @@ pub struct Function {
 #[derive(Deserialize, Clone, Debug)]
 pub struct ExpressionStatement {
     pub expression: Expression,
+    pub directive: Option<String>, // Set for the directive prologue, e.g. "use strict"
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct ThisExpression {}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Super {}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct Directive {
     expression: Literal,
@@ -188,18 +220,18 @@ pub struct ReturnStatement {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct LabeledStatement {
-    label: Identifier,
-    body: Statement,
+    pub label: Identifier,
+    pub body: Statement,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct BreakStatement {
-    label: Option<Identifier>,
+    pub label: Option<Identifier>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ContinueStatement {
-    label: Option<Identifier>,
+    pub label: Option<Identifier>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -211,73 +243,66 @@ pub struct IfStatement {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct SwitchStatement {
-    discriminant: Expression,
-    cases: Vec<SwitchCase>,
+    pub discriminant: Expression,
+    pub cases: Vec<SwitchCase>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct SwitchCase {
-    test: Option<Expression>,
-    consequent: Vec<Statement>,
+    pub test: Option<Expression>, // None for the default clause
+    pub consequent: Vec<Statement>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ThrowStatement {
-    argument: Expression,
+    pub argument: Expression,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TryStatement {
-    block: BlockStatement,
-    handler: Option<CatchClause>,
-    finalizer: Option<BlockStatement>,
+    pub block: BlockStatement,
+    pub handler: Option<CatchClause>,
+    pub finalizer: Option<BlockStatement>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct CatchClause {
-    param: Pattern,
-    body: BlockStatement,
+    pub param: Option<Pattern>, // None for an optional catch binding: catch { }
+    pub body: BlockStatement,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct WhileStatement {
-    test: Expression,
-    body: Statement,
+    pub test: Expression,
+    pub body: Statement,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct DoWhileStatement {
-    body: Statement,
-    test: Expression,
+    pub body: Statement,
+    pub test: Expression,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ForStatement {
-    #[serde(flatten)]
-    init: ForInitValue,
-    test: Option<Expression>,
-    update: Option<Expression>,
-    body: Statement,
+    pub init: Option<ForInitValue>, // VariableDeclaration | Expression
+    pub test: Option<Expression>,
+    pub update: Option<Expression>,
+    pub body: Statement,
 }
 
-#[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "type")]
-pub enum ForInitValue {
-    VariableDeclaration(VariableDeclaration),
-    Expression(Expression),
-    Null,
-}
+pub type ForInitValue = Box<Node>;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ForInStatement {
-    #[serde(flatten)]
-    left: ForInLeftValue,
-    right: Expression,
-    body: Statement,
+    pub left: ForInLeftValue,
+    pub right: Expression,
+    pub body: Statement,
 }
 
+// A declaration names the variables of the loop, any other pattern is assigned to
 #[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "type")]
+#[serde(untagged)]
 pub enum ForInLeftValue {
     VariableDeclaration(VariableDeclaration),
     Pattern(Pattern),
@@ -285,10 +310,13 @@ pub enum ForInLeftValue {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct FunctionDeclaration {
+    #[serde(deserialize_with = "de_declaration_name")]
     pub id: Identifier,
     pub params: Vec<Pattern>,
     pub body: FunctionBody,
     pub generator: bool,
+    #[serde(rename = "async", default)]
+    pub is_async: bool,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -297,6 +325,8 @@ pub struct FunctionExpression {
     pub params: Vec<Pattern>,
     pub body: FunctionBody,
     pub generator: bool,
+    #[serde(rename = "async", default)]
+    pub is_async: bool,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -307,37 +337,38 @@ pub struct VariableDeclaration {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct VariableDeclarator {
-    pub id: Identifier,
+    pub id: Pattern,
     pub init: Option<Expression>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ArrayExpression {
-    #[serde(flatten)]
-    elements: Vec<ArrayElements>,
+    pub elements: Vec<Option<ArrayElement>>, // None for holes: [1, , 2]
 }
 
+pub type ArrayElement = Box<Node>; // Expression | SpreadElement
+
 #[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "type")]
-pub enum ArrayElements {
-    Expression(Expression),
-    SpreadElement(SpreadElement),
-    Null,
+pub struct ObjectExpression {
+    pub properties: Vec<ObjectExpressionProperty>,
 }
 
+// { ...source } copies the own properties of source into the literal
 #[derive(Deserialize, Clone, Debug)]
-pub struct ObjectExpression {
-    pub properties: Vec<Property>,
+#[serde(tag = "type")]
+pub enum ObjectExpressionProperty {
+    Property(Property),
+    SpreadElement(SpreadElement),
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Property {
     pub key: Expression,
     pub value: Expression,
-    kind: String, // "init" | "get" | "set"
+    pub kind: String, // "init" | "get" | "set"
     method: bool,
-    shorthand: bool,
-    computed: bool,
+    pub shorthand: bool,
+    pub computed: bool,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -349,9 +380,9 @@ pub struct UnaryExpression {
 
 #[derive(Deserialize, Clone, Debug)]
 pub enum UnaryOperator {
-    #[serde(alias = "+")]
-    Minus,
     #[serde(alias = "-")]
+    Minus,
+    #[serde(alias = "+")]
     Plus,
     #[serde(alias = "!")]
     Bang,
@@ -363,6 +394,21 @@ pub enum UnaryOperator {
     Delete,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct UpdateExpression {
+    pub operator: UpdateOperator,
+    pub prefix: bool,
+    pub argument: Expression,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum UpdateOperator {
+    #[serde(alias = "++")]
+    PlusPlus,
+    #[serde(alias = "--")]
+    MinusMinus,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct BinaryExpression {
     pub operator: BinaryOperator,
@@ -404,6 +450,8 @@ pub enum BinaryOperator {
     Div,
     #[serde(alias = "%")]
     Mod,
+    #[serde(alias = "**")]
+    Exp,
     #[serde(alias = "|")]
     Pipe,
     #[serde(alias = "^")]
@@ -449,13 +497,24 @@ pub enum AssignmentOperator {
     CaretEq,
     #[serde(alias = "&=")]
     AndEq,
+    #[serde(alias = "**=")]
+    ExpEq,
+    #[serde(alias = "||=")]
+    OrEq,
+    #[serde(alias = "&&=")]
+    AndAndEq,
+    #[serde(alias = "??=")]
+    NullishEq,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct AssignmentProperty {
-    value: Pattern,
+    pub key: Expression,
+    pub value: Pattern,
     kind: String, // "init"
     method: bool, // false
+    pub shorthand: bool,
+    pub computed: bool,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -471,6 +530,8 @@ pub enum LogicalOperator {
     Or,
     #[serde(alias = "&&")]
     And,
+    #[serde(alias = "??")]
+    Nullish,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -489,140 +550,180 @@ pub struct ConditionalExpression {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct CallExpression {
-    pub callee: MemberIdentifier,
+    pub callee: Expression, // Expression | Super
     pub arguments: Vec<Expression>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "type")]
-pub enum MemberIdentifier {
-    MemberExpression(MemberExpression),
-    Expression(Expression),
-    Super(Super),
-    Identifier(Identifier),
-}
-
 #[derive(Deserialize, Clone, Debug)]
 pub struct NewExpression {
-    callee: Expression,
-    #[serde(flatten)]
-    arguments: Vec<NewExpressionArguments>,
-}
-
-#[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "type")]
-pub enum NewExpressionArguments {
-    Expression(Expression),
-    SpreadElement(SpreadElement),
+    pub callee: Expression,
+    pub arguments: Vec<Expression>, // Expression | SpreadElement
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct SequenceExpression {
-    expressions: Vec<Node>,
+    pub expressions: Vec<Node>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct SpreadElement {
-    argument: Expression,
+    pub argument: Expression,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct YieldExpression {
-    argument: Option<Expression>,
-    delegate: bool,
+    pub argument: Option<Expression>,
+    pub delegate: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct AwaitExpression {
+    pub argument: Expression,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TemplateLiteral {
-    quasis: Vec<TemplateElement>,
-    expressions: Vec<Expression>,
+    pub quasis: Vec<TemplateElement>,
+    pub expressions: Vec<Expression>,
+    // Tells apart the templates of one parse, and stays the same when the tree is cloned. A
+    // tagged template hands its tag the same strings array every time it is evaluated.
+    #[serde(skip, default = "next_template_site")]
+    pub site: usize,
+}
+
+fn next_template_site() -> usize {
+    static NEXT_SITE: AtomicUsize = AtomicUsize::new(0);
+    NEXT_SITE.fetch_add(1, Ordering::Relaxed)
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TaggedTemplateExpression {
-    tag: Expression,
-    quasi: TemplateLiteral,
+    pub tag: Expression,
+    pub quasi: TemplateLiteral,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TemplateElement {
     tail: bool,
-    #[serde(flatten)]
-    value: TemplateElementValue,
+    pub value: TemplateElementValue,
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "type")]
 pub struct TemplateElementValue {
-    cooked: String,
-    raw: String,
+    // None when the template is tagged and the text has an invalid escape sequence
+    pub cooked: Option<String>,
+    pub raw: String,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ObjectPattern {
-    properties: Vec<AssignmentProperty>,
+    pub properties: Vec<ObjectPatternProperty>,
+}
+
+// Properties of an object pattern are Property nodes whose value is a pattern
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum ObjectPatternProperty {
+    #[serde(rename = "Property")]
+    AssignmentProperty(AssignmentProperty),
+    RestElement(RestElement),
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ArrayPattern {
-    elements: Vec<Option<Pattern>>,
+    pub elements: Vec<Option<Pattern>>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct RestElement {
-    argument: Pattern,
+    pub argument: Pattern,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct AssignmentPattern {
-    left: Pattern,
-    right: Expression,
+    pub left: Pattern,
+    pub right: Expression,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Class {
-    id: Option<Identifier>,
-    super_class: Option<Expression>,
-    body: ClassBody,
+    pub id: Option<Identifier>,
+    #[serde(rename = "superClass")]
+    pub super_class: Option<Expression>,
+    pub body: ClassBody,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ClassBody {
-    body: Vec<MethodDefinition>,
+    pub body: Vec<Node>, // MethodDefinition | PropertyDefinition | StaticBlock
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct MethodDefinition {
-    key: Expression,
-    value: FunctionExpression,
-    kind: String, // "constructor" | "method" | "get" | "set"
-    coputed: bool,
-    r#static: bool,
+    pub key: Expression,
+    pub value: FunctionExpression,
+    pub kind: String, // "constructor" | "method" | "get" | "set"
+    pub computed: bool,
+    pub r#static: bool,
+}
+
+// A class field, its value is evaluated for every instance or once for static fields
+#[derive(Deserialize, Clone, Debug)]
+pub struct PropertyDefinition {
+    pub key: Expression, // Expression | PrivateIdentifier
+    pub value: Option<Expression>,
+    pub computed: bool,
+    pub r#static: bool,
+}
+
+// #name, in class element keys, member expressions and on the left of `in`
+#[derive(Deserialize, Clone, Debug)]
+pub struct PrivateIdentifier {
+    pub name: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct StaticBlock {
+    pub body: Vec<Node>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ClassDeclaration {
-    id: Identifier,
-    super_class: Option<Expression>,
-    body: ClassBody,
+    #[serde(deserialize_with = "de_declaration_name")]
+    pub id: Identifier,
+    #[serde(rename = "superClass")]
+    pub super_class: Option<Expression>,
+    pub body: ClassBody,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct MetaProperty {
-    meta: Identifier,
-    property: Identifier,
+    pub meta: Identifier,
+    pub property: Identifier,
+}
+
+// Only the function or class of an anonymous default export declaration has no name. It is bound
+// as default, which no other declaration can use as default is a reserved word.
+// https://tc39.es/ecma262/#sec-exports-static-semantics-boundnames
+fn de_declaration_name<'de, D>(deserializer: D) -> Result<Identifier, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let id: Option<Identifier> = Option::deserialize(deserializer)?;
+    Ok(id.unwrap_or_else(|| Identifier {
+        name: String::from("default"),
+    }))
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ModuleSpecifier {
-    local: Identifier,
+    pub local: Identifier,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ImportDeclaration {
-    #[serde(flatten)]
-    specifiers: ImportDeclarationSpecifiers,
-    source: Literal,
+    pub specifiers: Vec<ImportDeclarationSpecifiers>,
+    pub source: Literal,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -635,30 +736,38 @@ pub enum ImportDeclarationSpecifiers {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ImportSpecifier {
-    imported: Identifier,
+    pub imported: Identifier,
+    pub local: Identifier,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ExportNamedDeclaration {
-    declaration: Option<Declaration>,
-    specifiers: Vec<ExportSpecifier>,
-    source: Option<Literal>,
+    pub declaration: Option<Declaration>,
+    pub specifiers: Vec<ExportSpecifier>,
+    pub source: Option<Literal>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ExportSpecifier {
-    exported: Identifier,
+    pub local: Identifier,
+    pub exported: Identifier,
 }
 
+// The declaration is a function or class declaration, or else an expression
 #[derive(Deserialize, Clone, Debug)]
-#[serde(tag = "declaration")]
-pub enum ExportDefaultDeclaration {
-    FunctionDeclaration(FunctionDeclaration),
-    AnonymousDefaultExportedClassDeclaration,
-    AnonymousDefaultExportedFunctionDeclaration,
+pub struct ExportDefaultDeclaration {
+    pub declaration: Box<Node>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ExportAllDeclaration {
-    source: Literal,
+    pub source: Literal,
+    // Set by export * as name from "module"
+    pub exported: Option<Identifier>,
+}
+
+// import(specifier)
+#[derive(Deserialize, Clone, Debug)]
+pub struct ImportExpression {
+    pub source: Expression,
 }
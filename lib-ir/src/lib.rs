@@ -30,4 +30,21 @@ mod tests {
 		"#;
         let parsed_ast: ast::Node = serde_json::from_str(ast).expect("Unable to de-serialize AST");
     }
+
+    // acorn gives the value of a regular expression literal as an empty object, and oxc as null
+    #[test]
+    pub fn regexp_literal() {
+        let ast = r#"
+		{"type":"Literal","start":0,"end":8,"value":{},"raw":"/a\\/b/gi","regex":{"pattern":"a\\/b","flags":"gi"}}
+		"#;
+        let parsed_ast: ast::Node = serde_json::from_str(ast).expect("Unable to de-serialize AST");
+        let ast::NodeKind::Literal(literal) = parsed_ast.kind else {
+            unreachable!()
+        };
+        assert!(matches!(
+            literal.value,
+            ast::literal_value::LiteralValue::RegExp { pattern, flags }
+                if pattern == "a\\/b" && flags == "gi"
+        ));
+    }
 }
@@ -1,14 +1,34 @@
 import { Node, Options, parse as acornParse } from "acorn";
 
-export function parse(sourceCode: string): Node {
+export type SourceType = "script" | "module";
+
+export function parse(
+	sourceCode: string,
+	sourceType: SourceType = "script",
+	allowReturnOutsideFunction = false
+): Node {
 	const acornOptions: Options = {
 		ecmaVersion: "latest",
+		sourceType,
+		// Source locations are reported in runtime errors such as ReferenceError
+		locations: true,
+		allowReturnOutsideFunction,
 	};
 
-	const ast: Node = acornParse(sourceCode, acornOptions);
-
-	// Change top level program to a block statement
-	ast.type = "BlockStatement";
+	return acornParse(sourceCode, acornOptions);
+}
 
-	return ast;
+// Files are run as scripts unless they only parse as modules, for example because they import or
+// export, which is how node treats files it is not told the type of. Scripts are run as CommonJS
+// modules, wrapped in a function, so they may return at the top level.
+export function parseFile(sourceCode: string): Node {
+	try {
+		return parse(sourceCode, "script", true);
+	} catch (scriptError) {
+		try {
+			return parse(sourceCode, "module");
+		} catch {
+			throw scriptError;
+		}
+	}
 }
@@ -0,0 +1,210 @@
+use std::rc::Rc;
+
+use lib_ir::ast::literal_value::LiteralValue;
+
+use crate::closure::NativeFunction;
+use crate::evaluator::{get_iterator, get_member, EvaluatorError};
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::{InternalSlot, Object};
+use crate::realm::{intrinsics, ERROR_NAMES};
+
+use super::{argument, define_method};
+
+// Error and the native errors the evaluator throws. The errors it creates itself through
+// create_error_object inherit from the same prototypes, so that they can be told apart with
+// instanceof.
+// https://tc39.es/ecma262/#sec-error-objects
+pub fn create_error_constructors() -> Vec<(&'static str, EvaluatorValue)> {
+    let intrinsics = intrinsics();
+    let error_prototype = &intrinsics.error_prototypes["Error"];
+    define_method(error_prototype, "toString", to_string);
+    ERROR_NAMES
+        .iter()
+        .map(|&name| {
+            let prototype = &intrinsics.error_prototypes[name];
+            let constructor =
+                NativeFunction::new_constructor(name, move |this, args| error(name, this, args));
+            let constructor_object = Rc::clone(&constructor.object);
+            let constructor = EvaluatorValue::from(constructor);
+            constructor_object.borrow_mut().set(
+                String::from("prototype"),
+                EvaluatorValue::Object(Rc::clone(prototype)),
+            );
+            let mut prototype = prototype.borrow_mut();
+            prototype.set(String::from("constructor"), constructor.clone());
+            prototype.set(
+                String::from("name"),
+                EvaluatorValue::from(LiteralValue::from(name)),
+            );
+            prototype.set(
+                String::from("message"),
+                EvaluatorValue::from(LiteralValue::from("")),
+            );
+            (name, constructor)
+        })
+        .collect()
+}
+
+// Called with new the error is the new object, called as a function a new error is created. The
+// message and the cause from the options become own properties of the error. An AggregateError
+// takes the errors it holds first, as an iterable.
+// https://tc39.es/ecma262/#sec-error-message
+// https://tc39.es/ecma262/#sec-aggregate-error
+fn error(
+    name: &str,
+    this: EvaluatorValue,
+    args: Vec<EvaluatorValue>,
+) -> Result<EvaluatorValue, EvaluatorError> {
+    let prototype = Rc::clone(&intrinsics().error_prototypes[name]);
+    let error = match this {
+        EvaluatorValue::Object(obj)
+            if obj.borrow().internal_slot.is_none() && obj.borrow().inherits_from(&prototype) =>
+        {
+            obj
+        }
+        _ => Object::new(Some(prototype)),
+    };
+    let (errors, args) = match name {
+        "AggregateError" => (Some(argument(&args, 0)), args.get(1..).unwrap_or_default()),
+        _ => (None, &args[..]),
+    };
+    let message = argument(args, 0);
+    if !message.is_undefined() {
+        error.borrow_mut().set(
+            String::from("message"),
+            EvaluatorValue::from(LiteralValue::from(String::from(message))),
+        );
+    }
+    if let EvaluatorValue::Object(options) = argument(args, 1) {
+        if options.borrow().has_property("cause") {
+            let cause = get_member(EvaluatorValue::Object(options), String::from("cause"))?;
+            error.borrow_mut().set(String::from("cause"), cause);
+        }
+    }
+    if let Some(errors) = errors {
+        let mut iterator = get_iterator(errors)?;
+        let mut list = Vec::new();
+        while let Some(value) = iterator.step()? {
+            list.push(Some(value));
+        }
+        error
+            .borrow_mut()
+            .set(String::from("errors"), EvaluatorValue::from(list));
+    }
+    error.borrow_mut().internal_slot = Some(InternalSlot::Error);
+    Ok(EvaluatorValue::Object(error))
+}
+
+// The name defaults to "Error" and the message to the empty string
+// https://tc39.es/ecma262/#sec-error.prototype.tostring
+fn to_string(
+    this: EvaluatorValue,
+    _: Vec<EvaluatorValue>,
+) -> Result<EvaluatorValue, EvaluatorError> {
+    if !matches!(this, EvaluatorValue::Object(_)) {
+        return Err(EvaluatorError::InvalidType(String::from(
+            "Error.prototype.toString called on a value that is not an object",
+        )));
+    }
+    let name = match get_member(this.clone(), String::from("name"))? {
+        name if name.is_undefined() => String::from("Error"),
+        name => String::from(name),
+    };
+    let message = match get_member(this, String::from("message"))? {
+        message if message.is_undefined() => String::new(),
+        message => String::from(message),
+    };
+    Ok(EvaluatorValue::from(LiteralValue::from(error_description(
+        name, message,
+    ))))
+}
+
+// "name: message", or only one of the two when the other is empty
+pub fn error_description(name: String, message: String) -> String {
+    match (name.is_empty(), message.is_empty()) {
+        (_, true) => name,
+        (true, false) => message,
+        (false, false) => format!("{}: {}", name, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_completion, assert_throws};
+
+    #[test]
+    pub fn errors_are_instances_of_their_constructor() {
+        assert_completion(
+            r#"
+                let e = new RangeError("out of range");
+                `${e instanceof RangeError} ${e instanceof Error} ${e.name} ${e.message}`;
+            "#,
+            "true true RangeError out of range",
+        );
+        assert_completion(
+            r#"
+                let e = Error("no new");
+                `${e instanceof Error} ${e.message} ${e.name}`;
+            "#,
+            "true no new Error",
+        );
+    }
+
+    #[test]
+    pub fn evaluator_errors_inherit_from_the_constructors() {
+        assert_completion(
+            r#"
+                let caught = "";
+                try { null.x; } catch (e) { caught += " " + (e instanceof TypeError); }
+                try { undeclared; } catch (e) { caught += " " + (e instanceof ReferenceError); }
+                try { new RegExp("("); } catch (e) { caught += " " + (e instanceof SyntaxError); }
+                try { [].length = -1; } catch (e) { caught += " " + (e instanceof RangeError); }
+                caught;
+            "#,
+            " true true true true",
+        );
+    }
+
+    #[test]
+    pub fn error_to_string() {
+        assert_completion(
+            r#"
+                let e = new Error("e");
+                e.name = "";
+                `${String(new TypeError("bad"))}|${new Error().toString()}|${e}`;
+            "#,
+            "TypeError: bad|Error|e",
+        );
+    }
+
+    #[test]
+    pub fn error_cause_and_aggregate_errors() {
+        assert_completion(
+            r#"
+                let e = new AggregateError([1, 2], "many", { cause: "why" });
+                `${e.errors.length} ${e.message} ${e.cause} ${e instanceof Error}`;
+            "#,
+            "2 many why true",
+        );
+    }
+
+    #[test]
+    pub fn classes_extend_error() {
+        assert_completion(
+            r#"
+                class MyError extends Error {
+                    constructor(message) { super(message); this.name = "MyError"; }
+                }
+                try { throw new MyError("mine"); } catch (e) {
+                    `${e instanceof MyError} ${e instanceof Error} ${String(e)}`;
+                }
+            "#,
+            "true true MyError: mine",
+        );
+    }
+
+    #[test]
+    pub fn throwing_an_error_object() {
+        assert_throws("throw new TypeError('thrown');", "TypeError: thrown");
+    }
+}
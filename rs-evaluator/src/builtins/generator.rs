@@ -0,0 +1,44 @@
+use std::rc::Rc;
+
+use crate::evaluator::EvaluatorError;
+use crate::evaluator_value::EvaluatorValue;
+use crate::generator::{resume, Resumption};
+use crate::object::InternalSlot;
+use crate::realm::intrinsics;
+
+use super::{argument, define_method};
+
+// https://tc39.es/ecma262/#sec-properties-of-generator-prototype
+pub fn define_generator_methods() {
+    let prototype = &intrinsics().generator_prototype;
+    define_method(prototype, "next", |this, args| {
+        generator_resume(this, Resumption::Next(argument(&args, 0)), "next")
+    });
+    define_method(prototype, "return", |this, args| {
+        generator_resume(this, Resumption::Return(argument(&args, 0)), "return")
+    });
+    define_method(prototype, "throw", |this, args| {
+        generator_resume(this, Resumption::Throw(argument(&args, 0)), "throw")
+    });
+}
+
+fn generator_resume(
+    this: EvaluatorValue,
+    resumption: Resumption,
+    method: &str,
+) -> Result<EvaluatorValue, EvaluatorError> {
+    let generator = match &this {
+        EvaluatorValue::Object(obj) => match &obj.borrow().internal_slot {
+            Some(InternalSlot::Generator(generator)) => Some(Rc::clone(generator)),
+            _ => None,
+        },
+        _ => None,
+    };
+    match generator {
+        Some(generator) => resume(&generator, resumption),
+        None => Err(EvaluatorError::InvalidType(format!(
+            "Generator.prototype.{} called on incompatible receiver {}",
+            method, this
+        ))),
+    }
+}
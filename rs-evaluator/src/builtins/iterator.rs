@@ -0,0 +1,243 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use lib_ir::ast::{literal::Literal, literal_value::LiteralValue};
+
+use crate::closure::NativeFunction;
+use crate::constants::JS_UNDEFINED;
+use crate::evaluator::EvaluatorError;
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::{JsObject, Object};
+use crate::realm::intrinsics;
+
+use super::define_method;
+
+// Symbol.iterator is the key iterable objects keep their iterator method under
+// https://tc39.es/ecma262/#sec-symbol-constructor
+pub fn create_symbol_object() -> EvaluatorValue {
+    let symbol = Object::new(Some(Rc::clone(&intrinsics().object_prototype)));
+    symbol.borrow_mut().set(
+        String::from("iterator"),
+        EvaluatorValue::Symbol(intrinsics().symbol_iterator.clone()),
+    );
+    EvaluatorValue::Object(symbol)
+}
+
+// Arrays and strings iterate over their elements, and iterators over themselves
+pub fn define_iterator_methods() {
+    let intrinsics = intrinsics();
+    define_iterator_method(&intrinsics.iterator_prototype, |this, _| Ok(this));
+    define_iterator_method(&intrinsics.array_prototype, array_values);
+    define_iterator_method(&intrinsics.string_prototype, string_iterator);
+}
+
+fn define_iterator_method(
+    target: &JsObject,
+    function: impl Fn(EvaluatorValue, Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError>
+        + 'static,
+) {
+    target.borrow_mut().set(
+        intrinsics().symbol_iterator.clone(),
+        EvaluatorValue::from(NativeFunction::new("[Symbol.iterator]", function)),
+    );
+}
+
+// The array is read again on every step, elements pushed while iterating are visited too
+// https://tc39.es/ecma262/#sec-array.prototype.values
+fn array_values(
+    this: EvaluatorValue,
+    _: Vec<EvaluatorValue>,
+) -> Result<EvaluatorValue, EvaluatorError> {
+    let EvaluatorValue::Array(array) = this else {
+        return Err(EvaluatorError::InvalidType(String::from(
+            "Array.prototype[Symbol.iterator] called on a value that is not an array",
+        )));
+    };
+    let index = Cell::new(0);
+    Ok(create_iterator(move || {
+        let array = array.borrow();
+        if index.get() >= array.len() {
+            return None;
+        }
+        let element = array.get(index.get()).cloned();
+        index.set(index.get() + 1);
+        Some(element.unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED)))
+    }))
+}
+
+// https://tc39.es/ecma262/#sec-string.prototype-@@iterator
+fn string_iterator(
+    this: EvaluatorValue,
+    _: Vec<EvaluatorValue>,
+) -> Result<EvaluatorValue, EvaluatorError> {
+    let EvaluatorValue::Literal(Literal {
+        value: LiteralValue::String(s),
+    }) = this
+    else {
+        return Err(EvaluatorError::InvalidType(String::from(
+            "String.prototype[Symbol.iterator] called on a value that is not a string",
+        )));
+    };
+    let chars = RefCell::new(s.chars().collect::<Vec<char>>().into_iter());
+    Ok(create_iterator(move || {
+        let c = chars.borrow_mut().next()?;
+        Some(EvaluatorValue::from(LiteralValue::String(c.to_string())))
+    }))
+}
+
+// An iterator whose next method hands out the values produced by `step` until it returns None,
+// after which it stays done
+pub fn create_iterator(step: impl Fn() -> Option<EvaluatorValue> + 'static) -> EvaluatorValue {
+    let iterator = Object::new(Some(Rc::clone(&intrinsics().iterator_prototype)));
+    let done = Cell::new(false);
+    define_method(&iterator, "next", move |_, _| {
+        let value = if done.get() { None } else { step() };
+        match value {
+            Some(value) => Ok(create_iter_result_object(value, false)),
+            None => {
+                done.set(true);
+                Ok(create_iter_result_object(
+                    EvaluatorValue::from(JS_UNDEFINED),
+                    true,
+                ))
+            }
+        }
+    });
+    EvaluatorValue::Object(iterator)
+}
+
+// https://tc39.es/ecma262/#sec-createiterresultobject
+pub fn create_iter_result_object(value: EvaluatorValue, done: bool) -> EvaluatorValue {
+    let result = Object::new(Some(Rc::clone(&intrinsics().object_prototype)));
+    result.borrow_mut().set(String::from("value"), value);
+    result.borrow_mut().set(
+        String::from("done"),
+        EvaluatorValue::from(LiteralValue::from(done)),
+    );
+    EvaluatorValue::Object(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_completion, assert_throws};
+
+    #[test]
+    pub fn spreads_arguments_into_calls() {
+        assert_completion(
+            r#"
+                function sum(a, b, c) { return a + b + c; }
+                function count(...args) { return args.length; }
+                const xs = [1, 2];
+                sum(...xs, 3) + " " + count(..."ab", 0);
+            "#,
+            "6 3",
+        );
+    }
+
+    #[test]
+    pub fn rest_parameter_collects_remaining_arguments() {
+        assert_completion(
+            r#"
+                function f(first, ...rest) { return first + " " + rest; }
+                function g(...none) { return none.length; }
+                f(1, 2, 3) + " " + g();
+            "#,
+            "1 2,3 0",
+        );
+    }
+
+    #[test]
+    pub fn spreads_iterables_into_array_literals() {
+        assert_completion(
+            r#"
+                const a = [1, 2];
+                const b = [...a, 3, ..."ab", , 4];
+                b + " " + b.length;
+            "#,
+            "1,2,3,a,b,,4 7",
+        );
+    }
+
+    #[test]
+    pub fn spreads_objects_into_object_literals() {
+        assert_completion(
+            r#"
+                const base = { a: 1, b: 2 };
+                const copy = { ...base, b: 3, ...null, ...undefined };
+                const later = { b: 1, ...base };
+                copy.a + " " + copy.b + " " + later.b + " " + (copy !== base);
+            "#,
+            "1 3 2 true",
+        );
+    }
+
+    #[test]
+    pub fn spread_uses_the_iteration_protocol() {
+        assert_completion(
+            r#"
+                let state = "open";
+                const counter = {
+                    [Symbol.iterator]() {
+                        let i = 0;
+                        return {
+                            next() {
+                                return i < 3 ? { value: i++, done: false } : { value: undefined, done: true };
+                            },
+                            return() { state = "closed"; return {}; },
+                        };
+                    },
+                };
+                const all = [...counter];
+                const [first] = counter;
+                all + " " + state;
+            "#,
+            "0,1,2 closed",
+        );
+    }
+
+    #[test]
+    pub fn spreading_a_non_iterable_throws_type_error() {
+        assert_throws(
+            r#"
+                const n = 1;
+                [...n];
+            "#,
+            "is not iterable",
+        );
+    }
+
+    #[test]
+    pub fn symbol_iterator_is_a_symbol() {
+        assert_completion(
+            r#"
+                let o = { [Symbol.iterator]: 1 };
+                `${typeof Symbol.iterator} ${Symbol.iterator in o} ${"Symbol(Symbol.iterator)" in o}`;
+            "#,
+            "symbol true false",
+        );
+    }
+
+    #[test]
+    pub fn string_keys_do_not_make_an_object_iterable() {
+        assert_throws(
+            r#"
+                const o = { "@@iterator": function () { return [1][Symbol.iterator](); } };
+                [...o];
+            "#,
+            "is not iterable",
+        );
+    }
+
+    #[test]
+    pub fn objects_and_classes_define_symbol_iterator() {
+        assert_completion(
+            r#"
+                const o = { *[Symbol.iterator]() { yield 1; yield 2; } };
+                class C { *[Symbol.iterator]() { yield 3; } }
+                const [x] = new C();
+                `${[...o]} ${x}`;
+            "#,
+            "1,2 3",
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::closure::NativeFunction;
+use crate::constants::JS_UNDEFINED;
+use crate::evaluator::EvaluatorError;
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::JsObject;
+
+pub mod error;
+mod generator;
+pub mod iterator;
+mod object;
+mod promise;
+mod regexp;
+mod string;
+mod timers;
+
+// The constructors bound on the global object, in addition to its value properties
+// https://262.ecma-international.org/5.1/#sec-15.1.4
+pub fn global_properties() -> Vec<(&'static str, EvaluatorValue)> {
+    iterator::define_iterator_methods();
+    generator::define_generator_methods();
+    let mut properties = vec![
+        ("Object", object::create_object_constructor()),
+        ("Promise", promise::create_promise_constructor()),
+        ("RegExp", regexp::create_regexp_constructor()),
+        ("String", string::create_string_constructor()),
+        ("Symbol", iterator::create_symbol_object()),
+    ];
+    properties.extend(error::create_error_constructors());
+    properties.extend(timers::timer_functions());
+    properties
+}
+
+fn define_method(
+    target: &JsObject,
+    name: &str,
+    function: impl Fn(EvaluatorValue, Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError>
+        + 'static,
+) {
+    target.borrow_mut().set(
+        String::from(name),
+        EvaluatorValue::from(NativeFunction::new(name, function)),
+    );
+}
+
+// Arguments that were not passed are undefined
+pub fn argument(args: &[EvaluatorValue], index: usize) -> EvaluatorValue {
+    args.get(index)
+        .cloned()
+        .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED))
+}
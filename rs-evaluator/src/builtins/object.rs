@@ -0,0 +1,82 @@
+use std::rc::Rc;
+
+use lib_ir::ast::{literal::Literal, literal_value::LiteralValue};
+
+use crate::closure::NativeFunction;
+use crate::constants::JS_NULL;
+use crate::evaluator::EvaluatorError;
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::Object;
+use crate::realm::intrinsics;
+
+use super::{argument, define_method};
+
+// https://262.ecma-international.org/5.1/#sec-15.2
+pub fn create_object_constructor() -> EvaluatorValue {
+    let intrinsics = intrinsics();
+    let constructor = NativeFunction::new_constructor("Object", object);
+    let constructor_object = Rc::clone(&constructor.object);
+    let constructor = EvaluatorValue::from(constructor);
+
+    constructor_object.borrow_mut().set(
+        String::from("prototype"),
+        EvaluatorValue::Object(Rc::clone(&intrinsics.object_prototype)),
+    );
+    intrinsics
+        .object_prototype
+        .borrow_mut()
+        .set(String::from("constructor"), constructor.clone());
+    define_method(&constructor_object, "create", create);
+    define_method(&constructor_object, "getPrototypeOf", get_prototype_of);
+    constructor
+}
+
+// Objects are returned as they are, there are no wrapper objects for primitives yet so any other
+// value produces a new empty object
+// https://262.ecma-international.org/5.1/#sec-15.2.1.1
+fn object(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError> {
+    match argument(&args, 0) {
+        EvaluatorValue::Literal(_) => Ok(EvaluatorValue::Object(Object::new(Some(Rc::clone(
+            &intrinsics().object_prototype,
+        ))))),
+        value => Ok(value),
+    }
+}
+
+// https://262.ecma-international.org/5.1/#sec-15.2.3.5
+fn create(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError> {
+    let prototype = match argument(&args, 0) {
+        EvaluatorValue::Literal(Literal {
+            value: LiteralValue::Null,
+        }) => None,
+        EvaluatorValue::Object(obj) => Some(obj),
+        value @ (EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_)) => {
+            value.function_object()
+        }
+        value => {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Object prototype may only be an Object or null: {}",
+                value
+            )))
+        }
+    };
+    Ok(EvaluatorValue::Object(Object::new(prototype)))
+}
+
+// https://262.ecma-international.org/6.0/#sec-object.getprototypeof
+fn get_prototype_of(
+    _: EvaluatorValue,
+    args: Vec<EvaluatorValue>,
+) -> Result<EvaluatorValue, EvaluatorError> {
+    match argument(&args, 0) {
+        EvaluatorValue::Literal(Literal {
+            value: LiteralValue::Undefined | LiteralValue::Null,
+        }) => Err(EvaluatorError::InvalidType(String::from(
+            "Cannot convert undefined or null to object",
+        ))),
+        value => Ok(value
+            .prototype()
+            .map(EvaluatorValue::Object)
+            .unwrap_or_else(|| EvaluatorValue::from(JS_NULL))),
+    }
+}
@@ -0,0 +1,430 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use lib_ir::ast::literal_value::LiteralValue;
+
+use crate::closure::NativeFunction;
+use crate::constants::JS_UNDEFINED;
+use crate::evaluator::{
+    call_value, create_error_object, get_iterator, get_member, EvaluatorError, IteratorRecord,
+};
+use crate::evaluator_value::{Array, EvaluatorValue, JsArray};
+use crate::object::Object;
+use crate::promise::{
+    as_promise, create_resolving_functions, initialize_promise, new_promise_capability,
+    perform_then, promise_resolve, Capability,
+};
+use crate::realm::intrinsics;
+
+use super::{argument, define_method};
+
+type PromiseResult = Result<EvaluatorValue, EvaluatorError>;
+
+// https://tc39.es/ecma262/#sec-promise-constructor
+pub fn create_promise_constructor() -> EvaluatorValue {
+    let intrinsics = intrinsics();
+    let constructor = NativeFunction::new_constructor("Promise", promise);
+    let constructor_object = Rc::clone(&constructor.object);
+    let constructor = EvaluatorValue::from(constructor);
+
+    constructor_object.borrow_mut().set(
+        String::from("prototype"),
+        EvaluatorValue::Object(Rc::clone(&intrinsics.promise_prototype)),
+    );
+    intrinsics
+        .promise_prototype
+        .borrow_mut()
+        .set(String::from("constructor"), constructor.clone());
+    define_method(&intrinsics.promise_prototype, "then", then);
+    define_method(&intrinsics.promise_prototype, "catch", catch);
+    define_method(&intrinsics.promise_prototype, "finally", finally);
+    define_method(&constructor_object, "resolve", |_, args| {
+        promise_resolve(argument(&args, 0))
+    });
+    define_method(&constructor_object, "reject", |_, args| {
+        let capability = new_promise_capability();
+        capability.reject(argument(&args, 0))?;
+        Ok(capability.promise)
+    });
+    define_method(&constructor_object, "all", all);
+    define_method(&constructor_object, "allSettled", all_settled);
+    define_method(&constructor_object, "any", any);
+    define_method(&constructor_object, "race", race);
+    constructor
+}
+
+// The executor is called right away with the functions that settle the new promise, an exception
+// it throws rejects the promise
+// https://tc39.es/ecma262/#sec-promise-executor
+fn promise(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    let object = match &this {
+        EvaluatorValue::Object(obj) if obj.borrow().internal_slot.is_none() => Rc::clone(obj),
+        _ => {
+            return Err(EvaluatorError::InvalidType(String::from(
+                "Promise constructor cannot be invoked without 'new'",
+            )))
+        }
+    };
+    let executor = argument(&args, 0);
+    if !executor.is_callable() {
+        return Err(EvaluatorError::InvalidType(format!(
+            "Promise resolver {} is not a function",
+            executor
+        )));
+    }
+    initialize_promise(&object);
+    let (resolve, reject) = create_resolving_functions(&object);
+    if let Err(e) = call_value(
+        executor,
+        EvaluatorValue::from(JS_UNDEFINED),
+        vec![resolve, reject.clone()],
+    ) {
+        call_value(
+            reject,
+            EvaluatorValue::from(JS_UNDEFINED),
+            vec![e.into_exception()?],
+        )?;
+    }
+    Ok(this)
+}
+
+// https://tc39.es/ecma262/#sec-promise.prototype.then
+fn then(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    let Some(promise) = as_promise(&this) else {
+        return Err(EvaluatorError::InvalidType(format!(
+            "Method Promise.prototype.then called on incompatible receiver {}",
+            this
+        )));
+    };
+    let capability = new_promise_capability();
+    perform_then(
+        &promise,
+        argument(&args, 0),
+        argument(&args, 1),
+        Some(capability.clone()),
+    );
+    Ok(capability.promise)
+}
+
+// Calls the then method of a value the way user code would, so thenables that are not promises
+// work too
+fn invoke_then(value: EvaluatorValue, handlers: Vec<EvaluatorValue>) -> PromiseResult {
+    let then = get_member(value.clone(), String::from("then"))?;
+    call_value(then, value, handlers)
+}
+
+// https://tc39.es/ecma262/#sec-promise.prototype.catch
+fn catch(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    invoke_then(
+        this,
+        vec![EvaluatorValue::from(JS_UNDEFINED), argument(&args, 0)],
+    )
+}
+
+// The callback runs whichever way the promise settles, and the settled value or reason passes
+// through it unchanged unless the callback itself throws or rejects
+// https://tc39.es/ecma262/#sec-promise.prototype.finally
+fn finally(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    let on_finally = argument(&args, 0);
+    if !on_finally.is_callable() {
+        return invoke_then(this, vec![on_finally.clone(), on_finally]);
+    }
+    let handler = |rejected: bool| {
+        let on_finally = on_finally.clone();
+        EvaluatorValue::from(NativeFunction::new("", move |_, args| {
+            let value = argument(&args, 0);
+            let result = call_value(
+                on_finally.clone(),
+                EvaluatorValue::from(JS_UNDEFINED),
+                vec![],
+            )?;
+            let pass_on = NativeFunction::new("", move |_, _| match rejected {
+                true => Err(EvaluatorError::Throw(value.clone())),
+                false => Ok(value.clone()),
+            });
+            invoke_then(
+                promise_resolve(result)?,
+                vec![EvaluatorValue::from(pass_on)],
+            )
+        }))
+    };
+    invoke_then(this, vec![handler(false), handler(true)])
+}
+
+// Runs one of the combinators over the values of an iterable. Exceptions thrown while iterating
+// reject the returned promise instead of propagating.
+// https://tc39.es/ecma262/#sec-promise.all
+fn combine(
+    iterable: EvaluatorValue,
+    combinator: impl FnOnce(&mut IteratorRecord, &Capability) -> Result<(), EvaluatorError>,
+) -> PromiseResult {
+    let capability = new_promise_capability();
+    let result = get_iterator(iterable).and_then(|mut iterator| {
+        let result = combinator(&mut iterator, &capability);
+        if result.is_err() {
+            let _ = iterator.close();
+        }
+        result
+    });
+    if let Err(e) = result {
+        capability.reject(e.into_exception()?)?;
+    }
+    Ok(capability.promise)
+}
+
+// The values settled so far, and how many promises are yet to settle. The count starts at one so
+// that it cannot reach zero before the iterable has been read to the end.
+struct Settlements {
+    values: JsArray,
+    remaining: Cell<usize>,
+}
+
+impl Settlements {
+    fn new() -> Rc<Self> {
+        Rc::new(Settlements {
+            values: Rc::new(RefCell::new(Array::from(vec![]))),
+            remaining: Cell::new(1),
+        })
+    }
+
+    // Reserves a place for the next value, returning its index
+    fn push(&self) -> usize {
+        let mut values = self.values.borrow_mut();
+        let index = values.len();
+        values.set(index, EvaluatorValue::from(JS_UNDEFINED));
+        self.remaining.set(self.remaining.get() + 1);
+        index
+    }
+
+    // Records a value, returning whether it was the last one
+    fn settle(&self, index: usize, value: EvaluatorValue) -> bool {
+        self.values.borrow_mut().set(index, value);
+        self.finish_one()
+    }
+
+    fn finish_one(&self) -> bool {
+        self.remaining.set(self.remaining.get() - 1);
+        self.remaining.get() == 0
+    }
+
+    fn values(&self) -> EvaluatorValue {
+        EvaluatorValue::Array(Rc::clone(&self.values))
+    }
+}
+
+// A function that records its argument in `settlements` the first time it is called, and calls
+// `done` with all of the values once every promise has settled
+fn element_function(
+    settlements: &Rc<Settlements>,
+    index: usize,
+    done: impl Fn(EvaluatorValue) -> Result<(), EvaluatorError> + 'static,
+) -> EvaluatorValue {
+    let settlements = Rc::clone(settlements);
+    let already_called = Cell::new(false);
+    EvaluatorValue::from(NativeFunction::new("", move |_, args| {
+        if !already_called.replace(true) && settlements.settle(index, argument(&args, 0)) {
+            done(settlements.values())?;
+        }
+        Ok(EvaluatorValue::from(JS_UNDEFINED))
+    }))
+}
+
+// Fulfills with the values of all of the promises in order, or rejects with the first rejection
+// https://tc39.es/ecma262/#sec-performpromiseall
+fn all(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    combine(argument(&args, 0), |iterator, capability| {
+        let settlements = Settlements::new();
+        while let Some(value) = iterator.step()? {
+            let index = settlements.push();
+            let resolve = capability.clone();
+            let on_fulfilled = element_function(&settlements, index, move |v| resolve.resolve(v));
+            invoke_then(
+                promise_resolve(value)?,
+                vec![on_fulfilled, capability.reject.clone()],
+            )?;
+        }
+        if settlements.finish_one() {
+            capability.resolve(settlements.values())?;
+        }
+        Ok(())
+    })
+}
+
+// Fulfills once every promise has settled, with an object describing the outcome of each
+// https://tc39.es/ecma262/#sec-performpromiseallsettled
+fn all_settled(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    combine(argument(&args, 0), |iterator, capability| {
+        let settlements = Settlements::new();
+        while let Some(value) = iterator.step()? {
+            let index = settlements.push();
+            // Whichever of the two functions is called first settles the element
+            let already_called = Rc::new(Cell::new(false));
+            let handler = |status: &'static str, key: &'static str| {
+                let resolve = capability.clone();
+                let already_called = Rc::clone(&already_called);
+                let settlements = Rc::clone(&settlements);
+                EvaluatorValue::from(NativeFunction::new("", move |_, args| {
+                    if already_called.replace(true) {
+                        return Ok(EvaluatorValue::from(JS_UNDEFINED));
+                    }
+                    let outcome = settled_outcome(status, key, argument(&args, 0));
+                    if settlements.settle(index, outcome) {
+                        resolve.resolve(settlements.values())?;
+                    }
+                    Ok(EvaluatorValue::from(JS_UNDEFINED))
+                }))
+            };
+            invoke_then(
+                promise_resolve(value)?,
+                vec![handler("fulfilled", "value"), handler("rejected", "reason")],
+            )?;
+        }
+        if settlements.finish_one() {
+            capability.resolve(settlements.values())?;
+        }
+        Ok(())
+    })
+}
+
+fn settled_outcome(status: &str, key: &str, value: EvaluatorValue) -> EvaluatorValue {
+    let outcome = Object::new(Some(Rc::clone(&intrinsics().object_prototype)));
+    outcome.borrow_mut().set(
+        String::from("status"),
+        EvaluatorValue::from(LiteralValue::from(status)),
+    );
+    outcome.borrow_mut().set(String::from(key), value);
+    EvaluatorValue::Object(outcome)
+}
+
+// Fulfills with the first promise to fulfill, or rejects with an AggregateError holding every
+// reason once all of them have rejected
+// https://tc39.es/ecma262/#sec-performpromiseany
+fn any(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    combine(argument(&args, 0), |iterator, capability| {
+        let errors = Settlements::new();
+        while let Some(value) = iterator.step()? {
+            let index = errors.push();
+            let reject = capability.clone();
+            let on_rejected = element_function(&errors, index, move |errors| {
+                reject.reject(create_aggregate_error(errors))
+            });
+            invoke_then(
+                promise_resolve(value)?,
+                vec![capability.resolve.clone(), on_rejected],
+            )?;
+        }
+        if errors.finish_one() {
+            capability.reject(create_aggregate_error(errors.values()))?;
+        }
+        Ok(())
+    })
+}
+
+fn create_aggregate_error(errors: EvaluatorValue) -> EvaluatorValue {
+    let error = create_error_object("AggregateError", String::from("All promises were rejected"));
+    if let EvaluatorValue::Object(obj) = &error {
+        obj.borrow_mut().set(String::from("errors"), errors);
+    }
+    error
+}
+
+// Settles the same way as the first promise to settle
+// https://tc39.es/ecma262/#sec-performpromiserace
+fn race(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> PromiseResult {
+    combine(argument(&args, 0), |iterator, capability| {
+        while let Some(value) = iterator.step()? {
+            invoke_then(
+                promise_resolve(value)?,
+                vec![capability.resolve.clone(), capability.reject.clone()],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_completion, assert_throws};
+
+    #[test]
+    pub fn microtask_order() {
+        assert_completion(
+            r#"
+                let order = "";
+                async function a1() {
+                    order += "a1s,";
+                    await a2();
+                    order += "a1e,";
+                }
+                async function a2() { order += "a2,"; }
+                order += "start,";
+                Promise.resolve().then(() => { order += "p1,"; }).then(() => { order += "p2,"; });
+                a1();
+                new Promise(r => { order += "exec,"; r(); }).then(() => { order += "p3,"; });
+                order += "end,";
+                Promise.resolve().then(() => 0).then(() => 0).then(() => order);
+            "#,
+            "start,a1s,a2,exec,end,p1,a1e,p3,p2,",
+        );
+    }
+
+    #[test]
+    pub fn promise_then_catch_finally() {
+        assert_completion(
+            r#"
+                let s = "";
+                let cleaned = "";
+                new Promise(resolve => resolve(5)).then(v => v * 2).then(v => { s += v + ";"; throw "bad"; }).catch(e => { s += e + ";"; return 7; }).finally(() => { cleaned = "finally kept "; }).then(v => s + cleaned + v);
+            "#,
+            "10;bad;finally kept 7",
+        );
+    }
+
+    #[test]
+    pub fn promise_any() {
+        assert_completion(
+            r#"
+                async function main() {
+                    let s = "";
+                    try {
+                        await Promise.any([Promise.reject(1), Promise.reject(2)]);
+                    } catch (e) { s += e.name + ":" + e.errors[1]; }
+                    return s + "|" + await Promise.any([Promise.reject(1), 3]);
+                }
+                main();
+            "#,
+            "AggregateError:2|3",
+        );
+    }
+
+    #[test]
+    pub fn promise_all_and_race() {
+        assert_completion(
+            r#"
+                const later = v => new Promise(resolve => resolve(v));
+                async function main() {
+                    const all = await Promise.all([1, later(2), (async () => 3)()]);
+                    const settled = await Promise.allSettled([Promise.reject("x")]);
+                    const raced = await Promise.race([later("first"), later("second")]);
+                    return all[0] + "," + all[1] + "," + all[2] + "|" + settled[0].status + ":" + settled[0].reason + "|" + raced;
+                }
+                main();
+            "#,
+            "1,2,3|rejected:x|first",
+        );
+    }
+
+    #[test]
+    pub fn unhandled_rejection_of_program() {
+        assert_throws(
+            r#"
+                async function check(x) {
+                    if (typeof x !== "number") throw "not a number";
+                    return x;
+                }
+                check("a");
+            "#,
+            "Uncaught not a number",
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::rc::Rc;
+
+use lib_ir::ast::literal_value::LiteralValue;
+
+use crate::closure::NativeFunction;
+use crate::evaluator::EvaluatorError;
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::{JsObject, Object};
+use crate::realm::intrinsics;
+use crate::regexp::{as_regexp, initialize_regexp, regexp_builtin_exec, regexp_exec, RegExp};
+
+use super::{argument, define_method};
+
+type RegExpResult = Result<EvaluatorValue, EvaluatorError>;
+
+// https://tc39.es/ecma262/#sec-regexp-constructor
+pub fn create_regexp_constructor() -> EvaluatorValue {
+    let intrinsics = intrinsics();
+    let prototype = &intrinsics.regexp_prototype;
+    let constructor = NativeFunction::new_constructor("RegExp", regexp);
+    let constructor_object = Rc::clone(&constructor.object);
+    let constructor = EvaluatorValue::from(constructor);
+
+    constructor_object.borrow_mut().set(
+        String::from("prototype"),
+        EvaluatorValue::Object(Rc::clone(prototype)),
+    );
+    prototype
+        .borrow_mut()
+        .set(String::from("constructor"), constructor.clone());
+    define_method(prototype, "exec", |this, args| {
+        let (object, regexp) = this_regexp(&this, "exec")?;
+        regexp_exec(&object, &regexp, &String::from(argument(&args, 0)))
+    });
+    define_method(prototype, "test", |this, args| {
+        let (object, regexp) = this_regexp(&this, "test")?;
+        let found = regexp_builtin_exec(&object, &regexp, &String::from(argument(&args, 0)))?;
+        Ok(EvaluatorValue::from(LiteralValue::from(found.is_some())))
+    });
+    define_method(prototype, "toString", |this, _| {
+        this_regexp(&this, "toString")?;
+        Ok(EvaluatorValue::from(LiteralValue::from(String::from(this))))
+    });
+    define_getter(prototype, "source", |regexp| {
+        LiteralValue::from(regexp.source.as_str())
+    });
+    define_getter(prototype, "flags", |regexp| {
+        LiteralValue::from(regexp.flags.as_str())
+    });
+    let flags = [
+        ("hasIndices", 'd'),
+        ("global", 'g'),
+        ("ignoreCase", 'i'),
+        ("multiline", 'm'),
+        ("dotAll", 's'),
+        ("unicode", 'u'),
+        ("unicodeSets", 'v'),
+        ("sticky", 'y'),
+    ];
+    for (name, flag) in flags {
+        define_getter(prototype, name, move |regexp| {
+            LiteralValue::from(regexp.has_flag(flag))
+        });
+    }
+    constructor
+}
+
+// Called with new the regular expression is the new object, called as a function a new object
+// is created for it. A RegExp passed as the pattern is copied, with new flags if they are given.
+// https://tc39.es/ecma262/#sec-regexp-pattern-flags
+fn regexp(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> RegExpResult {
+    let (pattern, flags) = (argument(&args, 0), argument(&args, 1));
+    let flags = match flags.is_undefined() {
+        true => None,
+        false => Some(String::from(flags)),
+    };
+    let (pattern, flags) = match as_regexp(&pattern) {
+        Some((_, regexp)) => (
+            regexp.source.clone(),
+            flags.unwrap_or_else(|| regexp.flags.clone()),
+        ),
+        None if pattern.is_undefined() => (String::new(), flags.unwrap_or_default()),
+        None => (String::from(pattern), flags.unwrap_or_default()),
+    };
+    let object = match &this {
+        EvaluatorValue::Object(obj) if obj.borrow().internal_slot.is_none() => Rc::clone(obj),
+        _ => Object::new(Some(Rc::clone(&intrinsics().regexp_prototype))),
+    };
+    initialize_regexp(&object, &pattern, &flags)?;
+    Ok(EvaluatorValue::Object(object))
+}
+
+fn this_regexp(
+    this: &EvaluatorValue,
+    method: &str,
+) -> Result<(JsObject, Rc<RegExp>), EvaluatorError> {
+    as_regexp(this).ok_or_else(|| {
+        EvaluatorError::InvalidType(format!(
+            "RegExp.prototype.{} called on incompatible receiver {}",
+            method, this
+        ))
+    })
+}
+
+// The flags and source are read through getters on the prototype, as they are in the spec
+// https://tc39.es/ecma262/#sec-get-regexp.prototype.source
+fn define_getter(
+    target: &JsObject,
+    name: &'static str,
+    getter: impl Fn(&RegExp) -> LiteralValue + 'static,
+) {
+    let function = NativeFunction::new(name, move |this, _| {
+        let (_, regexp) = this_regexp(&this, name)?;
+        Ok(EvaluatorValue::from(getter(&regexp)))
+    });
+    target.borrow_mut().define_accessor(
+        String::from(name),
+        Some(EvaluatorValue::from(function)),
+        None,
+    );
+}
@@ -0,0 +1,458 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+use lib_ir::ast::{literal::JsNumber, literal_value::LiteralValue};
+
+use crate::closure::NativeFunction;
+use crate::constants::{JS_NULL, JS_UNDEFINED};
+use crate::evaluator::{call_value, get_member, EvaluatorError};
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::JsObject;
+use crate::realm::intrinsics;
+use crate::regexp::{
+    as_regexp, byte_offset, capture_value, char_index, last_index, match_array, named_groups,
+    regexp_builtin_exec, regexp_create, regexp_exec, set_last_index, RegExp,
+};
+
+use super::iterator::create_iterator;
+use super::{argument, define_method};
+
+type StringResult = Result<EvaluatorValue, EvaluatorError>;
+
+// There are no String objects yet, calling String converts its argument to a string
+// https://tc39.es/ecma262/#sec-string-constructor
+pub fn create_string_constructor() -> EvaluatorValue {
+    let intrinsics = intrinsics();
+    let constructor = NativeFunction::new_constructor("String", string);
+    let constructor_object = Rc::clone(&constructor.object);
+    let constructor = EvaluatorValue::from(constructor);
+
+    constructor_object.borrow_mut().set(
+        String::from("prototype"),
+        EvaluatorValue::Object(Rc::clone(&intrinsics.string_prototype)),
+    );
+    intrinsics
+        .string_prototype
+        .borrow_mut()
+        .set(String::from("constructor"), constructor.clone());
+    define_method(&constructor_object, "raw", raw);
+    let prototype = &intrinsics.string_prototype;
+    define_method(prototype, "match", string_match);
+    define_method(prototype, "matchAll", match_all);
+    define_method(prototype, "replace", replace);
+    define_method(prototype, "search", search);
+    define_method(prototype, "split", split);
+    constructor
+}
+
+fn string(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError> {
+    let value = match args.first() {
+        Some(value) => String::from(value.clone()),
+        None => String::new(),
+    };
+    Ok(EvaluatorValue::from(LiteralValue::String(value)))
+}
+
+// Joins the raw strings of a template with the substitutions between them, String.raw`\n` is
+// a backslash followed by an n
+// https://tc39.es/ecma262/#sec-string.raw
+fn raw(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError> {
+    let raw = get_member(argument(&args, 0), String::from("raw"))?;
+    let length = match get_member(raw.clone(), String::from("length"))? {
+        EvaluatorValue::Literal(literal) => match literal.value.into() {
+            JsNumber::Number(n) if n > 0.0 => n as usize,
+            _ => 0,
+        },
+        _ => 0,
+    };
+    let mut result = String::new();
+    for index in 0..length {
+        result.push_str(&String::from(get_member(raw.clone(), index.to_string())?));
+        if index + 1 < length {
+            if let Some(substitution) = args.get(index + 1) {
+                result.push_str(&String::from(substitution.clone()));
+            }
+        }
+    }
+    Ok(EvaluatorValue::from(LiteralValue::String(result)))
+}
+
+// A regular expression is used as it is, anything else is the pattern of a new one
+fn to_regexp(value: EvaluatorValue, flags: &str) -> Result<(JsObject, Rc<RegExp>), EvaluatorError> {
+    if let Some(regexp) = as_regexp(&value) {
+        return Ok(regexp);
+    }
+    let pattern = match value.is_undefined() {
+        true => String::new(),
+        false => String::from(value),
+    };
+    let regexp = regexp_create(&pattern, flags)?;
+    Ok(as_regexp(&regexp).expect("regexp_create returns a RegExp object"))
+}
+
+// Without the g flag the result is the match array of exec, with it an array of every matched
+// string, or null when nothing matched
+// https://tc39.es/ecma262/#sec-string.prototype.match
+fn string_match(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> StringResult {
+    let s = String::from(this);
+    let (object, regexp) = to_regexp(argument(&args, 0), "")?;
+    if !regexp.has_flag('g') {
+        return regexp_exec(&object, &regexp, &s);
+    }
+    let matches = global_matches(&object, &regexp, &s)?
+        .iter()
+        .map(|found| Some(EvaluatorValue::from(LiteralValue::from(found.as_str(&s)))))
+        .collect::<Vec<_>>();
+    match matches.is_empty() {
+        true => Ok(EvaluatorValue::from(JS_NULL)),
+        false => Ok(EvaluatorValue::from(matches)),
+    }
+}
+
+// Every match from the start of the string, a match of the empty string moves lastIndex on by a
+// character so that the next one is found after it
+// https://tc39.es/ecma262/#sec-regexp.prototype-@@match
+fn global_matches(
+    object: &JsObject,
+    regexp: &RegExp,
+    s: &str,
+) -> Result<Vec<regress::Match>, EvaluatorError> {
+    set_last_index(object, 0);
+    let mut matches = Vec::new();
+    while let Some(found) = regexp_builtin_exec(object, regexp, s)? {
+        if found.range().is_empty() {
+            set_last_index(object, last_index(object)? + 1);
+        }
+        matches.push(found);
+    }
+    Ok(matches)
+}
+
+// An iterator over the match arrays of every match. The regular expression is copied, so that
+// iterating does not move the lastIndex of the one that was passed.
+// https://tc39.es/ecma262/#sec-string.prototype.matchall
+fn match_all(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> StringResult {
+    let s = String::from(this);
+    let (object, regexp) = match as_regexp(&argument(&args, 0)) {
+        Some((_, regexp)) if !regexp.has_flag('g') => {
+            return Err(EvaluatorError::InvalidType(String::from(
+                "String.prototype.matchAll called with a non-global RegExp argument",
+            )))
+        }
+        Some((object, regexp)) => {
+            let copy = regexp_create(&regexp.source, &regexp.flags)?;
+            let (copy, regexp) = as_regexp(&copy).expect("regexp_create returns a RegExp object");
+            set_last_index(&copy, last_index(&object)?);
+            (copy, regexp)
+        }
+        None => to_regexp(argument(&args, 0), "g")?,
+    };
+    Ok(create_iterator(move || {
+        let found = regexp_builtin_exec(&object, &regexp, &s).ok()??;
+        if found.range().is_empty() {
+            set_last_index(&object, last_index(&object).ok()? + 1);
+        }
+        Some(match_array(&found, &regexp, &s))
+    }))
+}
+
+// A match to replace, with the captures its replacement can refer to
+struct Replaced {
+    range: Range<usize>,
+    captures: Vec<EvaluatorValue>,
+    groups: EvaluatorValue,
+}
+
+// Replaces the first match, or every match of a regular expression with the g flag. The
+// replacement is either a function called with the match, or a string where $& and the like
+// stand for parts of the match.
+// https://tc39.es/ecma262/#sec-string.prototype.replace
+fn replace(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> StringResult {
+    let s = String::from(this);
+    let (search, replace_value) = (argument(&args, 0), argument(&args, 1));
+    let template = match replace_value.is_callable() {
+        true => None,
+        false => Some(String::from(replace_value.clone())),
+    };
+    let replaced = match as_regexp(&search) {
+        Some((object, regexp)) => {
+            let matches = match regexp.has_flag('g') {
+                true => global_matches(&object, &regexp, &s)?,
+                false => regexp_builtin_exec(&object, &regexp, &s)?
+                    .into_iter()
+                    .collect(),
+            };
+            matches
+                .iter()
+                .map(|found| Replaced {
+                    range: found.range(),
+                    captures: found
+                        .groups()
+                        .skip(1)
+                        .map(|group| capture_value(group, &s))
+                        .collect(),
+                    groups: named_groups(found, &s),
+                })
+                .collect::<Vec<_>>()
+        }
+        None => {
+            let search = String::from(search);
+            s.find(&search)
+                .map(|start| Replaced {
+                    range: start..start + search.len(),
+                    captures: Vec::new(),
+                    groups: EvaluatorValue::from(JS_UNDEFINED),
+                })
+                .into_iter()
+                .collect()
+        }
+    };
+    let mut result = String::new();
+    let mut next = 0;
+    for Replaced {
+        range,
+        captures,
+        groups,
+    } in replaced
+    {
+        result.push_str(&s[next..range.start]);
+        let replacement = match &template {
+            Some(template) => get_substitution(template, &s, &range, &captures, &groups)?,
+            None => {
+                let mut arguments =
+                    vec![EvaluatorValue::from(LiteralValue::from(&s[range.clone()]))];
+                arguments.extend(captures);
+                arguments.push(EvaluatorValue::from(LiteralValue::from(
+                    char_index(&s, range.start) as f64,
+                )));
+                arguments.push(EvaluatorValue::from(LiteralValue::from(s.as_str())));
+                if !groups.is_undefined() {
+                    arguments.push(groups);
+                }
+                let value = call_value(
+                    replace_value.clone(),
+                    EvaluatorValue::from(JS_UNDEFINED),
+                    arguments,
+                )?;
+                String::from(value)
+            }
+        };
+        result.push_str(&replacement);
+        next = range.end;
+    }
+    result.push_str(&s[next..]);
+    Ok(EvaluatorValue::from(LiteralValue::String(result)))
+}
+
+// Expands the $ patterns of a replacement string: $$ is a dollar sign, $& the match, $` and $'
+// the text before and after it, $1 to $99 a capture and $<name> a named capture. A pattern that
+// refers to nothing is kept as it is.
+// https://tc39.es/ecma262/#sec-getsubstitution
+fn get_substitution(
+    template: &str,
+    s: &str,
+    range: &Range<usize>,
+    captures: &[EvaluatorValue],
+    groups: &EvaluatorValue,
+) -> Result<String, EvaluatorError> {
+    let capture = |index: usize| match captures[index - 1].is_undefined() {
+        true => String::new(),
+        false => String::from(captures[index - 1].clone()),
+    };
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(dollar) = rest.find('$') {
+        result.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+        let digits = rest
+            .chars()
+            .take(2)
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        let two = digits.parse::<usize>().ok().filter(|_| digits.len() == 2);
+        let one = digits
+            .get(..1)
+            .and_then(|digit| digit.parse::<usize>().ok());
+        let valid = |index: &usize| (1..=captures.len()).contains(index);
+        if let Some(index) = two.filter(valid) {
+            result.push_str(&capture(index));
+            rest = &rest[2..];
+        } else if let Some(index) = one.filter(valid) {
+            result.push_str(&capture(index));
+            rest = &rest[1..];
+        } else if let Some(after) = rest.strip_prefix('$') {
+            result.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('&') {
+            result.push_str(&s[range.clone()]);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('`') {
+            result.push_str(&s[..range.start]);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('\'') {
+            result.push_str(&s[range.end..]);
+            rest = after;
+        } else if let Some((name, after)) = rest
+            .strip_prefix('<')
+            .filter(|_| !groups.is_undefined())
+            .and_then(|after| after.split_once('>'))
+        {
+            let value = get_member(groups.clone(), String::from(name))?;
+            if !value.is_undefined() {
+                result.push_str(&String::from(value));
+            }
+            rest = after;
+        } else {
+            result.push('$');
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+// The index of the first match, or -1. The search always starts at the beginning of the string,
+// and leaves lastIndex as it was.
+// https://tc39.es/ecma262/#sec-regexp.prototype-@@search
+fn search(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> StringResult {
+    let s = String::from(this);
+    let (_, regexp) = to_regexp(argument(&args, 0), "")?;
+    let index = match regexp.find_sticky(&s, 0) {
+        Some(found) => char_index(&s, found.start()) as f64,
+        None => -1.0,
+    };
+    Ok(EvaluatorValue::from(LiteralValue::from(index)))
+}
+
+// Splits the string around every match of the separator, a regular expression adds its captures
+// to the result between the pieces. At most `limit` strings are returned.
+// https://tc39.es/ecma262/#sec-string.prototype.split
+fn split(this: EvaluatorValue, args: Vec<EvaluatorValue>) -> StringResult {
+    let s = String::from(this);
+    let (separator, limit) = (argument(&args, 0), argument(&args, 1));
+    let limit = match limit.is_undefined() {
+        true => u32::MAX as usize,
+        false => to_uint32(limit) as usize,
+    };
+    let string = |s: &str| Some(EvaluatorValue::from(LiteralValue::from(s)));
+    let pieces = if let Some((_, regexp)) = as_regexp(&separator) {
+        split_regexp(&regexp, &s)
+    } else if separator.is_undefined() {
+        vec![string(&s)]
+    } else {
+        match String::from(separator) {
+            separator if separator.is_empty() => s
+                .chars()
+                .map(|c| Some(EvaluatorValue::from(LiteralValue::String(c.to_string()))))
+                .collect(),
+            separator => s.split(separator.as_str()).map(string).collect(),
+        }
+    };
+    Ok(EvaluatorValue::from(
+        pieces.into_iter().take(limit).collect::<Vec<_>>(),
+    ))
+}
+
+// A match that ends where the last piece started, like an empty match at the start, does not
+// split the string
+// https://tc39.es/ecma262/#sec-regexp.prototype-@@split
+fn split_regexp(regexp: &RegExp, s: &str) -> Vec<Option<EvaluatorValue>> {
+    let string = |s: &str| Some(EvaluatorValue::from(LiteralValue::from(s)));
+    if s.is_empty() {
+        return match regexp.find_from(s, 0) {
+            Some(found) if found.start() == 0 => Vec::new(),
+            _ => vec![string(s)],
+        };
+    }
+    let size = s.chars().count();
+    let mut pieces = Vec::new();
+    let (mut p, mut q) = (0, 0);
+    while q < size {
+        let Some(found) = regexp.find_from(s, q) else {
+            break;
+        };
+        q = char_index(s, found.start());
+        let e = char_index(s, found.end()).min(size);
+        if q >= size {
+            break;
+        }
+        if e == p {
+            q += 1;
+            continue;
+        }
+        pieces.push(string(&s[byte_offset(s, p)..found.start()]));
+        pieces.extend(
+            found
+                .groups()
+                .skip(1)
+                .map(|group| Some(capture_value(group, s))),
+        );
+        p = e;
+        q = p;
+    }
+    pieces.push(string(&s[byte_offset(s, p)..]));
+    pieces
+}
+
+// https://262.ecma-international.org/5.1/#sec-9.6
+fn to_uint32(value: EvaluatorValue) -> u32 {
+    match value {
+        EvaluatorValue::Literal(literal) => match literal.value.into() {
+            JsNumber::Number(n) if n.is_finite() => ((n.trunc() % 4294967296.0) as i64) as u32,
+            _ => 0,
+        },
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::assert_completion;
+
+    #[test]
+    pub fn string_raw_joins_raw_strings() {
+        assert_completion(
+            r#"String.raw`C:\dev${1}` + " " + String.raw({ raw: ["a", "b", "c"] }, "-", "-", "-");"#,
+            "C:\\dev1 a-b-c",
+        );
+    }
+
+    #[test]
+    pub fn string_match_search_and_match_all() {
+        assert_completion(
+            r#"
+                const s = "The Quick brown fox, the lazy dog";
+                const all = s.match(/the/gi);
+                const first = s.match(/(qu)ick/i);
+                let found = "";
+                for (const m of "x1y22z".matchAll(/\d+/g)) found += m[0] + "@" + m.index + ";";
+                all + "|" + first[1] + first.index + "|" + s.search(/fox/) + "," + s.search(/cat/) + "|" + found + "|" + s.match(/cat/g);
+            "#,
+            "The,the|Qu4|16,-1|1@1;22@3;|null",
+        );
+    }
+
+    #[test]
+    pub fn string_replace_patterns_and_functions() {
+        assert_completion(
+            r#"
+                const date = "2024-01-15".replace(/(\d+)-(\d+)-(\d+)/, "$3/$2/$1 [$&] $$ $4");
+                const lengths = "a1b22c333".replace(/\d+/g, (m, offset) => "<" + m.length + "@" + offset + ">");
+                const named = "John Smith".replace(/(?<first>\w+)\s(?<last>\w+)/, "$<last>, $<first>");
+                const empty = "abc".replace(/x*/g, "-");
+                date + "|" + lengths + "|" + named + "|" + empty + "|" + "a.b.c".replace(".", "-");
+            "#,
+            "15/01/2024 [2024-01-15] $ $4|a<1@1>b<2@3>c<3@6>|Smith, John|-a-b-c-|a-b.c",
+        );
+    }
+
+    #[test]
+    pub fn string_split_with_regexp_and_limit() {
+        assert_completion(
+            r#"
+                const parts = "a, b,c".split(/\s*,\s*/);
+                parts.length + ":" + parts + "|" + "a1b2c".split(/(\d)/) + "|" + "ab".split(/(?:)/) + "|" + "aaa".split("a", 2).length + "|" + "".split(/x*/).length;
+            "#,
+            "3:a,b,c|a,1,b,2,c|a,b|2|0",
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use lib_ir::ast::{literal::JsNumber, literal_value::LiteralValue};
+
+use crate::closure::NativeFunction;
+use crate::constants::JS_UNDEFINED;
+use crate::evaluator::{call_value, EvaluatorError};
+use crate::evaluator_value::EvaluatorValue;
+use crate::realm::{enqueue_job, intrinsics};
+
+use super::argument;
+
+type TimerResult = Result<EvaluatorValue, EvaluatorError>;
+
+// The timer functions of the host, they are global functions rather than methods of an object
+// https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#timers
+pub fn timer_functions() -> Vec<(&'static str, EvaluatorValue)> {
+    let function = |name, function: fn(EvaluatorValue, Vec<EvaluatorValue>) -> TimerResult| {
+        (
+            name,
+            EvaluatorValue::from(NativeFunction::new(name, function)),
+        )
+    };
+    vec![
+        function("setTimeout", |_, args| set_timer(args, false)),
+        function("setInterval", |_, args| set_timer(args, true)),
+        function("clearTimeout", clear_timer),
+        function("clearInterval", clear_timer),
+        function("queueMicrotask", queue_microtask),
+    ]
+}
+
+// Schedules the callback to run once the delay has passed, with the arguments after the delay,
+// returning the id that clears it
+fn set_timer(args: Vec<EvaluatorValue>, repeat: bool) -> TimerResult {
+    let callback = callable_argument(&args)?;
+    let delay = to_number(argument(&args, 1));
+    let arguments = args.into_iter().skip(2).collect();
+    let id = intrinsics()
+        .timers
+        .borrow_mut()
+        .schedule(callback, delay, repeat, arguments);
+    Ok(EvaluatorValue::from(LiteralValue::from(id as f64)))
+}
+
+// Ids that do not belong to a pending timer are ignored
+fn clear_timer(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> TimerResult {
+    let id = to_number(argument(&args, 0));
+    if id.fract() == 0.0 && id >= 0.0 && id <= u32::MAX as f64 {
+        intrinsics().timers.borrow_mut().clear(id as u32);
+    }
+    Ok(EvaluatorValue::from(JS_UNDEFINED))
+}
+
+// The callback runs in a job of its own, along with promise reactions and before any timer
+// https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#microtask-queuing
+fn queue_microtask(_: EvaluatorValue, args: Vec<EvaluatorValue>) -> TimerResult {
+    let callback = callable_argument(&args)?;
+    enqueue_job(move || call_value(callback, EvaluatorValue::from(JS_UNDEFINED), vec![]).map(drop));
+    Ok(EvaluatorValue::from(JS_UNDEFINED))
+}
+
+fn callable_argument(args: &[EvaluatorValue]) -> TimerResult {
+    let callback = argument(args, 0);
+    if !callback.is_callable() {
+        return Err(EvaluatorError::InvalidType(format!(
+            "The \"callback\" argument must be of type function. Received {}",
+            callback
+        )));
+    }
+    Ok(callback)
+}
+
+// Anything that does not convert to a number is NaN
+fn to_number(value: EvaluatorValue) -> f64 {
+    match value {
+        EvaluatorValue::Literal(literal) => match literal.value.into() {
+            JsNumber::Number(n) => n,
+            JsNumber::Nan => f64::NAN,
+        },
+        _ => f64::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::evaluator::{begin_eval_with_host, Host};
+    use crate::event_loop::Clock;
+    use crate::testing::{assert_completion, assert_throws, assert_value, parse};
+
+    #[test]
+    pub fn timers_run_after_jobs_in_order_of_delay() {
+        assert_completion(
+            r#"
+                let log = "";
+                setTimeout(() => { log += "timeout 20,"; }, 20);
+                setTimeout(() => { log += "timeout 10,"; }, 10);
+                setTimeout(() => { log += "timeout 0,"; }, 0);
+                Promise.resolve().then(() => { log += "then,"; });
+                queueMicrotask(() => { log += "microtask,"; });
+                log += "sync,";
+                new Promise(resolve => setTimeout(() => resolve(log), 30));
+            "#,
+            "sync,then,microtask,timeout 0,timeout 10,timeout 20,",
+        );
+    }
+
+    #[test]
+    pub fn timer_jobs_run_before_next_timer() {
+        assert_completion(
+            r#"
+                let log = "";
+                setTimeout(() => {
+                    log += "a,";
+                    Promise.resolve().then(() => { log += "a then,"; });
+                    setTimeout(() => { log += "c,"; }, 0);
+                }, 0);
+                setTimeout(() => { log += "b,"; }, 1);
+                new Promise(resolve => setTimeout(() => resolve(log), 5));
+            "#,
+            "a,a then,b,c,",
+        );
+    }
+
+    #[test]
+    pub fn set_interval_until_cleared() {
+        assert_completion(
+            r#"
+                let total = 0;
+                const id = setInterval(step => {
+                    total += step;
+                    if (total >= 6) clearInterval(id);
+                }, 100, 2);
+                new Promise(resolve => setTimeout(() => resolve(total), 10000));
+            "#,
+            6.0,
+        );
+    }
+
+    #[test]
+    pub fn await_sleep_with_cleared_timeout() {
+        assert_completion(
+            r#"
+                const sleep = ms => new Promise(resolve => setTimeout(resolve, ms));
+                let fired = false;
+                clearTimeout(setTimeout(() => { fired = true; }, 5));
+                async function main() {
+                    await sleep(1000);
+                    await sleep(1000);
+                    return "slept, fired: " + fired;
+                }
+                main();
+            "#,
+            "slept, fired: false",
+        );
+    }
+
+    #[test]
+    pub fn real_time_clock_waits_for_timers_in_order() {
+        let tree = parse(
+            r#"
+                let log = "";
+                setTimeout(() => { log += "30,"; }, 30);
+                setTimeout(() => {
+                    log += "10,";
+                    setTimeout(() => { log += "10+5,"; }, 5);
+                }, 10);
+                setTimeout(() => { log += "20,"; }, 20);
+                new Promise(resolve => setTimeout(() => resolve(log), 40));
+            "#,
+        );
+        let host = Host {
+            clock: Clock::RealTime,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        assert_value(begin_eval_with_host(tree, host), "10,10+5,20,30,");
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[test]
+    pub fn set_timeout_requires_function() {
+        assert_throws(
+            r#"setTimeout("not a function", 10);"#,
+            "must be of type function",
+        );
+    }
+
+    #[test]
+    pub fn exception_in_timer_ends_program() {
+        assert_throws(
+            r#"
+                setTimeout(message => { throw message; }, 10, "from a timer");
+                "finished";
+            "#,
+            "Uncaught from a timer",
+        );
+    }
+}
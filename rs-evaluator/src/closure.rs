@@ -1,6 +1,49 @@
+use std::{fmt, rc::Rc};
+
 use lib_ir::ast::{BlockStatement, Expression};
 
-use crate::evaluator::Env;
+use crate::evaluator::{Env, EvaluatorError};
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::{JsObject, Object, PrivateElement, PrivateName, PropertyKey};
+use crate::realm::intrinsics;
+
+// Arrow functions have no this binding of their own, they see the this of the code around them.
+// Methods and arrow functions cannot be used with new, and class constructors can only be used
+// with new.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionKind {
+    Normal,
+    Arrow,
+    Method,
+    ClassConstructor,
+}
+
+// What a class constructor knows about the class it was defined by
+#[derive(Debug)]
+pub struct ClassDefinition {
+    // The value of the extends clause, classes that have one are derived classes whose this is
+    // created by the parent constructor through super()
+    pub heritage: Option<EvaluatorValue>,
+    // Classes without a constructor of their own pass their arguments on to the parent class
+    pub default_constructor: bool,
+    // Private methods and accessors, then fields, are added to every instance when it is created
+    pub private_methods: Vec<(PrivateName, PrivateElement)>,
+    pub fields: Vec<ClassField>,
+}
+
+#[derive(Clone, Debug)]
+pub enum FieldKey {
+    Public(PropertyKey),
+    Private(PrivateName),
+}
+
+// The initializer is run as a method of the new instance, a field without one is undefined
+// https://tc39.es/ecma262/#sec-classfielddefinition-record-specification-type
+#[derive(Clone, Debug)]
+pub struct ClassField {
+    pub key: FieldKey,
+    pub initializer: Option<Closure>,
+}
 
 #[derive(Clone, Debug)]
 pub struct Closure {
@@ -9,6 +52,18 @@ pub struct Closure {
     pub env: Env,
     // single expression arrow functions will be changed into block statements with return statements
     pub body: BlockStatement,
+    pub kind: FunctionKind,
+    // Strict functions receive their this value as is, sloppy ones replace undefined with the global object
+    pub strict: bool,
+    // Functions are objects too, copies of a closure share the same properties and identity
+    pub object: JsObject,
+    // The object whose prototype super property lookups start from, set for class methods
+    pub home_object: Option<JsObject>,
+    pub class: Option<Rc<ClassDefinition>>,
+    // Calling a generator function returns a generator instead of running the body
+    pub generator: bool,
+    // Calling an async function returns a promise for the completion of its body
+    pub is_async: bool,
 }
 
 impl Closure {
@@ -17,19 +72,85 @@ impl Closure {
         body: BlockStatement,
         name: Option<String>,
         env: Env,
+        kind: FunctionKind,
+        strict: bool,
     ) -> Self {
-        Closure {
+        let closure = Closure {
             name,
             parameters,
             env,
             body,
+            kind,
+            strict,
+            object: Object::new(Some(intrinsics().function_prototype.clone())),
+            home_object: None,
+            class: None,
+            generator: false,
+            is_async: false,
+        };
+        // Every ordinary function can be used as a constructor, the objects it creates inherit
+        // from its prototype property
+        // https://262.ecma-international.org/5.1/#sec-13.2
+        if kind == FunctionKind::Normal {
+            let prototype = Object::new(Some(intrinsics().object_prototype.clone()));
+            prototype.borrow_mut().set(
+                String::from("constructor"),
+                EvaluatorValue::from(closure.clone()),
+            );
+            closure
+                .object
+                .borrow_mut()
+                .set(String::from("prototype"), EvaluatorValue::Object(prototype));
         }
+        closure
+    }
+
+    // Generator functions cannot be used with new, their prototype property is the prototype of
+    // the generators they return and has no constructor
+    // https://tc39.es/ecma262/#sec-runtime-semantics-instantiategeneratorfunctionobject
+    pub fn with_generator(mut self, generator: bool) -> Self {
+        if generator {
+            self.generator = true;
+            let prototype = Object::new(Some(intrinsics().generator_prototype.clone()));
+            self.object
+                .borrow_mut()
+                .set(String::from("prototype"), EvaluatorValue::Object(prototype));
+        }
+        self
+    }
+
+    // Async functions cannot be used with new either, and have no prototype property at all
+    // https://tc39.es/ecma262/#sec-async-function-definitions-runtime-semantics-instantiateasyncfunctionexpression
+    pub fn with_async(mut self, is_async: bool) -> Self {
+        if is_async {
+            self.is_async = true;
+            self.object
+                .borrow_mut()
+                .properties
+                .shift_remove(&PropertyKey::from("prototype"));
+        }
+        self
     }
 }
 
-impl ToString for Closure {
-    fn to_string(&self) -> String {
-        format!("{:?}", self)
+impl fmt::Display for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kind == FunctionKind::ClassConstructor {
+            return match &self.name {
+                Some(name) => write!(f, "[class {}]", name),
+                None => write!(f, "[class (anonymous)]"),
+            };
+        }
+        let kind = match (self.is_async, self.generator) {
+            (true, true) => "AsyncGeneratorFunction",
+            (true, false) => "AsyncFunction",
+            (false, true) => "GeneratorFunction",
+            (false, false) => "Function",
+        };
+        match &self.name {
+            Some(name) => write!(f, "[{}: {}]", kind, name),
+            None => write!(f, "[{} (anonymous)]", kind),
+        }
     }
 }
 
@@ -39,3 +160,57 @@ impl Into<bool> for Closure {
         true
     }
 }
+
+pub type NativeFn =
+    dyn Fn(EvaluatorValue, Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError>;
+
+// A built in function implemented in Rust, called with the this value and the arguments
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub function: Rc<NativeFn>,
+    pub object: JsObject,
+    // Only the built in constructors can be called with new
+    pub constructor: bool,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: &str,
+        function: impl Fn(EvaluatorValue, Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError>
+            + 'static,
+    ) -> Self {
+        NativeFunction {
+            name: String::from(name),
+            function: Rc::new(function),
+            object: Object::new(Some(intrinsics().function_prototype.clone())),
+            constructor: false,
+        }
+    }
+
+    // A built in function that can also be called with new
+    pub fn new_constructor(
+        name: &str,
+        function: impl Fn(EvaluatorValue, Vec<EvaluatorValue>) -> Result<EvaluatorValue, EvaluatorError>
+            + 'static,
+    ) -> Self {
+        NativeFunction {
+            constructor: true,
+            ..NativeFunction::new(name, function)
+        }
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[Function: {}]", self.name)
+    }
+}
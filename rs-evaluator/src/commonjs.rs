@@ -0,0 +1,262 @@
+use std::rc::Rc;
+
+use lib_ir::ast::literal_value::LiteralValue;
+use lib_ir::ast::{Identifier, Node, NodeKind, Program, SourceType};
+
+use crate::builtins::argument;
+use crate::closure::NativeFunction;
+use crate::completion::Completion;
+use crate::constants::{JS_FALSE, JS_TRUE, JS_UNDEFINED};
+use crate::declarations::has_use_strict;
+use crate::environment::{FunctionFrame, ThisBinding};
+use crate::evaluator::{
+    create_error_object, evaluate, hoist_var_declarations, CompletionResult, EvaluatorError,
+    EvaluatorResult,
+};
+use crate::evaluator_value::EvaluatorValue;
+use crate::module::{load, resolve};
+use crate::object::{JsObject, Object};
+use crate::realm::intrinsics;
+
+// Runs the program as a CommonJS module, so that it can require the files next to it. Its value
+// is the value of its last statement, as it is for a script.
+pub fn evaluate_entry_script(key: String, tree: Node) -> CompletionResult {
+    let module = create_module_object(&key);
+    intrinsics()
+        .require_cache
+        .borrow_mut()
+        .insert(key.clone(), Rc::clone(&module));
+    run_module(&key, tree, &module)
+}
+
+// Loads and runs the file the specifier names the first time it is required, and returns its
+// module.exports. A file required again while it is still running, through a cycle, returns the
+// exports it has set so far.
+// https://nodejs.org/api/modules.html#cycles
+fn require(specifier: &str, referrer: &str) -> EvaluatorResult {
+    let key = resolve(specifier, referrer)?;
+    let cached = intrinsics().require_cache.borrow().get(&key).cloned();
+    let module = match cached {
+        Some(module) => module,
+        None => {
+            let tree = load(&key)?;
+            if let NodeKind::Program(Program {
+                source_type: SourceType::Module,
+                ..
+            }) = &tree.kind
+            {
+                return Err(EvaluatorError::Throw(create_error_object(
+                    "Error",
+                    format!("require() of ES Module {} not supported", key),
+                )));
+            }
+            let module = create_module_object(&key);
+            intrinsics()
+                .require_cache
+                .borrow_mut()
+                .insert(key.clone(), Rc::clone(&module));
+            // A file that throws is removed from the cache, requiring it again runs it again
+            if let Err(e) = run_module(&key, tree, &module) {
+                intrinsics().require_cache.borrow_mut().remove(&key);
+                return Err(e);
+            }
+            module
+        }
+    };
+    let exports = module.borrow().get("exports");
+    Ok(exports.unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED)))
+}
+
+// https://nodejs.org/api/modules.html#the-module-object
+fn create_module_object(key: &str) -> JsObject {
+    let prototype = Some(Rc::clone(&intrinsics().object_prototype));
+    let module = Object::new(prototype.clone());
+    let mut object = module.borrow_mut();
+    object.set(
+        String::from("id"),
+        EvaluatorValue::from(LiteralValue::from(key)),
+    );
+    object.set(
+        String::from("filename"),
+        EvaluatorValue::from(LiteralValue::from(key)),
+    );
+    object.set(
+        String::from("exports"),
+        EvaluatorValue::Object(Object::new(prototype)),
+    );
+    object.set(String::from("loaded"), EvaluatorValue::from(JS_FALSE));
+    drop(object);
+    module
+}
+
+// The file is evaluated as the body of a function, whose parameters are exports, require,
+// module, __filename and __dirname, and whose this is module.exports. Its vars and functions stay
+// in that function rather than becoming globals, and a return statement ends the file.
+// https://nodejs.org/api/modules.html#the-module-wrapper
+fn run_module(key: &str, tree: Node, module: &JsObject) -> CompletionResult {
+    let body = match &tree.kind {
+        NodeKind::Program(program) => &program.body[..],
+        NodeKind::BlockStatement(block) => &block.body[..],
+        _ => std::slice::from_ref(&tree),
+    };
+    let exports = module
+        .borrow()
+        .get("exports")
+        .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED));
+    let global = intrinsics().global_env();
+    let env = global.borrow().extend_function(
+        Rc::clone(&global),
+        FunctionFrame {
+            this: ThisBinding::Initialized(exports.clone()),
+            strict: has_use_strict(body),
+            new_target: None,
+            function: None,
+        },
+    );
+    env.borrow_mut().set_script_or_module(String::from(key));
+    let referrer = String::from(key);
+    let require = NativeFunction::new("require", move |_, args| {
+        require(&String::from(argument(&args, 0)), &referrer)
+    });
+    let dirname = match key.rsplit_once('/') {
+        Some(("", _)) => "/",
+        Some((dirname, _)) => dirname,
+        None => ".",
+    };
+    let parameters = [
+        ("exports", exports),
+        ("require", EvaluatorValue::from(require)),
+        ("module", EvaluatorValue::Object(Rc::clone(module))),
+        ("__filename", EvaluatorValue::from(LiteralValue::from(key))),
+        (
+            "__dirname",
+            EvaluatorValue::from(LiteralValue::from(dirname)),
+        ),
+    ];
+    for (name, value) in parameters {
+        let id = Identifier {
+            name: String::from(name),
+        };
+        env.borrow_mut()
+            .define(id, value, "var")
+            .map_err(EvaluatorError::EnvironmentError)?;
+    }
+    hoist_var_declarations(body, &env);
+    let completion = match evaluate(tree, env)? {
+        Completion::Return(value) => Completion::Normal(Some(value)),
+        completion => completion,
+    };
+    module
+        .borrow_mut()
+        .set(String::from("loaded"), EvaluatorValue::from(JS_TRUE));
+    Ok(completion)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_error, assert_value, eval_with_modules};
+
+    #[test]
+    pub fn commonjs_cycles_see_partial_exports() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    const a = require("./a.js");
+                    const again = require("./a.js");
+                    var top = 1;
+                    a.done + "," + a.sawB + "," + a.b.aWasDone + "," + (again === a) + "," + (this === module.exports) + "," + __filename + "," + __dirname + "," + module.loaded;
+                "#,
+                &[
+                    (
+                        "./a.js",
+                        r#"
+                            exports.done = false;
+                            const b = require("./b.js");
+                            exports.b = b;
+                            exports.sawB = b.done;
+                            exports.done = true;
+                        "#,
+                    ),
+                    (
+                        "./b.js",
+                        r#"
+                            const a = require("./a.js");
+                            module.exports = { done: true, aWasDone: a.done };
+                        "#,
+                    ),
+                ],
+            ),
+            "true,true,false,true,true,./main.js,.,false",
+        );
+    }
+
+    #[test]
+    pub fn commonjs_failed_require_runs_again() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    var secret = 1;
+                    function helper() {}
+                    let first;
+                    try { require("./flaky.js"); } catch (e) { first = e.message; }
+                    const second = require("./flaky.js");
+                    let esm;
+                    try { require("./esm.js"); } catch (e) { esm = e.message + "!"; }
+                    first + "|" + second.attempt + "|" + second.sawSecret + "|" + esm;
+                "#,
+                &[
+                    ("./esm.js", "export const x = 1;"),
+                    (
+                        "./flaky.js",
+                        r#"
+                            globalThis.attempts = (globalThis.attempts || 0) + 1;
+                            if (globalThis.attempts === 1) throw { message: "first try" };
+                            exports.attempt = globalThis.attempts;
+                            exports.sawSecret = typeof secret + typeof helper;
+                        "#,
+                    ),
+                ],
+            ),
+            "first try|2|undefinedundefined|require() of ES Module ./esm.js not supported!",
+        );
+    }
+
+    #[test]
+    pub fn commonjs_module_exports_replaced() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    const Counter = require("./counter.js");
+                    const counter = new Counter();
+                    counter.add();
+                    counter.add();
+                    counter.count + "," + Counter.instances + "," + typeof count;
+                "#,
+                &[(
+                    "./counter.js",
+                    r#"
+                        var count = 0;
+                        class Counter {
+                            constructor() {
+                                this.count = 0;
+                                Counter.instances = ++count;
+                            }
+                            add() { this.count++; }
+                        }
+                        module.exports = Counter;
+                        exports.ignored = true;
+                    "#,
+                )],
+            ),
+            "2,1,undefined",
+        );
+    }
+
+    #[test]
+    pub fn commonjs_require_of_missing_file() {
+        assert_error(
+            eval_with_modules(r#"require("./missing.js");"#, &[]),
+            "Cannot find module './missing.js'",
+        );
+    }
+}
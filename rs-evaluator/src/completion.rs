@@ -0,0 +1,56 @@
+use lib_ir::ast::Identifier;
+
+use crate::evaluator_value::EvaluatorValue;
+
+// https://262.ecma-international.org/5.1/#sec-8.9
+// Statements evaluate to a completion record so that control transfers (return, break, continue)
+// can unwind through any number of enclosing blocks until a construct that consumes them is found.
+// Normal completions with no value correspond to the spec's "empty".
+#[derive(Clone, Debug)]
+pub enum Completion {
+    Normal(Option<EvaluatorValue>),
+    Return(EvaluatorValue),
+    Break(Option<Identifier>),
+    Continue(Option<Identifier>),
+}
+
+impl Completion {
+    pub fn empty() -> Self {
+        Completion::Normal(None)
+    }
+
+    pub fn is_abrupt(&self) -> bool {
+        !matches!(self, Completion::Normal(_))
+    }
+
+    // https://262.ecma-international.org/6.0/#sec-loopcontinues
+    pub fn loop_continues(&self, label_set: &[Identifier]) -> bool {
+        match self {
+            Completion::Normal(_) | Completion::Continue(None) => true,
+            Completion::Continue(Some(label)) => label_set.contains(label),
+            _ => false,
+        }
+    }
+
+    // The completion of a loop or switch that stopped early, an unlabelled break is consumed here
+    pub fn exit_breakable(self, value: EvaluatorValue) -> Self {
+        match self {
+            Completion::Break(None) => Completion::Normal(Some(value)),
+            completion => completion.update_empty(Some(value)),
+        }
+    }
+
+    // https://tc39.es/ecma262/#sec-updateempty
+    pub fn update_empty(self, value: Option<EvaluatorValue>) -> Self {
+        match self {
+            Completion::Normal(None) => Completion::Normal(value),
+            completion => completion,
+        }
+    }
+}
+
+impl From<EvaluatorValue> for Completion {
+    fn from(value: EvaluatorValue) -> Self {
+        Completion::Normal(Some(value))
+    }
+}
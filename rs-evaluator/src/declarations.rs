@@ -0,0 +1,152 @@
+use lib_ir::ast::{
+    ForInLeftValue, FunctionDeclaration, Identifier, Node, NodeKind, ObjectPatternProperty,
+    VariableDeclaration,
+};
+
+use crate::environment::DeclarationKind;
+
+// Names declared with var anywhere in a function body or program, these are bound to undefined
+// before the body runs. Nested functions and classes are skipped as they have a var scope of
+// their own.
+// https://262.ecma-international.org/6.0/#sec-static-semantics-varscopeddeclarations
+pub fn var_declared_names<'a>(body: impl IntoIterator<Item = &'a Node>) -> Vec<Identifier> {
+    let mut names = Vec::new();
+    body.into_iter()
+        .for_each(|statement| collect_var_names(statement, &mut names));
+    names
+}
+
+fn collect_var_names(node: &Node, names: &mut Vec<Identifier>) {
+    match &node.kind {
+        NodeKind::FunctionDeclaration(_)
+        | NodeKind::FunctionExpression(_)
+        | NodeKind::ArrowFunctionExpression(_)
+        | NodeKind::Class(_)
+        | NodeKind::ClassExpression(_)
+        | NodeKind::ClassDeclaration(_) => return,
+        NodeKind::VariableDeclaration(d) => collect_declaration_names(d, names),
+        // The head of a for-in or for-of loop is a declaration but not a node of its own
+        NodeKind::ForInStatement(s) | NodeKind::ForOfStatement(s) => {
+            if let ForInLeftValue::VariableDeclaration(d) = &s.left {
+                collect_declaration_names(d, names);
+            }
+        }
+        _ => {}
+    }
+    node.children()
+        .into_iter()
+        .for_each(|child| collect_var_names(child, names));
+}
+
+fn collect_declaration_names(declaration: &VariableDeclaration, names: &mut Vec<Identifier>) {
+    if declaration.kind != "var" {
+        return;
+    }
+    let declared = declaration
+        .declarations
+        .iter()
+        .flat_map(|d| bound_names(&d.id));
+    for name in declared {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+}
+
+// Function declarations made directly in a statement list, these are initialized when the
+// enclosing block is entered so that they can be called before the point they are declared.
+// https://262.ecma-international.org/6.0/#sec-blockdeclarationinstantiation
+pub fn function_declarations<'a>(
+    statements: impl IntoIterator<Item = &'a Node>,
+) -> Vec<FunctionDeclaration> {
+    statements
+        .into_iter()
+        .filter_map(|statement| match &statement.kind {
+            NodeKind::FunctionDeclaration(f) => Some(f.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+// let, const and class declarations made directly in a statement list. Their bindings exist from
+// the start of the block but stay uninitialized until the declaration itself is evaluated.
+// https://262.ecma-international.org/6.0/#sec-static-semantics-lexicallyscopeddeclarations
+pub fn lexically_declared_names<'a>(
+    statements: impl IntoIterator<Item = &'a Node>,
+) -> Vec<(Identifier, DeclarationKind)> {
+    let mut names = Vec::new();
+    for statement in statements {
+        match &statement.kind {
+            NodeKind::VariableDeclaration(d) => names.extend(lexical_declaration_names(d)),
+            NodeKind::ClassDeclaration(c) => names.push((c.id.clone(), DeclarationKind::Let)),
+            _ => {}
+        }
+    }
+    names
+}
+
+// The names a let or const declaration binds, none for var
+pub fn lexical_declaration_names(d: &VariableDeclaration) -> Vec<(Identifier, DeclarationKind)> {
+    if d.kind == "var" {
+        return Vec::new();
+    }
+    d.declarations
+        .iter()
+        .flat_map(|declarator| bound_names(&declarator.id))
+        .map(|name| (name, DeclarationKind::from(d.kind.as_str())))
+        .collect()
+}
+
+// The identifiers a binding pattern declares, in source order
+// https://262.ecma-international.org/6.0/#sec-destructuring-binding-patterns-static-semantics-boundnames
+pub fn bound_names(pattern: &Node) -> Vec<Identifier> {
+    match &pattern.kind {
+        NodeKind::Identifier(id) => vec![id.clone()],
+        NodeKind::ObjectPattern(p) => p
+            .properties
+            .iter()
+            .flat_map(|property| match property {
+                ObjectPatternProperty::AssignmentProperty(p) => bound_names(&p.value),
+                ObjectPatternProperty::RestElement(r) => bound_names(&r.argument),
+            })
+            .collect(),
+        NodeKind::ArrayPattern(p) => p
+            .elements
+            .iter()
+            .flatten()
+            .flat_map(|e| bound_names(e))
+            .collect(),
+        NodeKind::RestElement(r) => bound_names(&r.argument),
+        NodeKind::AssignmentPattern(p) => bound_names(&p.left),
+        _ => Vec::new(),
+    }
+}
+
+// Whether a parameter list holds default values or computed keys, such parameters are evaluated
+// in a scope of their own that the function body's declarations cannot be seen from
+// https://tc39.es/ecma262/#sec-static-semantics-containsexpression
+pub fn contains_expression(pattern: &Node) -> bool {
+    match &pattern.kind {
+        NodeKind::AssignmentPattern(_) => true,
+        NodeKind::ObjectPattern(p) => p.properties.iter().any(|property| match property {
+            ObjectPatternProperty::AssignmentProperty(p) => {
+                p.computed || contains_expression(&p.value)
+            }
+            ObjectPatternProperty::RestElement(r) => contains_expression(&r.argument),
+        }),
+        NodeKind::ArrayPattern(p) => p.elements.iter().flatten().any(|e| contains_expression(e)),
+        NodeKind::RestElement(r) => contains_expression(&r.argument),
+        _ => false,
+    }
+}
+
+// Whether a function body or program starts with a "use strict" directive
+// https://262.ecma-international.org/5.1/#sec-14.1
+pub fn has_use_strict(body: &[Node]) -> bool {
+    body.iter()
+        .map_while(|statement| match &statement.kind {
+            NodeKind::ExpressionStatement(s) => s.directive.as_deref(),
+            _ => None,
+        })
+        .any(|directive| directive == "use strict")
+}
@@ -0,0 +1,406 @@
+use lib_ir::ast::{
+    CatchClause, ForInLeftValue, Identifier, Node, NodeKind, Pattern, VariableDeclaration,
+};
+
+use crate::declarations::{
+    bound_names, function_declarations, lexical_declaration_names, lexically_declared_names,
+    var_declared_names,
+};
+use crate::evaluator::EvaluatorError;
+
+// Labels visible at a point in the program, and whether they name an iteration statement
+#[derive(Clone, Default)]
+struct LabelScope {
+    labels: Vec<(Identifier, bool)>,
+    in_iteration: bool,
+    in_breakable: bool,
+}
+
+// Statically rejects break and continue statements that have no valid target, before any code
+// runs. Each function body and class static block starts with an empty label scope.
+// https://262.ecma-international.org/6.0/#sec-labelled-statements-static-semantics-early-errors
+pub fn check_labels(tree: &Node) -> Result<(), EvaluatorError> {
+    check_node(tree, &LabelScope::default(), &[])
+}
+
+fn is_iteration(node: &Node) -> bool {
+    matches!(
+        node.kind,
+        NodeKind::WhileStatement(_)
+            | NodeKind::DoWhileStatement(_)
+            | NodeKind::ForStatement(_)
+            | NodeKind::ForInStatement(_)
+            | NodeKind::ForOfStatement(_)
+    )
+}
+
+// `pending` holds the labels directly attached to `node`
+fn check_node(
+    node: &Node,
+    scope: &LabelScope,
+    pending: &[Identifier],
+) -> Result<(), EvaluatorError> {
+    match &node.kind {
+        NodeKind::LabeledStatement(s) => {
+            if scope.labels.iter().any(|(l, _)| *l == s.label) || pending.contains(&s.label) {
+                return Err(EvaluatorError::SyntaxError(format!(
+                    "Label '{}' has already been declared",
+                    s.label.name
+                )));
+            }
+            let mut pending = pending.to_vec();
+            pending.push(s.label.clone());
+            check_node(&s.body, scope, &pending)
+        }
+        // A labelled break may target the statement it is the body of, as in `foo: break foo;`
+        NodeKind::BreakStatement(s) => match &s.label {
+            Some(label)
+                if !scope.labels.iter().any(|(l, _)| l == label) && !pending.contains(label) =>
+            {
+                Err(EvaluatorError::SyntaxError(format!(
+                    "Undefined label '{}'",
+                    label.name
+                )))
+            }
+            None if !scope.in_breakable => Err(EvaluatorError::SyntaxError(String::from(
+                "Illegal break statement",
+            ))),
+            _ => Ok(()),
+        },
+        NodeKind::ContinueStatement(s) => match &s.label {
+            Some(label) => match scope.labels.iter().find(|(l, _)| l == label) {
+                None => Err(EvaluatorError::SyntaxError(format!(
+                    "Undefined label '{}'",
+                    label.name
+                ))),
+                Some((_, false)) => Err(EvaluatorError::SyntaxError(format!(
+                    "Illegal continue statement: '{}' does not denote an iteration statement",
+                    label.name
+                ))),
+                Some((_, true)) => Ok(()),
+            },
+            None if !scope.in_iteration => Err(EvaluatorError::SyntaxError(String::from(
+                "Illegal continue statement: no surrounding iteration statement",
+            ))),
+            None => Ok(()),
+        },
+        NodeKind::FunctionDeclaration(_)
+        | NodeKind::FunctionExpression(_)
+        | NodeKind::ArrowFunctionExpression(_)
+        | NodeKind::StaticBlock(_) => node
+            .children()
+            .into_iter()
+            .try_for_each(|child| check_node(child, &LabelScope::default(), &[])),
+        _ => {
+            let iteration = is_iteration(node);
+            let mut inner = scope.clone();
+            inner
+                .labels
+                .extend(pending.iter().map(|l| (l.clone(), iteration)));
+            if iteration {
+                inner.in_iteration = true;
+                inner.in_breakable = true;
+            } else if let NodeKind::SwitchStatement(_) = node.kind {
+                inner.in_breakable = true;
+            }
+            node.children()
+                .into_iter()
+                .try_for_each(|child| check_node(child, &inner, &[]))
+        }
+    }
+}
+
+// Statically rejects declarations that clash with another binding of the same scope, before any
+// code runs: a let, const or class may not share its name with another declaration of its scope,
+// and a let, const, class or function in a catch block may not redeclare the catch parameter.
+// https://tc39.es/ecma262/#sec-block-static-semantics-early-errors
+// https://tc39.es/ecma262/#sec-try-statement-static-semantics-early-errors
+pub fn check_declarations(tree: &Node) -> Result<(), EvaluatorError> {
+    check_declarations_in(tree, true)
+}
+
+// `top_level` is set for the body of a program or function
+fn check_declarations_in(node: &Node, top_level: bool) -> Result<(), EvaluatorError> {
+    match &node.kind {
+        NodeKind::Program(p) => check_scope(&p.body, true)?,
+        NodeKind::BlockStatement(b) | NodeKind::FunctionBody(b) => check_scope(&b.body, top_level)?,
+        NodeKind::StaticBlock(b) => check_scope(&b.body, true)?,
+        NodeKind::FunctionDeclaration(f) => check_function(&f.params, &f.body.body)?,
+        NodeKind::FunctionExpression(f) => check_function(&f.params, &f.body.body)?,
+        NodeKind::MethodDefinition(m) => check_function(&m.value.params, &m.value.body.body)?,
+        NodeKind::ArrowFunctionExpression(f) => {
+            for param in &f.params {
+                check_declarations_in(param, false)?;
+            }
+            if let NodeKind::BlockStatement(b) | NodeKind::FunctionBody(b) = &f.body.kind {
+                check_parameters(&f.params, lexical_names(&b.body))?;
+            }
+            return check_declarations_in(&f.body, true);
+        }
+        NodeKind::SwitchStatement(s) => check_scope(
+            s.cases
+                .iter()
+                .flat_map(|case| case.consequent.iter().map(|c| c.as_ref())),
+            false,
+        )?,
+        NodeKind::TryStatement(s) => {
+            check_scope(&s.block.body, false)?;
+            if let Some(handler) = &s.handler {
+                check_scope(&handler.body.body, false)?;
+                check_catch_parameter(handler)?;
+            }
+            if let Some(finalizer) = &s.finalizer {
+                check_scope(&finalizer.body, false)?;
+            }
+        }
+        NodeKind::ForStatement(s) => {
+            if let Some(NodeKind::VariableDeclaration(d)) = s.init.as_ref().map(|init| &init.kind) {
+                check_loop_head(d, &s.body)?;
+            }
+        }
+        NodeKind::ForInStatement(s) | NodeKind::ForOfStatement(s) => {
+            if let ForInLeftValue::VariableDeclaration(d) = &s.left {
+                check_loop_head(d, &s.body)?;
+            }
+        }
+        _ => {}
+    }
+    node.children()
+        .into_iter()
+        .try_for_each(|child| check_declarations_in(child, false))
+}
+
+// The declarations made directly in a block or body. Function declarations are bound like a var
+// at the top level of a program or function, and like a let in any other block, where they may
+// still share their name with other function declarations.
+fn check_scope<'a>(
+    statements: impl IntoIterator<Item = &'a Node>,
+    top_level: bool,
+) -> Result<(), EvaluatorError> {
+    let statements = statements.into_iter().collect::<Vec<_>>();
+    let mut lexical_names = Vec::new();
+    for (id, _) in lexically_declared_names(statements.iter().copied()) {
+        if lexical_names.contains(&id) {
+            return Err(already_declared(&id));
+        }
+        lexical_names.push(id);
+    }
+    let mut var_names = var_declared_names(statements.iter().copied());
+    let function_names = function_declarations(statements.iter().copied())
+        .into_iter()
+        .map(|f| f.id)
+        .collect::<Vec<_>>();
+    if top_level {
+        var_names.extend(function_names);
+    } else {
+        if let Some(id) = function_names.iter().find(|id| lexical_names.contains(id)) {
+            return Err(already_declared(id));
+        }
+        lexical_names.extend(function_names);
+    }
+    check_no_var_named(lexical_names, &var_names)
+}
+
+// A let or const in the head of a for loop may not share its name with a var in the loop body
+fn check_loop_head(declaration: &VariableDeclaration, body: &Node) -> Result<(), EvaluatorError> {
+    let lexical_names = lexical_declaration_names(declaration)
+        .into_iter()
+        .map(|(id, _)| id);
+    check_no_var_named(lexical_names, &var_declared_names([body]))
+}
+
+fn check_no_var_named(
+    lexical_names: impl IntoIterator<Item = Identifier>,
+    var_names: &[Identifier],
+) -> Result<(), EvaluatorError> {
+    match lexical_names.into_iter().find(|id| var_names.contains(id)) {
+        Some(id) => Err(already_declared(&id)),
+        None => Ok(()),
+    }
+}
+
+// A let, const or class in the body of a function may not redeclare a parameter, while a var or
+// function may
+// https://tc39.es/ecma262/#sec-function-definitions-static-semantics-early-errors
+fn check_function(parameters: &[Pattern], body: &[Node]) -> Result<(), EvaluatorError> {
+    check_scope(body, true)?;
+    check_parameters(parameters, lexical_names(body))
+}
+
+// The function declarations of a catch block are lexical, so they may not redeclare the catch
+// parameter either
+fn check_catch_parameter(handler: &CatchClause) -> Result<(), EvaluatorError> {
+    let body = &handler.body.body;
+    let lexical_names = lexical_names(body)
+        .into_iter()
+        .chain(function_declarations(body).into_iter().map(|f| f.id));
+    check_parameters(&handler.param, lexical_names)
+}
+
+fn lexical_names(body: &[Node]) -> Vec<Identifier> {
+    lexically_declared_names(body)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+}
+
+fn check_parameters<'a>(
+    parameters: impl IntoIterator<Item = &'a Pattern>,
+    lexical_names: impl IntoIterator<Item = Identifier>,
+) -> Result<(), EvaluatorError> {
+    let parameter_names = parameters
+        .into_iter()
+        .flat_map(|parameter| bound_names(parameter))
+        .collect::<Vec<_>>();
+    match lexical_names
+        .into_iter()
+        .find(|id| parameter_names.contains(id))
+    {
+        Some(id) => Err(already_declared(&id)),
+        None => Ok(()),
+    }
+}
+
+fn already_declared(id: &Identifier) -> EvaluatorError {
+    EvaluatorError::SyntaxError(format!(
+        "Identifier '{}' has already been declared",
+        id.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::JS_UNDEFINED;
+    use crate::testing::{assert_completion, assert_throws};
+
+    #[test]
+    pub fn undefined_label() {
+        assert_throws(
+            "while (true) { break foo; }",
+            "SyntaxError: Undefined label 'foo'",
+        );
+    }
+
+    #[test]
+    pub fn break_targets_enclosing_label() {
+        assert_completion(
+            r#"
+                let x = 0;
+                foo: break foo;
+                foo: { x = 1; break foo; x = 2; }
+                x;
+            "#,
+            1.0,
+        );
+    }
+
+    #[test]
+    pub fn function_bodies_reset_labels() {
+        assert_throws(
+            "foo: while (true) { function f() { break foo; } }",
+            "Undefined label 'foo'",
+        );
+        assert_throws(
+            "while (true) { () => { continue; }; }",
+            "Illegal continue statement",
+        );
+    }
+
+    #[test]
+    pub fn static_blocks_reset_labels() {
+        assert_throws(
+            "while (true) { class A { static { break; } } }",
+            "Illegal break statement",
+        );
+        assert_throws(
+            "foo: { class A { static { break foo; } } }",
+            "Undefined label 'foo'",
+        );
+    }
+
+    #[test]
+    pub fn catch_block_redeclaring_the_parameter() {
+        assert_throws(
+            "function f() { try {} catch (e) { let e; } }",
+            "SyntaxError: Identifier 'e' has already been declared",
+        );
+        assert_throws(
+            "try {} catch ({ a, b }) { class b {} }",
+            "Identifier 'b' has already been declared",
+        );
+        assert_completion("try { throw 1; } catch (e) { var e = 2; } e", JS_UNDEFINED);
+    }
+
+    #[test]
+    pub fn var_redeclaring_a_lexical_binding() {
+        assert_throws(
+            "let x = 1; var x = 2;",
+            "SyntaxError: Identifier 'x' has already been declared",
+        );
+        assert_throws(
+            "function f() { var y; { var z; } const z = 1; }",
+            "Identifier 'z' has already been declared",
+        );
+        assert_throws(
+            "class C {} { var C; }",
+            "Identifier 'C' has already been declared",
+        );
+        assert_throws(
+            "for (let i = 0; i < 1; i++) { var i; }",
+            "Identifier 'i' has already been declared",
+        );
+        assert_completion("var x = 1; { let x = 2; } function f() { let x; } x", 1.0);
+    }
+
+    #[test]
+    pub fn function_redeclaring_a_lexical_binding() {
+        assert_throws(
+            "let f = 1; function f() {}",
+            "SyntaxError: Identifier 'f' has already been declared",
+        );
+        assert_throws(
+            "function g() { { const f = 1; function f() {} } }",
+            "Identifier 'f' has already been declared",
+        );
+        assert_throws(
+            "{ function f() {} var f; }",
+            "Identifier 'f' has already been declared",
+        );
+        assert_throws(
+            "switch (0) { case 0: let a; case 1: let a; }",
+            "Identifier 'a' has already been declared",
+        );
+        assert_completion(
+            r#"
+                var f = 1;
+                function f() {}
+                { function g() {} function g() { return 3; } g(); }
+            "#,
+            3.0,
+        );
+        assert_completion("() => { var f; function f() {} }; 1", 1.0);
+    }
+
+    #[test]
+    pub fn body_let_redeclaring_a_parameter_throws() {
+        assert_throws(
+            "function f(a) { let a = 1; }",
+            "SyntaxError: Identifier 'a' has already been declared",
+        );
+        assert_throws(
+            "const f = ({ a }) => { class a {} };",
+            "Identifier 'a' has already been declared",
+        );
+        assert_throws(
+            "const o = { m(a) { const a = 1; } };",
+            "Identifier 'a' has already been declared",
+        );
+        assert_completion(
+            r#"
+                function f(a) { var a; function a() {} { let a = 2; } return typeof a; }
+                f(1);
+            "#,
+            "function",
+        );
+    }
+}
@@ -2,7 +2,10 @@ use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use lib_ir::ast;
 
+use crate::closure::Closure;
+use crate::constants::JS_UNDEFINED;
 use crate::evaluator_value::EvaluatorValue;
+use crate::object::PrivateName;
 
 #[derive(Clone, Debug)]
 pub enum DeclarationKind {
@@ -27,12 +30,81 @@ pub enum EnvironmentError {
     DuplicateDeclaration,
     ReassignmentConst,
     UndefinedVariable,
+    UninitializedVariable(String),
+    // this was read in a derived constructor before super() was called
+    UninitializedThis,
+    // super() was called a second time in the same constructor
+    ThisAlreadyInitialized,
 }
 
+// A binding whose value is None has been created but not initialized yet, reading or writing it
+// is an error until its declaration is evaluated (the temporal dead zone)
 #[derive(Clone, Debug)]
 pub struct Variable {
-    pub value: EvaluatorValue,
+    pub value: Option<EvaluatorValue>,
     kind: DeclarationKind,
+    // Set for a name imported from a module, which reads the binding it was exported from in the
+    // module's frame and cannot be assigned to
+    // https://tc39.es/ecma262/#sec-createimportbinding
+    import: Option<(Rc<RefCell<Environment>>, ast::Identifier)>,
+}
+
+impl Variable {
+    fn new(value: Option<EvaluatorValue>, kind: DeclarationKind) -> Self {
+        Variable {
+            value,
+            kind,
+            import: None,
+        }
+    }
+
+    // Follows an imported name to the binding it refers to
+    fn resolve(&self) -> Option<Variable> {
+        match &self.import {
+            Some((env, name)) => env.borrow().lookup(name),
+            None => Some(self.clone()),
+        }
+    }
+
+    fn assign(
+        &mut self,
+        id: &ast::Identifier,
+        value: EvaluatorValue,
+    ) -> Result<(), EnvironmentError> {
+        if self.import.is_some() {
+            return Err(EnvironmentError::ReassignmentConst);
+        }
+        if self.value.is_none() {
+            return Err(EnvironmentError::UninitializedVariable(id.name.to_owned()));
+        }
+        if let DeclarationKind::Const = self.kind {
+            return Err(EnvironmentError::ReassignmentConst);
+        }
+        self.value = Some(value);
+        Ok(())
+    }
+}
+
+// https://tc39.es/ecma262/#table-function-environment-records
+#[derive(Clone, Debug)]
+pub enum ThisBinding {
+    // Arrow functions see the this, new.target and super of the code around them
+    Lexical,
+    // Derived constructors have no this until super() returns one
+    Uninitialized,
+    Initialized(EvaluatorValue),
+}
+
+// Created for every function call and for the program
+// https://tc39.es/ecma262/#sec-function-environment-records
+#[derive(Clone, Debug)]
+pub struct FunctionFrame {
+    pub this: ThisBinding,
+    pub strict: bool,
+    // The function new was applied to, None when the function was called without new
+    pub new_target: Option<EvaluatorValue>,
+    // The function being run, which super references are resolved against
+    pub function: Option<Closure>,
 }
 
 // https://262.ecma-international.org/5.1/#sec-10.2.1
@@ -40,6 +112,13 @@ pub struct Variable {
 pub struct Environment {
     parent: Option<Rc<RefCell<Environment>>>,
     values: HashMap<ast::Identifier, Variable>,
+    function: Option<FunctionFrame>,
+    // The #names declared by a class body, visible to all code nested in the class
+    // https://tc39.es/ecma262/#sec-privateenvironment-records
+    private_names: HashMap<String, PrivateName>,
+    // The key of the script or module whose top level this frame is, the specifiers passed to
+    // import() by its code are resolved against it
+    script_or_module: Option<String>,
 }
 
 impl Environment {
@@ -54,6 +133,144 @@ impl Environment {
         Rc::new(RefCell::new(new_scope))
     }
 
+    pub fn extend_function(
+        &self,
+        parent: Rc<RefCell<Environment>>,
+        function: FunctionFrame,
+    ) -> Rc<RefCell<Self>> {
+        let new_scope = self.extend(parent);
+        new_scope.borrow_mut().function = Some(function);
+        new_scope
+    }
+
+    pub fn set_function_frame(&mut self, function: FunctionFrame) {
+        self.function = Some(function);
+    }
+
+    // The frame of the nearest function that is not an arrow function
+    // https://tc39.es/ecma262/#sec-getthisenvironment
+    pub fn this_environment(&self) -> Option<FunctionFrame> {
+        match &self.function {
+            Some(
+                function @ FunctionFrame {
+                    this: ThisBinding::Uninitialized | ThisBinding::Initialized(_),
+                    ..
+                },
+            ) => Some(function.clone()),
+            _ => self.parent.as_ref()?.borrow().this_environment(),
+        }
+    }
+
+    // https://tc39.es/ecma262/#sec-resolvethisbinding
+    pub fn this_binding(&self) -> Result<EvaluatorValue, EnvironmentError> {
+        match self.this_environment().map(|frame| frame.this) {
+            Some(ThisBinding::Initialized(this)) => Ok(this),
+            Some(ThisBinding::Uninitialized) => Err(EnvironmentError::UninitializedThis),
+            Some(ThisBinding::Lexical) | None => Ok(EvaluatorValue::from(JS_UNDEFINED)),
+        }
+    }
+
+    // Called by super() with the object constructed by the parent class
+    // https://tc39.es/ecma262/#sec-bindthisvalue
+    pub fn bind_this(&mut self, value: EvaluatorValue) -> Result<(), EnvironmentError> {
+        match &mut self.function {
+            Some(FunctionFrame {
+                this: this @ ThisBinding::Uninitialized,
+                ..
+            }) => {
+                *this = ThisBinding::Initialized(value);
+                Ok(())
+            }
+            Some(FunctionFrame {
+                this: ThisBinding::Initialized(_),
+                ..
+            }) => Err(EnvironmentError::ThisAlreadyInitialized),
+            _ => match &self.parent {
+                Some(parent) => parent.borrow_mut().bind_this(value),
+                None => Err(EnvironmentError::ThisAlreadyInitialized),
+            },
+        }
+    }
+
+    // https://tc39.es/ecma262/#sec-meta-properties-runtime-semantics-evaluation
+    pub fn new_target(&self) -> Option<EvaluatorValue> {
+        self.this_environment()?.new_target
+    }
+
+    // Whether code running in this frame is strict mode code
+    pub fn is_strict(&self) -> bool {
+        match (&self.function, &self.parent) {
+            (Some(function), _) => function.strict,
+            (None, Some(parent)) => parent.borrow().is_strict(),
+            (None, None) => false,
+        }
+    }
+
+    // The this value of the outermost frame, which is the global object
+    pub fn global_this(&self) -> Option<EvaluatorValue> {
+        match &self.parent {
+            Some(parent) => parent.borrow().global_this(),
+            None => match &self.function.as_ref()?.this {
+                ThisBinding::Initialized(this) => Some(this.clone()),
+                _ => None,
+            },
+        }
+    }
+
+    // A variable of the outermost frame that is also a property of the global object, as made by
+    // assigning to an undeclared identifier in sloppy mode code
+    pub fn define_global(&mut self, id: ast::Identifier, value: EvaluatorValue) {
+        if let Some(parent) = &self.parent {
+            return parent.borrow_mut().define_global(id, value);
+        }
+        if let Some(EvaluatorValue::Object(global_object)) = self.global_this() {
+            global_object
+                .borrow_mut()
+                .set(id.name.to_owned(), value.clone());
+        }
+        self.values
+            .insert(id, Variable::new(Some(value), DeclarationKind::Var));
+    }
+
+    // Creates a sibling frame with copies of this frame's bindings, so that closures created in
+    // one loop iteration do not observe the updates made by the next one
+    // https://262.ecma-international.org/6.0/#sec-createperiterationenvironment
+    pub fn copy_frame(&self) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Environment {
+            parent: self.parent.as_ref().map(Rc::clone),
+            values: self.values.clone(),
+            function: self.function.clone(),
+            private_names: self.private_names.clone(),
+            script_or_module: self.script_or_module.clone(),
+        }))
+    }
+
+    pub fn set_script_or_module(&mut self, key: String) {
+        self.script_or_module = Some(key);
+    }
+
+    // https://tc39.es/ecma262/#sec-getactivescriptormodule
+    pub fn active_script_or_module(&self) -> Option<String> {
+        match &self.script_or_module {
+            Some(key) => Some(key.clone()),
+            None => self.parent.as_ref()?.borrow().active_script_or_module(),
+        }
+    }
+
+    pub fn declare_private_name(&mut self, name: &str) {
+        self.private_names
+            .entry(name.to_owned())
+            .or_insert_with(|| PrivateName::new(name));
+    }
+
+    // https://tc39.es/ecma262/#sec-resolve-private-identifier
+    pub fn resolve_private_name(&self, name: &str) -> Option<PrivateName> {
+        match self.private_names.get(name) {
+            Some(private_name) => Some(private_name.clone()),
+            None => self.parent.as_ref()?.borrow().resolve_private_name(name),
+        }
+    }
+
     pub fn define(
         &mut self,
         id: ast::Identifier,
@@ -65,27 +282,83 @@ impl Environment {
             return Err(EnvironmentError::DuplicateDeclaration);
         }
 
-        self.values.insert(id, Variable { value, kind: k });
+        self.values.insert(id, Variable::new(Some(value), k));
         Ok(())
     }
 
-    pub fn update(
+    // Creates an uninitialized binding for a let, const or class declared in this block
+    pub fn declare_lexical(
+        &mut self,
+        id: ast::Identifier,
+        kind: DeclarationKind,
+    ) -> Result<(), EnvironmentError> {
+        if self.values.contains_key(&id) {
+            return Err(EnvironmentError::DuplicateDeclaration);
+        }
+        self.values.insert(id, Variable::new(None, kind));
+        Ok(())
+    }
+
+    // Binds a name imported from a module to the binding `name` in the module's frame
+    pub fn declare_import(
+        &mut self,
+        id: ast::Identifier,
+        env: Rc<RefCell<Environment>>,
+        name: ast::Identifier,
+    ) -> Result<(), EnvironmentError> {
+        if self.values.contains_key(&id) {
+            return Err(EnvironmentError::DuplicateDeclaration);
+        }
+        let mut variable = Variable::new(None, DeclarationKind::Const);
+        variable.import = Some((env, name));
+        self.values.insert(id, variable);
+        Ok(())
+    }
+
+    // Evaluating a lexical declaration initializes the binding created for it in this frame
+    pub fn initialize(
         &mut self,
         id: ast::Identifier,
         value: EvaluatorValue,
+        kind: &str,
     ) -> Result<(), EnvironmentError> {
-        if let Some(Variable { kind, .. }) = self.values.get(&id) {
-            if let DeclarationKind::Const = kind {
-                return Err(EnvironmentError::ReassignmentConst);
-            }
-            self.values.insert(
-                id,
-                Variable {
-                    value,
-                    kind: kind.to_owned(),
+        match self.values.get_mut(&id) {
+            Some(
+                variable @ Variable {
+                    value: None,
+                    import: None,
+                    ..
                 },
-            );
-            return Ok(());
+            ) => {
+                variable.value = Some(value);
+                Ok(())
+            }
+            Some(_) => Err(EnvironmentError::DuplicateDeclaration),
+            None => self.define(id, value, kind),
+        }
+    }
+
+    // var declarations may be repeated, and do not reset a binding that is already present
+    pub fn declare_var(&mut self, id: ast::Identifier) {
+        self.values.entry(id).or_insert(Variable::new(
+            Some(EvaluatorValue::from(JS_UNDEFINED)),
+            DeclarationKind::Var,
+        ));
+    }
+
+    // A later function declaration with the same name replaces an earlier one
+    pub fn define_function(&mut self, id: ast::Identifier, value: EvaluatorValue) {
+        self.values
+            .insert(id, Variable::new(Some(value), DeclarationKind::Var));
+    }
+
+    pub fn update(
+        &mut self,
+        id: ast::Identifier,
+        value: EvaluatorValue,
+    ) -> Result<(), EnvironmentError> {
+        if let Some(variable) = self.values.get_mut(&id) {
+            return variable.assign(&id, value);
         };
         // recursively lookup parent frames
         let mut curr: Option<Rc<RefCell<Environment>>>;
@@ -102,16 +375,10 @@ impl Environment {
             };
 
             let mut borrowed_env = RefCell::borrow_mut(&rc);
-            let maybe_parent = &borrowed_env.parent;
-            if let Some(Variable { kind, .. }) = borrowed_env.values.get(&id) {
-                if let DeclarationKind::Const = kind {
-                    return Err(EnvironmentError::ReassignmentConst);
-                }
-                let k = kind.clone();
-                borrowed_env.values.insert(id, Variable { value, kind: k });
-                return Ok(());
+            if let Some(variable) = borrowed_env.values.get_mut(&id) {
+                return variable.assign(&id, value);
             } else {
-                match maybe_parent {
+                match &borrowed_env.parent {
                     None => return Err(EnvironmentError::UndefinedVariable),
                     Some(ref next_rc) => {
                         curr = Some(Rc::clone(next_rc));
@@ -122,8 +389,8 @@ impl Environment {
     }
 
     pub fn lookup(&self, id: &ast::Identifier) -> Option<Variable> {
-        if self.values.contains_key(id) {
-            return self.values.get(id).cloned();
+        if let Some(variable) = self.values.get(id) {
+            return variable.resolve();
         }
         // recursively lookup parent environments
         let mut curr: Option<Rc<RefCell<Environment>>>;
@@ -143,8 +410,8 @@ impl Environment {
             let borrowed_env = RefCell::borrow(&rc);
             let maybe_parent = &borrowed_env.parent;
 
-            if borrowed_env.values.contains_key(id) {
-                return borrowed_env.values.get(id).cloned();
+            if let Some(variable) = borrowed_env.values.get(id) {
+                return variable.resolve();
             } else {
                 match maybe_parent {
                     None => return None,
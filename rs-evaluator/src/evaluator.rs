@@ -1,102 +1,467 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::rc::Rc;
 
 use lib_ir::ast::arrow_function::ArrowFunctionExpression;
 use lib_ir::ast::coerced_eq::CoercedEq;
 use lib_ir::ast::literal::{JsNumber, Literal};
 use lib_ir::ast::literal_value::LiteralValue;
-use lib_ir::ast::math::{Additive, BitwiseBinary, BitwiseShift, Multiplicative};
+use lib_ir::ast::math::{Additive, BitwiseBinary, BitwiseShift, Exponential, Multiplicative};
 use lib_ir::ast::{
-    self, AssignmentExpression, AssignmentOperator, BinaryExpression, CallExpression,
-    ConditionalExpression, FunctionDeclaration, FunctionExpression, Identifier, IfStatement,
-    LogicalExpression, MemberExpression, Node, ObjectExpression, Property, ReturnStatement,
-    UnaryExpression, VariableDeclaration, VariableDeclarator,
+    self, ArrayExpression, AssignmentExpression, AssignmentOperator, BinaryExpression,
+    BreakStatement, CallExpression, CatchClause, Class, ClassDeclaration, ConditionalExpression,
+    ContinueStatement, DoWhileStatement, ForInLeftValue, ForInStatement, ForStatement,
+    FunctionDeclaration, FunctionExpression, Identifier, IfStatement, LabeledStatement,
+    LogicalExpression, MemberExpression, MetaProperty, MethodDefinition, NewExpression, Node,
+    ObjectExpression, PrivateIdentifier, Program, PropertyDefinition, ReturnStatement,
+    SequenceExpression, SourceLocation, SourceType, StaticBlock, SwitchCase, SwitchStatement,
+    ThrowStatement, TryStatement, UnaryExpression, UpdateExpression, UpdateOperator,
+    VariableDeclaration, VariableDeclarator, WhileStatement,
 };
 use lib_ir::ast::{BlockStatement, NodeKind};
 
-use crate::closure::Closure;
+use crate::builtins::global_properties;
+use crate::closure::{ClassDefinition, ClassField, Closure, FieldKey, FunctionKind};
+use crate::commonjs::evaluate_entry_script;
+use crate::completion::Completion;
 use crate::constants::{JS_FALSE, JS_NAN, JS_NULL, JS_TRUE, JS_UNDEFINED};
-use crate::environment::{Environment, EnvironmentError};
-use crate::evaluator_value::EvaluatorValue;
+use crate::declarations::{
+    bound_names, contains_expression, function_declarations, has_use_strict,
+    lexical_declaration_names, lexically_declared_names, var_declared_names,
+};
+use crate::early_errors::{check_declarations, check_labels};
+use crate::environment::{
+    DeclarationKind, Environment, EnvironmentError, FunctionFrame, ThisBinding, Variable,
+};
+use crate::evaluator_value::{Array, EvaluatorValue, JsArray};
+use crate::event_loop::{run_event_loop, Clock, Timers};
+use crate::generator::{
+    create_generator, eval_expression_in, run, start_async_function, Coroutine, Suspendable,
+};
+use crate::module::{
+    eval_export_default, eval_import_call, evaluate_entry_module, import_meta, ModuleLoader,
+};
+use crate::object::{
+    InternalSlot, JsObject, Object, PrivateElement, PrivateName, Property, PropertyKey,
+};
+use crate::promise::{new_promise_capability, promise_state, PromiseState};
+use crate::realm::{create_realm, intrinsics};
+use crate::regexp::regexp_create;
 
-type EvaluatorResult = Result<EvaluatorValue, EvaluatorError>;
+pub type EvaluatorResult = Result<EvaluatorValue, EvaluatorError>;
+pub type CompletionResult = Result<Completion, EvaluatorError>;
 
 pub type Env = Rc<RefCell<Environment>>;
 
+// Throw completions are carried on the error side of results, so that `?` propagates a JS
+// exception out of any expression until a try statement catches it
 #[derive(Debug)]
 pub enum EvaluatorError {
     EnvironmentError(EnvironmentError),
     InvalidType(String),
+    SyntaxError(String),
+    // An identifier that could not be resolved, with where it appeared in the source
+    ReferenceError(Identifier, Option<SourceLocation>),
+    Throw(EvaluatorValue),
+    // The return method of a suspended generator was called, this unwinds its body like a return
+    // statement would. Catch clauses do not see it, finally blocks still run.
+    GeneratorReturn(EvaluatorValue),
 }
 
 impl EvaluatorError {
     pub fn as_str(&self) -> String {
         match self {
-            EvaluatorError::EnvironmentError(e) => format!("{:?}", e),
+            EvaluatorError::EnvironmentError(e) => {
+                let (name, message) = environment_error_message(e);
+                format!("Uncaught {}: {}", name, message)
+            }
             EvaluatorError::InvalidType(s) => s.to_owned(),
+            EvaluatorError::SyntaxError(s) => format!("SyntaxError: {}", s),
+            EvaluatorError::ReferenceError(id, loc) => match loc {
+                Some(loc) => format!(
+                    "Uncaught ReferenceError: {} is not defined at {}",
+                    id.name, loc
+                ),
+                None => format!("Uncaught ReferenceError: {} is not defined", id.name),
+            },
+            EvaluatorError::Throw(value) => format!("Uncaught {}", describe_exception(value)),
+            EvaluatorError::GeneratorReturn(value) => format!("Generator returned {}", value),
+        }
+    }
+
+    // The value seen by a catch clause. Errors raised by the evaluator while running code are
+    // exposed as error objects, early errors happen before any code runs and cannot be caught.
+    pub fn into_exception(self) -> Result<EvaluatorValue, EvaluatorError> {
+        match self {
+            EvaluatorError::Throw(value) => Ok(value),
+            EvaluatorError::InvalidType(message) => Ok(create_error_object("TypeError", message)),
+            EvaluatorError::EnvironmentError(e) => {
+                let (name, message) = environment_error_message(&e);
+                Ok(create_error_object(name, message))
+            }
+            EvaluatorError::ReferenceError(id, _) => Ok(create_error_object(
+                "ReferenceError",
+                format!("{} is not defined", id.name),
+            )),
+            EvaluatorError::SyntaxError(_) | EvaluatorError::GeneratorReturn(_) => Err(self),
+        }
+    }
+}
+
+// The name of the error an environment error is thrown as, and its message
+fn environment_error_message(e: &EnvironmentError) -> (&'static str, String) {
+    match e {
+        EnvironmentError::ReassignmentConst => {
+            ("TypeError", String::from("Assignment to constant variable."))
         }
+        EnvironmentError::DuplicateDeclaration => (
+            "SyntaxError",
+            String::from("Identifier has already been declared"),
+        ),
+        EnvironmentError::UndefinedVariable => (
+            "ReferenceError",
+            String::from("Assignment to undeclared variable"),
+        ),
+        EnvironmentError::UninitializedVariable(name) => (
+            "ReferenceError",
+            format!("Cannot access '{}' before initialization", name),
+        ),
+        EnvironmentError::UninitializedThis => (
+            "ReferenceError",
+            String::from("Must call super constructor in derived class before accessing 'this' or returning from derived constructor"),
+        ),
+        EnvironmentError::ThisAlreadyInitialized => (
+            "ReferenceError",
+            String::from("Super constructor may only be called once"),
+        ),
     }
 }
 
+pub fn create_error_object(name: &str, message: String) -> EvaluatorValue {
+    let error = Object::new(Some(Rc::clone(&intrinsics().error_prototypes[name])));
+    error.borrow_mut().internal_slot = Some(InternalSlot::Error);
+    error.borrow_mut().set(
+        String::from("message"),
+        EvaluatorValue::from(LiteralValue::from(message)),
+    );
+    EvaluatorValue::Object(error)
+}
+
+// Error objects are reported as "name: message", like uncaught errors in node
+fn describe_exception(value: &EvaluatorValue) -> String {
+    if let EvaluatorValue::Object(obj) = value {
+        let obj = obj.borrow();
+        if let (Some(name), Some(message)) = (obj.get("name"), obj.get("message")) {
+            return format!("{}: {}", name, message);
+        }
+    }
+    value.to_string()
+}
+
+// What the host running a program provides to it
+#[derive(Default)]
+pub struct Host {
+    pub clock: Clock,
+    // Programs run without a loader cannot import or require modules
+    pub loader: Option<Rc<dyn ModuleLoader>>,
+    // The key of the program, which the specifiers it imports are resolved against
+    pub path: String,
+}
+
 pub fn begin_eval(tree: ast::Node) -> EvaluatorResult {
+    begin_eval_with_host(tree, Host::default())
+}
+
+// Evaluates the program as a script or as a module, then runs its jobs and timers until there
+// are none left
+pub fn begin_eval_with_host(tree: ast::Node, host: Host) -> EvaluatorResult {
+    let value = start_eval(tree, host)?;
+    run_event_loop()?;
+    program_result(value)
+}
+
+// Evaluates the program, leaving the jobs and timers it queued for the event loop
+pub fn start_eval(tree: ast::Node, host: Host) -> EvaluatorResult {
+    check_labels(&tree)?;
+    check_declarations(&tree)?;
+    let (strict, module) = match &tree.kind {
+        NodeKind::Program(program) if program.source_type == SourceType::Module => (true, true),
+        NodeKind::Program(Program { body, .. })
+        | NodeKind::BlockStatement(BlockStatement { body }) => (has_use_strict(body), false),
+        _ => (false, false),
+    };
+    let intrinsics = create_realm();
+    *intrinsics.module_loader.borrow_mut() = host.loader;
+    *intrinsics.timers.borrow_mut() = Timers::new(host.clock);
+    let env = create_global_environment(strict);
+    env.borrow_mut().set_script_or_module(host.path.clone());
+    let completion = match tree.kind {
+        NodeKind::Program(program) if module => evaluate_entry_module(host.path, program.body)?,
+        // Scripts run by a host that can load files are CommonJS modules
+        _ if intrinsics.module_loader.borrow().is_some() => evaluate_entry_script(host.path, tree)?,
+        _ => {
+            hoist_var_declarations(std::slice::from_ref(&tree), &env);
+            evaluate(tree, env)?
+        }
+    };
+    match completion {
+        Completion::Normal(value) => {
+            Ok(value.unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED)))
+        }
+        Completion::Return(_) => Err(EvaluatorError::InvalidType(String::from(
+            "Illegal return statement",
+        ))),
+        Completion::Break(label) | Completion::Continue(label) => {
+            Err(EvaluatorError::InvalidType(match label {
+                Some(Identifier { name }) => format!("Undefined label '{}'", name),
+                None => String::from("Illegal break or continue statement"),
+            }))
+        }
+    }
+}
+
+// Once the event loop is done, a program that ends in a promise evaluates to what the promise
+// settled with
+pub fn program_result(value: EvaluatorValue) -> EvaluatorResult {
+    match promise_state(&value) {
+        Some(PromiseState::Fulfilled(value)) => Ok(value),
+        Some(PromiseState::Rejected(reason)) => Err(EvaluatorError::Throw(reason)),
+        _ => Ok(value),
+    }
+}
+
+// The value properties of the global object
+// https://262.ecma-international.org/5.1/#sec-15.1.1
+// Top level code sees the global object as this, in strict mode as well
+fn create_global_environment(strict: bool) -> Env {
     let env = Rc::new(RefCell::new(Environment::new()));
-    evaluate(tree, env)
+    let global_object = Object::new(Some(Rc::clone(&intrinsics().object_prototype)));
+    let globals = [
+        ("undefined", JS_UNDEFINED),
+        ("NaN", JS_NAN),
+        ("Infinity", LiteralValue::from(f64::INFINITY)),
+    ];
+    for (name, value) in globals {
+        let id = Identifier {
+            name: String::from(name),
+        };
+        global_object
+            .borrow_mut()
+            .set(id.name.to_owned(), EvaluatorValue::from(value.clone()));
+        env.borrow_mut()
+            .define(id, EvaluatorValue::from(value), "const")
+            .expect("globals are only defined once");
+    }
+    for (name, value) in global_properties() {
+        let id = Identifier {
+            name: String::from(name),
+        };
+        global_object
+            .borrow_mut()
+            .set(id.name.to_owned(), value.clone());
+        env.borrow_mut()
+            .define(id, value, "var")
+            .expect("globals are only defined once");
+    }
+
+    let global_object = EvaluatorValue::Object(global_object);
+    let id = Identifier {
+        name: String::from("globalThis"),
+    };
+    env.borrow_mut()
+        .define(id, global_object.clone(), "let")
+        .expect("globals are only defined once");
+    env.borrow_mut().set_function_frame(FunctionFrame {
+        this: ThisBinding::Initialized(global_object),
+        strict,
+        new_target: None,
+        function: None,
+    });
+    *intrinsics().global_env.borrow_mut() = Some(Rc::clone(&env));
+    env
 }
 
+// Statements produce completion records, expressions are evaluated through eval_expression
 // TODO change this an eval context struct that collects errors
-pub fn evaluate(tree: ast::Node, env: Env) -> EvaluatorResult {
+pub fn evaluate(tree: ast::Node, env: Env) -> CompletionResult {
+    run(eval_statement(tree, env, None))
+}
+
+// The statements of generator and async function bodies are evaluated with the coroutine `co` of
+// the body, which a yield or await in them suspends. Other code has none and runs to completion.
+pub fn eval_statement(
+    tree: ast::Node,
+    env: Env,
+    co: Option<Coroutine>,
+) -> Suspendable<CompletionResult> {
+    Box::pin(async move {
+        match tree.kind {
+            // Scripts are evaluated like a block, modules are evaluated by evaluate_entry_module
+            NodeKind::Program(Program { body, .. }) => {
+                eval_block(BlockStatement { body }, Rc::clone(&env), co).await
+            }
+            NodeKind::BlockStatement(block) => eval_block(block, Rc::clone(&env), co).await,
+            NodeKind::ExpressionStatement(expr) => eval_expression_in(*expr.expression, env, co)
+                .await
+                .map(Completion::from),
+            NodeKind::EmptyStatement(_) => Ok(Completion::empty()),
+            NodeKind::VariableDeclaration(decl) => eval_variable_declaration(decl, env, co).await,
+            // Function declarations are initialized when the enclosing block is entered
+            NodeKind::FunctionDeclaration(_) => Ok(Completion::empty()),
+            NodeKind::ClassDeclaration(c) => eval_class_declaration(c, env, co).await,
+            NodeKind::ReturnStatement(r) => eval_return_statement(r, env, co).await,
+            NodeKind::BreakStatement(BreakStatement { label }) => Ok(Completion::Break(label)),
+            NodeKind::ContinueStatement(ContinueStatement { label }) => {
+                Ok(Completion::Continue(label))
+            }
+            NodeKind::IfStatement(e) => eval_if_statement(e, env, co).await,
+            NodeKind::LabeledStatement(s) => eval_labeled_statement(s, env, co, vec![]).await,
+            NodeKind::WhileStatement(s) => eval_while_statement(s, env, co, &[]).await,
+            NodeKind::DoWhileStatement(s) => eval_do_while_statement(s, env, co, &[]).await,
+            NodeKind::ForStatement(s) => eval_for_statement(s, env, co, &[]).await,
+            NodeKind::ForOfStatement(s) => eval_for_of_statement(s, env, co, &[]).await,
+            NodeKind::SwitchStatement(s) => eval_switch_statement(s, env, co).await,
+            NodeKind::ThrowStatement(s) => eval_throw_statement(s, env, co).await,
+            NodeKind::TryStatement(s) => eval_try_statement(s, env, co).await,
+            // Imports are bound when the module is linked
+            NodeKind::ImportDeclaration(_) | NodeKind::ExportAllDeclaration(_) => {
+                Ok(Completion::empty())
+            }
+            NodeKind::ExportNamedDeclaration(e) => match e.declaration {
+                Some(declaration) => eval_statement(*declaration, env, co).await,
+                None => Ok(Completion::empty()),
+            },
+            NodeKind::ExportDefaultDeclaration(e) => eval_export_default(e, env),
+            _ => unimplemented!("{:?}", tree.kind),
+        }
+    })
+}
+
+pub fn eval_expression(tree: ast::Node, env: Env) -> EvaluatorResult {
     match tree.kind {
-        NodeKind::Program(_) => unreachable!(),
-        NodeKind::BlockStatement(block) => eval_block_statement(block, Rc::clone(&env)),
-        NodeKind::ExpressionStatement(expr) => evaluate(*expr.expression, env),
         NodeKind::UnaryExpression(expr) => eval_unary_expression(expr, env),
+        NodeKind::UpdateExpression(expr) => run(eval_update_expression(expr, env, None)),
         NodeKind::BinaryExpression(expr) => eval_binary_expression(expr, env),
         NodeKind::LogicalExpression(expr) => eval_logical_expression(expr, env),
+        // Every evaluation of a regular expression literal creates a new RegExp object
+        // https://tc39.es/ecma262/#sec-regular-expression-literals-runtime-semantics-evaluation
+        NodeKind::Literal(Literal {
+            value: LiteralValue::RegExp { pattern, flags },
+        }) => regexp_create(&pattern, &flags),
         NodeKind::Literal(literal) => Ok(EvaluatorValue::from(literal)),
-        NodeKind::VariableDeclaration(decl) => eval_variable_declaration(decl, env),
-        NodeKind::Identifier(id) => eval_identifier(id, env),
-        NodeKind::AssignmentExpression(expr) => eval_assignment_expr(expr, env),
-        NodeKind::FunctionDeclaration(f) => eval_function_declaration(f, env),
+        NodeKind::Identifier(id) => eval_identifier(id, tree.loc, env),
+        NodeKind::AssignmentExpression(expr) => run(eval_assignment_expr(expr, env, None)),
         NodeKind::FunctionExpression(f) => eval_function_expression(f, env),
         NodeKind::ArrowFunctionExpression(f) => eval_arrow_function(f, env),
         NodeKind::CallExpression(c) => eval_call_expr(c, env),
-        NodeKind::ReturnStatement(r) => eval_return_statement(r, env),
+        NodeKind::NewExpression(e) => eval_new_expression(e, env),
+        NodeKind::MetaProperty(p) => eval_meta_property(p, env),
         NodeKind::ObjectExpression(e) => eval_object_expression(e, env),
+        NodeKind::ArrayExpression(e) => eval_array_expression(e, env),
         NodeKind::MemberExpression(e) => eval_member_expression(e, env),
-        NodeKind::IfStatement(e) => eval_if_statement(e, env),
         NodeKind::ConditionalExpression(e) => eval_conditional_expression(e, env),
+        NodeKind::SequenceExpression(e) => eval_sequence_expression(e, env),
+        NodeKind::ThisExpression(_) => eval_this(&env),
+        NodeKind::ClassExpression(c) => eval_class(c, env),
+        NodeKind::TemplateLiteral(t) => eval_template_literal(t, env),
+        NodeKind::TaggedTemplateExpression(t) => eval_tagged_template(t, env),
+        NodeKind::ImportExpression(i) => eval_import_call(i, env),
+        // Generator bodies evaluate yield themselves, one found here is somewhere they cannot
+        // suspend from
+        NodeKind::YieldExpression(_) => Err(EvaluatorError::SyntaxError(String::from(
+            "yield is not supported in this position",
+        ))),
+        NodeKind::AwaitExpression(_) => Err(EvaluatorError::SyntaxError(String::from(
+            "await is only valid in async functions and the top level bodies of modules",
+        ))),
         _ => unimplemented!("{:?}", tree.kind),
     }
 }
 
+pub fn eval_block_statement(block: BlockStatement, env: Env) -> CompletionResult {
+    run(eval_block(block, env, None))
+}
+
 // Create a new env frame, evaluate innerscope
-pub fn eval_block_statement(block: BlockStatement, env: Env) -> EvaluatorResult {
+pub async fn eval_block(
+    block: BlockStatement,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
     let body = block.body;
     let inner_env = env.borrow_mut().extend(Rc::clone(&env));
-    eval_sequence(body, inner_env)
+    hoist_lexical_declarations(lexically_declared_names(&body), &inner_env)?;
+    hoist_function_declarations(function_declarations(&body), &inner_env);
+    eval_statement_list(body, inner_env, co).await
 }
 
-pub fn eval_sequence(seq: Vec<Node>, env: Env) -> EvaluatorResult {
-    if seq.is_empty() {
-        // Empty block in js should return undefined
-        return Ok(EvaluatorValue::from(JS_UNDEFINED));
-    }
-    // TODO: this might be an expensive clone
-    let first_seq = seq.first().unwrap().to_owned();
-    if seq.len() == 1 {
-        return evaluate(first_seq, env);
+// Binds the var names of a function body or program in its outermost frame, before it runs
+pub fn hoist_var_declarations(body: &[Node], env: &Env) {
+    var_declared_names(body)
+        .into_iter()
+        .for_each(|id| env.borrow_mut().declare_var(id));
+}
+
+pub fn hoist_lexical_declarations(
+    names: Vec<(Identifier, DeclarationKind)>,
+    env: &Env,
+) -> Result<(), EvaluatorError> {
+    names.into_iter().try_for_each(|(id, kind)| {
+        env.borrow_mut()
+            .declare_lexical(id, kind)
+            .map_err(EvaluatorError::EnvironmentError)
+    })
+}
+
+pub fn hoist_function_declarations(declarations: Vec<FunctionDeclaration>, env: &Env) {
+    for FunctionDeclaration {
+        id,
+        params,
+        body,
+        generator,
+        is_async,
+    } in declarations
+    {
+        let strict = env.borrow().is_strict() || has_use_strict(&body.body);
+        let closure = Closure::new(
+            params,
+            body,
+            Some(id.name.to_owned()),
+            Rc::clone(env),
+            FunctionKind::Normal,
+            strict,
+        )
+        .with_generator(generator)
+        .with_async(is_async);
+        env.borrow_mut()
+            .define_function(id, EvaluatorValue::from(closure));
     }
-    if let NodeKind::ReturnStatement(return_statement) = first_seq.kind {
-        eval_return_statement(return_statement, env)
-    } else {
-        // HACK most elegant way to pop the first element
-        let mut seq_q = std::collections::VecDeque::from(seq);
-        let first = seq_q.pop_front().unwrap();
-        evaluate(first, Rc::clone(&env))?;
-        let rest = Vec::from(seq_q);
-        eval_sequence(rest, env)
+}
+
+pub fn eval_sequence(seq: Vec<Node>, env: Env) -> CompletionResult {
+    run(eval_statement_list(seq, env, None))
+}
+
+// Evaluates statements in order until one of them completes abruptly. The value of the sequence
+// is the value of the last statement that produced one.
+// https://262.ecma-international.org/5.1/#sec-12.1
+pub async fn eval_statement_list(
+    seq: Vec<Node>,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let mut value = None;
+    for statement in seq {
+        let completion = eval_statement(statement, Rc::clone(&env), co.clone()).await?;
+        if completion.is_abrupt() {
+            return Ok(completion.update_empty(value));
+        }
+        if let Completion::Normal(Some(v)) = completion {
+            value = Some(v);
+        }
     }
+    Ok(Completion::Normal(value))
 }
 
 // https://262.ecma-international.org/5.1/#sec-11.4
@@ -105,20 +470,47 @@ pub fn eval_unary_expression(node: UnaryExpression, env: Env) -> EvaluatorResult
         operator, argument, ..
     } = node;
 
-    let arg_value = evaluate(*argument, env)?;
+    // typeof is the one operator that accepts an unresolvable reference
+    if let (ast::UnaryOperator::TypeOf, NodeKind::Identifier(id)) = (&operator, &argument.kind) {
+        if env.borrow().lookup(id).is_none() {
+            return Ok(EvaluatorValue::from(LiteralValue::from("undefined")));
+        }
+    }
+
+    let arg_value = eval_expression(*argument, env)?;
 
-    let Literal { value } = match arg_value {
-        EvaluatorValue::Literal(l) => l,
-        EvaluatorValue::Closure(_) => unimplemented!(),
-        EvaluatorValue::Object(_) => todo!(),
+    let value = match arg_value {
+        EvaluatorValue::Literal(Literal { value }) => value,
+        value => match operator {
+            ast::UnaryOperator::TypeOf => {
+                let type_name = match value {
+                    EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_) => "function",
+                    EvaluatorValue::Symbol(_) => "symbol",
+                    _ => "object",
+                };
+                return Ok(EvaluatorValue::from(LiteralValue::from(type_name)));
+            }
+            ast::UnaryOperator::Bang => return Ok(EvaluatorValue::from(JS_FALSE)),
+            ast::UnaryOperator::Void => return Ok(EvaluatorValue::from(JS_UNDEFINED)),
+            ast::UnaryOperator::Delete => return Ok(EvaluatorValue::from(JS_TRUE)),
+            // Objects are converted to a primitive first
+            _ => match to_primitive(value)? {
+                EvaluatorValue::Literal(Literal { value }) => value,
+                value => LiteralValue::String(String::from(value)),
+            },
+        },
     };
 
     let evaluated_val = match value {
         LiteralValue::String(s) => match operator {
-            ast::UnaryOperator::Minus | ast::UnaryOperator::Plus => JS_NAN,
+            ast::UnaryOperator::Minus => match LiteralValue::String(s).into() {
+                JsNumber::Number(n) => LiteralValue::from(-n),
+                JsNumber::Nan => JS_NAN,
+            },
+            ast::UnaryOperator::Plus => LiteralValue::Number(LiteralValue::String(s).into()),
             ast::UnaryOperator::Bang => LiteralValue::from(s.is_empty()),
             ast::UnaryOperator::TypeOf => LiteralValue::from("string"),
-            ast::UnaryOperator::Void => LiteralValue::from(s),
+            ast::UnaryOperator::Void => JS_UNDEFINED,
             ast::UnaryOperator::Delete => JS_TRUE,
         },
         LiteralValue::Boolean(b) => match operator {
@@ -160,12 +552,12 @@ pub fn eval_unary_expression(node: UnaryExpression, env: Env) -> EvaluatorResult
                 ast::UnaryOperator::Delete => JS_FALSE,
             },
         },
-        LiteralValue::RegExp => match operator {
+        LiteralValue::RegExp { .. } => match operator {
             ast::UnaryOperator::Minus | ast::UnaryOperator::Plus => JS_NAN,
             ast::UnaryOperator::Bang => JS_FALSE,
             ast::UnaryOperator::TypeOf => LiteralValue::from("object"),
             ast::UnaryOperator::Void => LiteralValue::Undefined,
-            ast::UnaryOperator::Delete => todo!(),
+            ast::UnaryOperator::Delete => JS_TRUE,
         },
         LiteralValue::Undefined => match operator {
             ast::UnaryOperator::Minus | ast::UnaryOperator::Plus => JS_NAN,
@@ -186,50 +578,215 @@ fn eval_binary_expression(expr: BinaryExpression, env: Env) -> EvaluatorResult {
         operator,
     } = expr;
 
-    let left_evaluator_value = evaluate(*left, Rc::clone(&env))?;
-    let right_evaluator_value = evaluate(*right, Rc::clone(&env))?;
+    // #x in obj checks whether obj was initialized by the class that declares #x
+    // https://tc39.es/ecma262/#sec-relational-operators-runtime-semantics-evaluation
+    if let NodeKind::PrivateIdentifier(id) = left.kind {
+        let name = resolve_private_name(&id, &env)?;
+        let target = eval_expression(*right, env)?;
+        if let EvaluatorValue::Literal(_) = target {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Cannot use 'in' operator to search for '{}' in {}",
+                name,
+                String::from(target)
+            )));
+        }
+        let found = private_elements_holder(&target)
+            .is_some_and(|obj| obj.borrow().find_private(&name).is_some());
+        return Ok(EvaluatorValue::from(LiteralValue::from(found)));
+    }
+
+    let left_evaluator_value = eval_expression(*left, Rc::clone(&env))?;
+    let right_evaluator_value = eval_expression(*right, Rc::clone(&env))?;
+
+    apply_binary_operator(operator, left_evaluator_value, right_evaluator_value)
+}
+
+// Shared by binary expressions and compound assignment, once both operands are evaluated
+fn apply_binary_operator(
+    operator: ast::BinaryOperator,
+    left_evaluator_value: EvaluatorValue,
+    right_evaluator_value: EvaluatorValue,
+) -> EvaluatorResult {
+    // Strict equality compares objects by reference, so it is decided before any conversion
+    match operator {
+        ast::BinaryOperator::EqEqEq => {
+            let equal = left_evaluator_value.strict_equals(&right_evaluator_value);
+            return Ok(EvaluatorValue::from(LiteralValue::from(equal)));
+        }
+        ast::BinaryOperator::BangEqEq => {
+            let equal = left_evaluator_value.strict_equals(&right_evaluator_value);
+            return Ok(EvaluatorValue::from(LiteralValue::from(!equal)));
+        }
+        ast::BinaryOperator::Instanceof => {
+            let result = instance_of(left_evaluator_value, right_evaluator_value)?;
+            return Ok(EvaluatorValue::from(LiteralValue::from(result)));
+        }
+        ast::BinaryOperator::In => {
+            let result = has_property(right_evaluator_value, left_evaluator_value)?;
+            return Ok(EvaluatorValue::from(LiteralValue::from(result)));
+        }
+        _ => {}
+    }
+
+    // https://262.ecma-international.org/5.1/#sec-11.9.3
+    match operator {
+        ast::BinaryOperator::EqEq => {
+            let equal = loosely_equals(left_evaluator_value, right_evaluator_value)?;
+            return Ok(EvaluatorValue::from(LiteralValue::from(equal)));
+        }
+        ast::BinaryOperator::BangEq => {
+            let equal = loosely_equals(left_evaluator_value, right_evaluator_value)?;
+            return Ok(EvaluatorValue::from(LiteralValue::from(!equal)));
+        }
+        _ => {}
+    }
 
-    let left_value = match left_evaluator_value {
+    // Objects are converted to a primitive first, the left operand before the right
+    let left_value = match to_primitive(left_evaluator_value)? {
         EvaluatorValue::Literal(l) => l.value,
-        EvaluatorValue::Closure(c) => LiteralValue::String(c.to_string()),
-        EvaluatorValue::Object(_) => todo!(),
+        value => LiteralValue::String(String::from(value)),
     };
 
-    let right_value = match right_evaluator_value {
+    let right_value = match to_primitive(right_evaluator_value)? {
         EvaluatorValue::Literal(l) => l.value,
-        EvaluatorValue::Closure(c) => LiteralValue::String(c.to_string()),
-        EvaluatorValue::Object(_) => todo!(),
+        value => LiteralValue::String(String::from(value)),
     };
 
     let evaluated_val = match operator {
-        // https://262.ecma-international.org/5.1/#sec-11.9.3
-        ast::BinaryOperator::EqEq => LiteralValue::from(left_value.coerced_eq(&right_value)),
-        ast::BinaryOperator::BangEq => LiteralValue::from(left_value.coerced_neq(&right_value)),
-        ast::BinaryOperator::EqEqEq => LiteralValue::from(left_value.eq(&right_value)),
-        ast::BinaryOperator::BangEqEq => LiteralValue::from(left_value.ne(&right_value)),
+        ast::BinaryOperator::EqEq
+        | ast::BinaryOperator::BangEq
+        | ast::BinaryOperator::EqEqEq
+        | ast::BinaryOperator::BangEqEq
+        | ast::BinaryOperator::In
+        | ast::BinaryOperator::Instanceof => unreachable!(),
         ast::BinaryOperator::Lt => LiteralValue::from(left_value.lt(&right_value)),
         ast::BinaryOperator::Leq => LiteralValue::from(left_value.le(&right_value)),
         ast::BinaryOperator::Gt => LiteralValue::from(left_value.gt(&right_value)),
         ast::BinaryOperator::Geq => LiteralValue::from(left_value.ge(&right_value)),
         ast::BinaryOperator::LtLt => left_value.left_shift(&right_value),
-        ast::BinaryOperator::GtGt => left_value.unsigned_right_shift(&right_value),
-        ast::BinaryOperator::GtGtGt => left_value.signed_right_shift(&right_value),
+        ast::BinaryOperator::GtGt => left_value.signed_right_shift(&right_value),
+        ast::BinaryOperator::GtGtGt => left_value.unsigned_right_shift(&right_value),
         ast::BinaryOperator::Plus => left_value.add(&right_value),
         ast::BinaryOperator::Minus => left_value.sub(&right_value),
         ast::BinaryOperator::Mult => left_value.mul(&right_value),
         ast::BinaryOperator::Div => left_value.div(&right_value),
         ast::BinaryOperator::Mod => left_value.modulo(&right_value),
+        ast::BinaryOperator::Exp => left_value.pow(&right_value),
         ast::BinaryOperator::Pipe => left_value.bitwise_or(&right_value),
         ast::BinaryOperator::Caret => left_value.bitwise_xor(&right_value),
         ast::BinaryOperator::And => left_value.bitwise_and(&right_value),
-        ast::BinaryOperator::In => unimplemented!(),
-        ast::BinaryOperator::Instanceof => todo!("requires primitive type info"),
     };
 
     Ok(EvaluatorValue::from(evaluated_val))
 }
 
-// Account for short circuiting behaviour
+// Two objects are only equal when they are the same object, an object compared with a primitive
+// is converted to a primitive first. Null and undefined are only equal to each other.
+// https://tc39.es/ecma262/#sec-islooselyequal
+fn loosely_equals(left: EvaluatorValue, right: EvaluatorValue) -> Result<bool, EvaluatorError> {
+    let is_nullish = |value: &EvaluatorValue| {
+        matches!(
+            value,
+            EvaluatorValue::Literal(Literal {
+                value: LiteralValue::Null | LiteralValue::Undefined
+            })
+        )
+    };
+    match (&left, &right) {
+        (EvaluatorValue::Literal(l), EvaluatorValue::Literal(r)) => {
+            Ok(l.value.coerced_eq(&r.value))
+        }
+        _ if is_nullish(&left) || is_nullish(&right) => Ok(false),
+        (EvaluatorValue::Literal(_), EvaluatorValue::Symbol(_))
+        | (EvaluatorValue::Symbol(_), EvaluatorValue::Literal(_)) => Ok(false),
+        (EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_), _) => {
+            loosely_equals(left, to_primitive(right)?)
+        }
+        (_, EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_)) => {
+            loosely_equals(to_primitive(left)?, right)
+        }
+        _ => Ok(left.strict_equals(&right)),
+    }
+}
+
+// Calls valueOf and then toString, and takes the first primitive either returns. An object that
+// inherits no toString converts to its default string form, the way Object.prototype.toString
+// would convert it.
+// https://tc39.es/ecma262/#sec-toprimitive
+pub fn to_primitive(value: EvaluatorValue) -> EvaluatorResult {
+    if let EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_) = value {
+        return Ok(value);
+    }
+    let mut has_to_string = false;
+    for name in ["valueOf", "toString"] {
+        let method = get_member(value.clone(), name)?;
+        if !method.is_callable() {
+            continue;
+        }
+        has_to_string = name == "toString";
+        let result = call_value(method, value.clone(), Vec::new())?;
+        if let EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_) = result {
+            return Ok(result);
+        }
+    }
+    if has_to_string {
+        return Err(EvaluatorError::InvalidType(String::from(
+            "Cannot convert object to primitive value",
+        )));
+    }
+    Ok(EvaluatorValue::from(LiteralValue::from(String::from(
+        value,
+    ))))
+}
+
+// Whether the prototype property of the constructor is on the prototype chain of the value
+// https://262.ecma-international.org/5.1/#sec-15.3.5.3
+fn instance_of(value: EvaluatorValue, constructor: EvaluatorValue) -> Result<bool, EvaluatorError> {
+    if !constructor.is_callable() {
+        return Err(EvaluatorError::InvalidType(String::from(
+            "Right-hand side of 'instanceof' is not callable",
+        )));
+    }
+    let prototype = match get_member(constructor, String::from("prototype"))? {
+        EvaluatorValue::Object(prototype) => prototype,
+        prototype => {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Function has non-object prototype '{}' in instanceof check",
+                prototype
+            )))
+        }
+    };
+    Ok(match value.prototype() {
+        Some(p) => Rc::ptr_eq(&p, &prototype) || p.borrow().inherits_from(&prototype),
+        None => false,
+    })
+}
+
+// Own and inherited properties are both found by the in operator
+// https://262.ecma-international.org/5.1/#sec-11.8.7
+fn has_property(target: EvaluatorValue, key: EvaluatorValue) -> Result<bool, EvaluatorError> {
+    let key = PropertyKey::from(key);
+    match target {
+        EvaluatorValue::Object(obj) => Ok(obj.borrow().has_property(&key)),
+        EvaluatorValue::Array(arr) => {
+            let present = key == "length"
+                || array_index(&key).is_some_and(|index| arr.borrow().get(index).is_some());
+            Ok(present || intrinsics().array_prototype.borrow().has_property(&key))
+        }
+        target @ (EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_)) => Ok(target
+            .function_object()
+            .is_some_and(|obj| obj.borrow().has_property(&key))),
+        EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_) => {
+            Err(EvaluatorError::InvalidType(format!(
+                "Cannot use 'in' operator to search for '{}' in {}",
+                key,
+                String::from(target)
+            )))
+        }
+    }
+}
+
+// Account for short circuiting behaviour, the result is the value of whichever operand decided it
 // https://262.ecma-international.org/5.1/#sec-11.11
 fn eval_logical_expression(
     LogicalExpression {
@@ -239,121 +796,469 @@ fn eval_logical_expression(
     }: LogicalExpression,
     env: Env,
 ) -> EvaluatorResult {
-    let left_value = evaluate(*left, Rc::clone(&env))?;
+    let left_value = eval_expression(*left, Rc::clone(&env))?;
 
-    let left_bool: bool = left_value.into();
+    if short_circuits(&operator, &left_value) {
+        Ok(left_value)
+    } else {
+        eval_expression(*right, env)
+    }
+}
 
-    let evaluated_value = match operator {
-        ast::LogicalOperator::And => {
-            if left_bool {
-                evaluate(*right, Rc::clone(&env))?
-            } else {
-                EvaluatorValue::from(JS_FALSE)
-            }
-        }
-        ast::LogicalOperator::Or => {
-            if left_bool {
-                EvaluatorValue::from(JS_TRUE)
-            } else {
-                evaluate(*right, Rc::clone(&env))?
-            }
-        }
-    };
-    Ok(evaluated_value)
+pub fn short_circuits(operator: &ast::LogicalOperator, left_value: &EvaluatorValue) -> bool {
+    match operator {
+        ast::LogicalOperator::And => !Into::<bool>::into(left_value.clone()),
+        ast::LogicalOperator::Or => left_value.clone().into(),
+        ast::LogicalOperator::Nullish => !matches!(
+            left_value,
+            EvaluatorValue::Literal(Literal {
+                value: LiteralValue::Undefined | LiteralValue::Null,
+            })
+        ),
+    }
 }
 
-fn eval_variable_declaration(
+async fn eval_variable_declaration(
     VariableDeclaration { declarations, kind }: VariableDeclaration,
     env: Env,
-) -> EvaluatorResult {
+    co: Option<Coroutine>,
+) -> CompletionResult {
     for d in declarations {
-        eval_variable_declarator(d, kind.as_str(), Rc::clone(&env))?;
+        eval_variable_declarator(d, kind.as_str(), Rc::clone(&env), co.clone()).await?;
     }
-    Ok(EvaluatorValue::from(JS_NULL))
+    Ok(Completion::empty())
 }
 
-fn eval_variable_declarator(
+async fn eval_variable_declarator(
     VariableDeclarator { id, init }: VariableDeclarator,
     kind: &str,
     env: Env,
+    co: Option<Coroutine>,
 ) -> EvaluatorResult {
+    // var names are bound when the enclosing function is entered, the declaration only assigns
+    if kind == "var" && init.is_none() {
+        return Ok(EvaluatorValue::from(JS_NULL));
+    }
+
     let value = if let Some(init) = init {
-        evaluate(*init, Rc::clone(&env))?
+        eval_expression_in(*init, Rc::clone(&env), co.clone()).await?
     } else {
         EvaluatorValue::from(JS_UNDEFINED)
     };
-
-    env.borrow_mut()
-        .define(id, value, kind)
-        .map_err(EvaluatorError::EnvironmentError)?;
+    bind_pattern(*id, value, BindingKind::Declaration(kind), env, co).await?;
 
     Ok(EvaluatorValue::from(JS_NULL))
 }
 
-fn eval_identifier(id: Identifier, env: Env) -> EvaluatorResult {
-    let evaluator_value = env
-        .borrow()
-        .lookup(&id)
-        .map_or(EvaluatorValue::from(JS_UNDEFINED), |v| v.value);
-    Ok(evaluator_value)
+// How the names in a pattern receive their values. Declarations initialize the bindings created
+// for them in the current frame, assignments write through references like `x = value` would.
+#[derive(Clone, Copy)]
+pub enum BindingKind<'a> {
+    Declaration(&'a str),
+    Assignment,
+}
+
+// Binds a value to a pattern, taking it apart for object and array patterns. Shared by variable
+// declarations, parameters, catch clauses and destructuring assignments. Default values and
+// computed keys may suspend a generator or async function, so binding is async too.
+// https://tc39.es/ecma262/#sec-runtime-semantics-bindinginitialization
+// https://tc39.es/ecma262/#sec-runtime-semantics-destructuringassignmentevaluation
+pub fn bind_pattern<'a>(
+    pattern: Node,
+    value: EvaluatorValue,
+    kind: BindingKind<'a>,
+    env: Env,
+    co: Option<Coroutine>,
+) -> Pin<Box<dyn Future<Output = Result<(), EvaluatorError>> + 'a>> {
+    Box::pin(async move {
+        match pattern.kind {
+            NodeKind::Identifier(id) => match kind {
+                BindingKind::Declaration(kind) if kind != "var" => env
+                    .borrow_mut()
+                    .initialize(id, value, kind)
+                    .map_err(EvaluatorError::EnvironmentError),
+                _ => Reference::Binding(id, pattern.loc).put_value(value, env),
+            },
+            NodeKind::AssignmentPattern(ast::AssignmentPattern { left, right }) => {
+                let value = match value {
+                    EvaluatorValue::Literal(Literal {
+                        value: LiteralValue::Undefined,
+                    }) => eval_expression_in(*right, Rc::clone(&env), co.clone()).await?,
+                    value => value,
+                };
+                bind_pattern(*left, value, kind, env, co).await
+            }
+            NodeKind::ObjectPattern(ast::ObjectPattern { properties }) => {
+                bind_object_pattern(properties, value, kind, env, co).await
+            }
+            NodeKind::ArrayPattern(ast::ArrayPattern { elements }) => {
+                bind_array_pattern(elements, value, kind, env, co).await
+            }
+            NodeKind::MemberExpression(_) if matches!(kind, BindingKind::Assignment) => {
+                eval_reference(pattern, Rc::clone(&env), co)
+                    .await?
+                    .put_value(value, env)
+            }
+            _ => Err(EvaluatorError::SyntaxError(String::from(
+                "Invalid destructuring assignment target",
+            ))),
+        }
+    })
+}
+
+// https://tc39.es/ecma262/#sec-runtime-semantics-propertybindinginitialization
+async fn bind_object_pattern(
+    properties: Vec<ast::ObjectPatternProperty>,
+    value: EvaluatorValue,
+    kind: BindingKind<'_>,
+    env: Env,
+    co: Option<Coroutine>,
+) -> Result<(), EvaluatorError> {
+    if let EvaluatorValue::Literal(Literal {
+        value: LiteralValue::Undefined | LiteralValue::Null,
+    }) = value
+    {
+        return Err(EvaluatorError::InvalidType(format!(
+            "Cannot destructure '{}' as it is {}.",
+            String::from(value.clone()),
+            String::from(value)
+        )));
+    }
+    let mut used_keys = Vec::new();
+    for property in properties {
+        match property {
+            ast::ObjectPatternProperty::AssignmentProperty(ast::AssignmentProperty {
+                key,
+                value: target,
+                computed,
+                ..
+            }) => {
+                let key = eval_member_key(*key, computed, Rc::clone(&env), co.clone()).await?;
+                let property_value = get_member(value.clone(), key.clone())?;
+                used_keys.push(key);
+                bind_pattern(*target, property_value, kind, Rc::clone(&env), co.clone()).await?;
+            }
+            // The rest object gets the own properties that were not named before it
+            ast::ObjectPatternProperty::RestElement(ast::RestElement { argument }) => {
+                let rest = Object::new(Some(Rc::clone(&intrinsics().object_prototype)));
+                copy_data_properties(&rest, value.clone(), &used_keys)?;
+                bind_pattern(
+                    *argument,
+                    EvaluatorValue::Object(rest),
+                    kind,
+                    Rc::clone(&env),
+                    co.clone(),
+                )
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Copies the own properties of a value onto an object, except for the excluded keys. Spreading
+// undefined or null copies nothing.
+// https://tc39.es/ecma262/#sec-copydataproperties
+fn copy_data_properties(
+    target: &JsObject,
+    source: EvaluatorValue,
+    excluded_keys: &[PropertyKey],
+) -> Result<(), EvaluatorError> {
+    for key in own_keys(&source) {
+        if !excluded_keys.contains(&key) {
+            let value = get_member(source.clone(), key.clone())?;
+            target.borrow_mut().set(key, value);
+        }
+    }
+    Ok(())
+}
+
+// The names of the own properties of a value that spreading it copies
+fn own_keys(value: &EvaluatorValue) -> Vec<PropertyKey> {
+    match value {
+        EvaluatorValue::Object(obj) => obj.borrow().properties.keys().cloned().collect(),
+        EvaluatorValue::Array(arr) => {
+            let arr = arr.borrow();
+            arr.entries()
+                .map(|(index, _)| PropertyKey::from(index.to_string()))
+                .chain(arr.properties.keys().cloned())
+                .collect()
+        }
+        EvaluatorValue::Literal(Literal {
+            value: LiteralValue::String(s),
+        }) => (0..s.chars().count())
+            .map(|index| PropertyKey::from(index.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Array patterns take their elements from the value's iterator, which is closed when the pattern
+// does not run it to completion
+// https://tc39.es/ecma262/#sec-runtime-semantics-iteratorbindinginitialization
+async fn bind_array_pattern(
+    elements: Vec<Option<Box<Node>>>,
+    value: EvaluatorValue,
+    kind: BindingKind<'_>,
+    env: Env,
+    co: Option<Coroutine>,
+) -> Result<(), EvaluatorError> {
+    let mut iterator = get_iterator(value)?;
+    let result = bind_iterator_elements(elements, &mut iterator, kind, env, co).await;
+    match result {
+        Ok(()) => iterator.close(),
+        // the error the pattern ended with is kept over any error from closing the iterator
+        Err(e) => {
+            let _ = iterator.close();
+            Err(e)
+        }
+    }
+}
+
+async fn bind_iterator_elements(
+    elements: Vec<Option<Box<Node>>>,
+    iterator: &mut IteratorRecord,
+    kind: BindingKind<'_>,
+    env: Env,
+    co: Option<Coroutine>,
+) -> Result<(), EvaluatorError> {
+    for element in elements {
+        let Some(element) = element else {
+            iterator.step()?;
+            continue;
+        };
+        match element.kind {
+            NodeKind::RestElement(ast::RestElement { argument }) => {
+                let mut rest = Vec::new();
+                while let Some(value) = iterator.step()? {
+                    rest.push(Some(value));
+                }
+                let rest = EvaluatorValue::from(rest);
+                bind_pattern(*argument, rest, kind, Rc::clone(&env), co.clone()).await?;
+            }
+            _ => {
+                let element_value = iterator
+                    .step()?
+                    .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED));
+                bind_pattern(*element, element_value, kind, Rc::clone(&env), co.clone()).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn eval_identifier(id: Identifier, loc: Option<SourceLocation>, env: Env) -> EvaluatorResult {
+    match env.borrow().lookup(&id) {
+        Some(Variable {
+            value: Some(value), ..
+        }) => Ok(value),
+        Some(Variable { value: None, .. }) => Err(EvaluatorError::EnvironmentError(
+            EnvironmentError::UninitializedVariable(id.name),
+        )),
+        None => Err(EvaluatorError::ReferenceError(id, loc)),
+    }
+}
+
+// https://262.ecma-international.org/5.1/#sec-8.7
+// The target of an assignment or update, resolved once so that the object and key expressions of
+// a member target are not evaluated a second time when the target is written back.
+enum Reference {
+    Binding(Identifier, Option<SourceLocation>),
+    Member(EvaluatorValue, PropertyKey),
+    Private(EvaluatorValue, PrivateName),
+}
+
+impl Reference {
+    fn get_value(&self, env: Env) -> EvaluatorResult {
+        match self {
+            Reference::Binding(id, loc) => eval_identifier(id.clone(), loc.clone(), env),
+            Reference::Member(target, key) => get_member(target.clone(), key.clone()),
+            Reference::Private(target, name) => get_private(target.clone(), name),
+        }
+    }
+
+    fn put_value(self, value: EvaluatorValue, env: Env) -> Result<(), EvaluatorError> {
+        match self {
+            Reference::Binding(id, loc) => {
+                let result = env.borrow_mut().update(id.clone(), value.clone());
+                match result {
+                    // Assigning to an undeclared identifier creates a global variable, unless the
+                    // code is strict
+                    // https://tc39.es/ecma262/#sec-putvalue
+                    Err(EnvironmentError::UndefinedVariable) if !env.borrow().is_strict() => {
+                        env.borrow_mut().define_global(id, value);
+                        Ok(())
+                    }
+                    Err(EnvironmentError::UndefinedVariable) => {
+                        Err(EvaluatorError::ReferenceError(id, loc))
+                    }
+                    result => result.map_err(EvaluatorError::EnvironmentError),
+                }
+            }
+            Reference::Member(target, key) => set_member(target, key, value),
+            Reference::Private(target, name) => set_private(target, &name, value),
+        }
+    }
+}
+
+async fn eval_reference(
+    node: Node,
+    env: Env,
+    co: Option<Coroutine>,
+) -> Result<Reference, EvaluatorError> {
+    match node.kind {
+        NodeKind::Identifier(id) => Ok(Reference::Binding(id, node.loc)),
+        NodeKind::MemberExpression(MemberExpression {
+            object,
+            property,
+            computed,
+        }) => {
+            // Writes through super land on this, there are no setters found through super yet
+            let target = match object.kind {
+                NodeKind::Super(_) => eval_this(&env)?,
+                _ => eval_expression_in(*object, Rc::clone(&env), co.clone()).await?,
+            };
+            if let NodeKind::PrivateIdentifier(id) = property.kind {
+                let name = resolve_private_name(&id, &env)?;
+                return Ok(Reference::Private(target, name));
+            }
+            let key = eval_member_key(*property, computed, env, co).await?;
+            Ok(Reference::Member(target, key))
+        }
+        _ => Err(EvaluatorError::SyntaxError(String::from(
+            "Invalid left-hand side in assignment",
+        ))),
+    }
 }
 
-fn eval_assignment_expr(
+// The target is resolved and, for compound assignments, read before the right side is
+// evaluated, which may suspend a generator or async function in between
+// https://tc39.es/ecma262/#sec-assignment-operators-runtime-semantics-evaluation
+pub async fn eval_assignment_expr(
     AssignmentExpression {
         left,
         right,
         operator,
     }: AssignmentExpression,
     env: Env,
+    co: Option<Coroutine>,
 ) -> EvaluatorResult {
-    let right_copy = right.clone();
-    let right_value = evaluate(*right, Rc::clone(&env))?;
+    // [a, b] = [b, a] takes the value apart, and evaluates to the whole value
+    if let NodeKind::ObjectPattern(_) | NodeKind::ArrayPattern(_) = left.kind {
+        let value = eval_expression_in(*right, Rc::clone(&env), co.clone()).await?;
+        bind_pattern(*left, value.clone(), BindingKind::Assignment, env, co).await?;
+        return Ok(value);
+    }
+    let reference = eval_reference(*left, Rc::clone(&env), co.clone()).await?;
 
-    match operator {
-        AssignmentOperator::Eq => {}
-        _ => unimplemented!("Only Assignment using = allowed"),
+    let logical_operator = match operator {
+        AssignmentOperator::OrEq => Some(ast::LogicalOperator::Or),
+        AssignmentOperator::AndAndEq => Some(ast::LogicalOperator::And),
+        AssignmentOperator::NullishEq => Some(ast::LogicalOperator::Nullish),
+        _ => None,
     };
-
-    if let NodeKind::MemberExpression(_) = right_copy.kind {
-        todo!("Updating a property of an object")
-    } else if let NodeKind::Identifier(id) = left.kind {
-        env.borrow_mut()
-            .update(id, right_value)
-            .map_err(EvaluatorError::EnvironmentError)?;
-    } else {
-        unreachable!()
+    if let Some(logical_operator) = logical_operator {
+        let left_value = reference.get_value(Rc::clone(&env))?;
+        if short_circuits(&logical_operator, &left_value) {
+            return Ok(left_value);
+        }
+        let right_value = eval_expression_in(*right, Rc::clone(&env), co).await?;
+        reference.put_value(right_value.clone(), env)?;
+        return Ok(right_value);
     }
-    Ok(EvaluatorValue::from(JS_UNDEFINED))
+
+    let binary_operator = match operator {
+        AssignmentOperator::Eq => None,
+        AssignmentOperator::PlusEq => Some(ast::BinaryOperator::Plus),
+        AssignmentOperator::MinusEq => Some(ast::BinaryOperator::Minus),
+        AssignmentOperator::MultEq => Some(ast::BinaryOperator::Mult),
+        AssignmentOperator::DivEq => Some(ast::BinaryOperator::Div),
+        AssignmentOperator::ModEq => Some(ast::BinaryOperator::Mod),
+        AssignmentOperator::ExpEq => Some(ast::BinaryOperator::Exp),
+        AssignmentOperator::LtLtEq => Some(ast::BinaryOperator::LtLt),
+        AssignmentOperator::GtGtEq => Some(ast::BinaryOperator::GtGt),
+        AssignmentOperator::GtGtGtEq => Some(ast::BinaryOperator::GtGtGt),
+        AssignmentOperator::PipeEq => Some(ast::BinaryOperator::Pipe),
+        AssignmentOperator::CaretEq => Some(ast::BinaryOperator::Caret),
+        AssignmentOperator::AndEq => Some(ast::BinaryOperator::And),
+        AssignmentOperator::OrEq | AssignmentOperator::AndAndEq | AssignmentOperator::NullishEq => {
+            unreachable!()
+        }
+    };
+
+    let value = match binary_operator {
+        Some(binary_operator) => {
+            let left_value = reference.get_value(Rc::clone(&env))?;
+            let right_value = eval_expression_in(*right, Rc::clone(&env), co).await?;
+            apply_binary_operator(binary_operator, left_value, right_value)?
+        }
+        None => eval_expression_in(*right, Rc::clone(&env), co).await?,
+    };
+    reference.put_value(value.clone(), env)?;
+    Ok(value)
 }
 
-// TODO: how to hoist functions?
-fn eval_function_declaration(
-    FunctionDeclaration {
-        id, params, body, ..
-    }: FunctionDeclaration,
+// https://tc39.es/ecma262/#sec-postfix-increment-operator
+pub async fn eval_update_expression(
+    UpdateExpression {
+        operator,
+        prefix,
+        argument,
+    }: UpdateExpression,
     env: Env,
+    co: Option<Coroutine>,
 ) -> EvaluatorResult {
-    let closure = Closure::new(params, body, Some(id.name.to_owned()), Rc::clone(&env));
-    // Currently does not hoist
-    env.borrow_mut()
-        .define(id, EvaluatorValue::from(closure), "let")
-        .map_err(EvaluatorError::EnvironmentError)?;
-    Ok(EvaluatorValue::from(JS_UNDEFINED))
+    let reference = eval_reference(*argument, Rc::clone(&env), co).await?;
+    let old_value = match reference.get_value(Rc::clone(&env))? {
+        EvaluatorValue::Literal(Literal { value }) => {
+            LiteralValue::Number(Into::<JsNumber>::into(value))
+        }
+        _ => JS_NAN,
+    };
+    let new_value = match operator {
+        UpdateOperator::PlusPlus => old_value.add(&LiteralValue::from(1.0)),
+        UpdateOperator::MinusMinus => old_value.sub(&LiteralValue::from(1.0)),
+    };
+    reference.put_value(EvaluatorValue::from(new_value.clone()), env)?;
+
+    if prefix {
+        Ok(EvaluatorValue::from(new_value))
+    } else {
+        Ok(EvaluatorValue::from(old_value))
+    }
 }
 
-// TODO: no lexical this binding present yet
 fn eval_function_expression(
     FunctionExpression {
-        id, params, body, ..
+        id,
+        params,
+        body,
+        generator,
+        is_async,
     }: FunctionExpression,
     env: Env,
 ) -> EvaluatorResult {
-    let closure = Closure::new(params, body, id.map(|id| id.name), Rc::clone(&env));
+    let strict = env.borrow().is_strict() || has_use_strict(&body.body);
+    let closure = Closure::new(
+        params,
+        body,
+        id.map(|id| id.name),
+        Rc::clone(&env),
+        FunctionKind::Normal,
+        strict,
+    )
+    .with_generator(generator)
+    .with_async(is_async);
     Ok(EvaluatorValue::from(closure))
 }
 
 fn eval_arrow_function(
-    ArrowFunctionExpression { params, body, .. }: ArrowFunctionExpression,
+    ArrowFunctionExpression {
+        params,
+        body,
+        is_async,
+        ..
+    }: ArrowFunctionExpression,
     env: Env,
 ) -> EvaluatorResult {
     let normalized_body = match body.kind {
@@ -367,165 +1272,3189 @@ fn eval_arrow_function(
             }],
         },
     };
-    let closure = Closure::new(params, normalized_body, None, Rc::clone(&env));
+    let strict = env.borrow().is_strict() || has_use_strict(&normalized_body.body);
+    let closure = Closure::new(
+        params,
+        normalized_body,
+        None,
+        Rc::clone(&env),
+        FunctionKind::Arrow,
+        strict,
+    )
+    .with_async(is_async);
     Ok(EvaluatorValue::from(closure))
 }
 
-// If we call the function with fewer than required args, the rest should default to undefined
-// If we call the function with more than the required args, the rest should be ignored
-fn eval_call_expr(
-    CallExpression { callee, arguments }: CallExpression,
+// The class is evaluated like a class expression of the same name, so that a yield or await in
+// its heritage suspends the body it is declared in
+async fn eval_class_declaration(
+    ClassDeclaration {
+        id,
+        super_class,
+        body,
+    }: ClassDeclaration,
     env: Env,
-) -> EvaluatorResult {
-    let closure = match callee {
-        ast::MemberIdentifier::Identifier(id) => {
-            if let EvaluatorValue::Closure(c) = eval_identifier(id, Rc::clone(&env))? {
-                c
-            } else {
-                unreachable!("Trying to call a non function")
-            }
-        }
-        ast::MemberIdentifier::MemberExpression(e) => {
-            if let EvaluatorValue::Closure(c) = eval_member_expression(e, Rc::clone(&env))? {
-                c
-            } else {
-                return Err(EvaluatorError::InvalidType(String::from(
-                    "Received a non callable value",
-                )));
-            }
-        }
-        ast::MemberIdentifier::Expression(_) => todo!(),
-        ast::MemberIdentifier::Super(_) => todo!(),
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let class = Node {
+        loc: None,
+        kind: NodeKind::ClassExpression(Class {
+            id: Some(id.clone()),
+            super_class,
+            body,
+        }),
     };
+    let class = eval_expression_in(class, Rc::clone(&env), co).await?;
+    env.borrow_mut()
+        .initialize(id, class, "let")
+        .map_err(EvaluatorError::EnvironmentError)?;
+    Ok(Completion::empty())
+}
 
-    // eval arguments
-    let mut arg_values: Vec<EvaluatorValue> = arguments
-        .into_iter()
-        .take(closure.parameters.len())
-        .map(|arg| evaluate(*arg, Rc::clone(&env)).expect("Unable to evaluate argument"))
-        .collect();
+// Class bodies are strict mode code. The class name is also bound inside the class, as a
+// constant that methods keep referring to when the outer binding is reassigned.
+// https://tc39.es/ecma262/#sec-runtime-semantics-classdefinitionevaluation
+fn eval_class(
+    Class {
+        id,
+        super_class,
+        body,
+    }: Class,
+    env: Env,
+) -> EvaluatorResult {
+    let class_env = env.borrow_mut().extend(Rc::clone(&env));
+    if let Some(id) = &id {
+        class_env
+            .borrow_mut()
+            .declare_lexical(id.clone(), DeclarationKind::Const)
+            .map_err(EvaluatorError::EnvironmentError)?;
+    }
+    for element in &body.body {
+        let key = match &element.kind {
+            NodeKind::MethodDefinition(m) => &m.key,
+            NodeKind::PropertyDefinition(p) => &p.key,
+            _ => continue,
+        };
+        if let NodeKind::PrivateIdentifier(name) = &key.kind {
+            class_env.borrow_mut().declare_private_name(&name.name);
+        }
+    }
+
+    let intrinsics = intrinsics();
+    let (heritage, prototype_parent, constructor_parent) = match super_class {
+        None => (
+            None,
+            Some(Rc::clone(&intrinsics.object_prototype)),
+            Rc::clone(&intrinsics.function_prototype),
+        ),
+        Some(super_class) => {
+            let parent = eval_expression(*super_class, Rc::clone(&class_env))?;
+            match parent {
+                EvaluatorValue::Literal(Literal {
+                    value: LiteralValue::Null,
+                }) => (
+                    Some(parent),
+                    None,
+                    Rc::clone(&intrinsics.function_prototype),
+                ),
+                parent if is_constructor(&parent) => {
+                    let prototype_parent =
+                        match get_member(parent.clone(), String::from("prototype"))? {
+                            EvaluatorValue::Object(prototype) => Some(prototype),
+                            EvaluatorValue::Literal(Literal {
+                                value: LiteralValue::Null,
+                            }) => None,
+                            prototype => {
+                                return Err(EvaluatorError::InvalidType(format!(
+                                    "Class extends value does not have valid prototype property {}",
+                                    prototype
+                                )))
+                            }
+                        };
+                    let constructor_parent = parent
+                        .function_object()
+                        .expect("constructors are functions");
+                    (Some(parent), prototype_parent, constructor_parent)
+                }
+                parent => {
+                    return Err(EvaluatorError::InvalidType(format!(
+                        "Class extends value {} is not a constructor or null",
+                        parent
+                    )))
+                }
+            }
+        }
+    };
+
+    let prototype = Object::new(prototype_parent);
+    let (constructor_method, elements): (Vec<Node>, Vec<Node>) = body.body.into_iter().partition(
+        |element| matches!(&element.kind, NodeKind::MethodDefinition(m) if m.kind == "constructor"),
+    );
+    let mut class_definition = ClassDefinition {
+        heritage,
+        default_constructor: constructor_method.is_empty(),
+        private_methods: vec![],
+        fields: vec![],
+    };
+    let (params, body) = match constructor_method.into_iter().next().map(|m| m.kind) {
+        Some(NodeKind::MethodDefinition(MethodDefinition { value, .. })) => {
+            (value.params, value.body)
+        }
+        _ => (vec![], BlockStatement { body: vec![] }),
+    };
+    let mut constructor = Closure::new(
+        params,
+        body,
+        id.as_ref().map(|id| id.name.to_owned()),
+        Rc::clone(&class_env),
+        FunctionKind::ClassConstructor,
+        true,
+    );
+    constructor.home_object = Some(Rc::clone(&prototype));
+    let constructor_object = Rc::clone(&constructor.object);
+    constructor_object.borrow_mut().prototype = Some(constructor_parent);
+    constructor_object.borrow_mut().set(
+        String::from("prototype"),
+        EvaluatorValue::Object(Rc::clone(&prototype)),
+    );
 
-    for _ in arg_values.len()..closure.parameters.len() {
-        arg_values.push(EvaluatorValue::from(JS_UNDEFINED));
+    // Methods are defined on the prototype and static methods on the constructor as the class is
+    // evaluated. Static fields and blocks run in order once the class binding is initialized.
+    let mut static_elements = vec![];
+    for element in elements {
+        match element.kind {
+            NodeKind::MethodDefinition(MethodDefinition {
+                key,
+                value,
+                kind,
+                computed,
+                r#static,
+            }) => {
+                let home_object = match r#static {
+                    true => Rc::clone(&constructor_object),
+                    false => Rc::clone(&prototype),
+                };
+                let key = eval_class_element_key(*key, computed, &class_env)?;
+                let mut method = Closure::new(
+                    value.params,
+                    value.body,
+                    Some(match &key {
+                        FieldKey::Public(key) => key.function_name(),
+                        FieldKey::Private(name) => name.to_string(),
+                    }),
+                    Rc::clone(&class_env),
+                    FunctionKind::Method,
+                    true,
+                )
+                .with_generator(value.generator)
+                .with_async(value.is_async);
+                method.home_object = Some(Rc::clone(&home_object));
+                let method = EvaluatorValue::from(method);
+                let (get, set) = match kind.as_str() {
+                    "get" => (Some(method.clone()), None),
+                    "set" => (None, Some(method.clone())),
+                    _ => (None, None),
+                };
+                match key {
+                    FieldKey::Public(key) if kind == "method" => {
+                        home_object.borrow_mut().set(key, method)
+                    }
+                    FieldKey::Public(key) => {
+                        home_object.borrow_mut().define_accessor(key, get, set)
+                    }
+                    FieldKey::Private(name) => {
+                        let element = match kind.as_str() {
+                            "method" => PrivateElement::Method(method),
+                            _ => PrivateElement::Accessor { get, set },
+                        };
+                        match r#static {
+                            true => add_private_method(
+                                &mut constructor_object.borrow_mut().private_elements,
+                                name,
+                                element,
+                            ),
+                            false => add_private_method(
+                                &mut class_definition.private_methods,
+                                name,
+                                element,
+                            ),
+                        }
+                    }
+                }
+            }
+            NodeKind::PropertyDefinition(PropertyDefinition {
+                key,
+                value,
+                computed,
+                r#static,
+            }) => {
+                let home_object = match r#static {
+                    true => Rc::clone(&constructor_object),
+                    false => Rc::clone(&prototype),
+                };
+                let key = eval_class_element_key(*key, computed, &class_env)?;
+                let initializer = value.map(|value| {
+                    let body = BlockStatement {
+                        body: vec![Node {
+                            loc: None,
+                            kind: NodeKind::ReturnStatement(ReturnStatement {
+                                argument: Some(value),
+                            }),
+                        }],
+                    };
+                    class_element_function(body, home_object, &class_env)
+                });
+                let field = ClassField { key, initializer };
+                match r#static {
+                    true => static_elements.push(StaticElement::Field(field)),
+                    false => class_definition.fields.push(field),
+                }
+            }
+            NodeKind::StaticBlock(StaticBlock { body }) => {
+                let block = class_element_function(
+                    BlockStatement { body },
+                    Rc::clone(&constructor_object),
+                    &class_env,
+                );
+                static_elements.push(StaticElement::Block(block));
+            }
+            kind => unreachable!("{:?}", kind),
+        }
     }
 
-    // extend env with arg values
-    let new_env = env.borrow_mut().extend(Rc::clone(&env));
-    // set arg values
-    closure
-        .parameters
-        .into_iter()
-        .zip(arg_values.into_iter())
-        .try_for_each(|(id, value)| {
-            if let NodeKind::Identifier(id) = id.kind {
-                new_env
-                    .borrow_mut()
-                    .define(id, value, "let")
-                    .map_err(EvaluatorError::EnvironmentError)?;
-                Ok(())
-            } else {
-                unreachable!()
+    constructor.class = Some(Rc::new(class_definition));
+    let constructor = EvaluatorValue::from(constructor);
+    prototype
+        .borrow_mut()
+        .set(String::from("constructor"), constructor.clone());
+
+    if let Some(id) = id {
+        class_env
+            .borrow_mut()
+            .initialize(id, constructor.clone(), "const")
+            .map_err(EvaluatorError::EnvironmentError)?;
+    }
+
+    for element in static_elements {
+        match element {
+            StaticElement::Field(field) => define_field(&constructor, field)?,
+            StaticElement::Block(block) => {
+                call_function(block, constructor.clone(), vec![])?;
             }
-        })?;
+        }
+    }
+    Ok(constructor)
+}
 
-    // eval closure body with new env
-    eval_block_statement(closure.body, new_env)
+// Static fields and static blocks are run with the class as this, after it has been defined
+enum StaticElement {
+    Field(ClassField),
+    Block(Closure),
 }
 
-fn eval_return_statement(r: ReturnStatement, env: Env) -> EvaluatorResult {
-    match r.argument {
-        None => Ok(EvaluatorValue::from(JS_UNDEFINED)),
-        Some(argument) => evaluate(*argument, env),
+// Class element names can be private names, which are resolved to the ones the class declared
+fn eval_class_element_key(
+    key: Node,
+    computed: bool,
+    env: &Env,
+) -> Result<FieldKey, EvaluatorError> {
+    match key.kind {
+        NodeKind::PrivateIdentifier(id) => Ok(FieldKey::Private(resolve_private_name(&id, env)?)),
+        _ => Ok(FieldKey::Public(run(eval_member_key(
+            key,
+            computed,
+            Rc::clone(env),
+            None,
+        ))?)),
     }
 }
 
-fn eval_object_expression(
-    ObjectExpression { properties }: ObjectExpression,
+// Field initializers and static blocks are evaluated as methods, where this is the instance or
+// the class and super refers to its parent
+fn class_element_function(body: BlockStatement, home_object: JsObject, env: &Env) -> Closure {
+    let mut function = Closure::new(
+        vec![],
+        body,
+        None,
+        Rc::clone(env),
+        FunctionKind::Method,
+        true,
+    );
+    function.home_object = Some(home_object);
+    function
+}
+
+// A getter and a setter with the same private name make up a single private accessor
+fn add_private_method(
+    elements: &mut Vec<(PrivateName, PrivateElement)>,
+    name: PrivateName,
+    element: PrivateElement,
+) {
+    let existing = elements.iter_mut().find(|(n, _)| *n == name);
+    match (existing, element) {
+        (
+            Some((
+                _,
+                PrivateElement::Accessor {
+                    get: old_get,
+                    set: old_set,
+                },
+            )),
+            PrivateElement::Accessor { get, set },
+        ) => {
+            *old_get = get.or(old_get.take());
+            *old_set = set.or(old_set.take());
+        }
+        (_, element) => elements.push((name, element)),
+    }
+}
+
+// https://tc39.es/ecma262/#sec-definefield
+fn define_field(
+    receiver: &EvaluatorValue,
+    ClassField { key, initializer }: ClassField,
+) -> Result<(), EvaluatorError> {
+    let value = match initializer {
+        Some(initializer) => call_function(initializer, receiver.clone(), vec![])?,
+        None => EvaluatorValue::from(JS_UNDEFINED),
+    };
+    let object = match receiver {
+        EvaluatorValue::Object(obj) => Rc::clone(obj),
+        receiver => match receiver.function_object() {
+            Some(obj) => obj,
+            None => return Ok(()),
+        },
+    };
+    match key {
+        FieldKey::Public(key) => object.borrow_mut().set(key, value),
+        FieldKey::Private(name) => {
+            add_private_element(&object, name, PrivateElement::Field(value))?;
+        }
+    }
+    Ok(())
+}
+
+fn add_private_element(
+    object: &JsObject,
+    name: PrivateName,
+    element: PrivateElement,
+) -> Result<(), EvaluatorError> {
+    let description = name.to_string();
+    match object.borrow_mut().add_private(name, element) {
+        true => Ok(()),
+        false => Err(EvaluatorError::InvalidType(format!(
+            "Cannot initialize {} twice on the same object",
+            description
+        ))),
+    }
+}
+
+// Private methods and then fields are added to an object when a class constructor creates it, or
+// when super() returns it to a derived constructor
+// https://tc39.es/ecma262/#sec-initializeinstanceelements
+fn initialize_instance_elements(
+    receiver: &EvaluatorValue,
+    class: &ClassDefinition,
+) -> Result<(), EvaluatorError> {
+    if let EvaluatorValue::Object(object) = receiver {
+        for (name, element) in &class.private_methods {
+            add_private_element(object, name.clone(), element.clone())?;
+        }
+    }
+    class
+        .fields
+        .iter()
+        .try_for_each(|field| define_field(receiver, field.clone()))
+}
+
+fn resolve_private_name(id: &PrivateIdentifier, env: &Env) -> Result<PrivateName, EvaluatorError> {
+    env.borrow().resolve_private_name(&id.name).ok_or_else(|| {
+        EvaluatorError::SyntaxError(format!(
+            "Private field '#{}' must be declared in an enclosing class",
+            id.name
+        ))
+    })
+}
+
+// The object that holds the private elements of a value, if it can have any
+fn private_elements_holder(target: &EvaluatorValue) -> Option<JsObject> {
+    match target {
+        EvaluatorValue::Object(obj) => Some(Rc::clone(obj)),
+        target => target.function_object(),
+    }
+}
+
+// Reading a private name from an object that was not created by its class is a TypeError, this
+// is the brand check that `#x in obj` tests for without throwing
+// https://tc39.es/ecma262/#sec-privateget
+fn get_private(target: EvaluatorValue, name: &PrivateName) -> EvaluatorResult {
+    let element =
+        private_elements_holder(&target).and_then(|obj| obj.borrow().find_private(name).cloned());
+    match element {
+        Some(PrivateElement::Field(value) | PrivateElement::Method(value)) => Ok(value),
+        Some(PrivateElement::Accessor {
+            get: Some(getter), ..
+        }) => call_value(getter, target, vec![]),
+        Some(PrivateElement::Accessor { get: None, .. }) => Err(EvaluatorError::InvalidType(
+            format!("'{}' was defined without a getter", name),
+        )),
+        None => Err(EvaluatorError::InvalidType(format!(
+            "Cannot read private member {} from an object whose class did not declare it",
+            name
+        ))),
+    }
+}
+
+// https://tc39.es/ecma262/#sec-privateset
+fn set_private(
+    target: EvaluatorValue,
+    name: &PrivateName,
+    value: EvaluatorValue,
+) -> Result<(), EvaluatorError> {
+    let holder = match private_elements_holder(&target) {
+        Some(holder) => holder,
+        None => {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Cannot write private member {} to an object whose class did not declare it",
+                name
+            )))
+        }
+    };
+    let element = holder.borrow().find_private(name).cloned();
+    match element {
+        Some(PrivateElement::Field(_)) => {
+            holder.borrow_mut().set_private_field(name, value);
+            Ok(())
+        }
+        Some(PrivateElement::Method(_)) => Err(EvaluatorError::InvalidType(format!(
+            "Private method '{}' is not writable",
+            name
+        ))),
+        Some(PrivateElement::Accessor {
+            set: Some(setter), ..
+        }) => call_value(setter, target, vec![value]).map(|_| ()),
+        Some(PrivateElement::Accessor { set: None, .. }) => Err(EvaluatorError::InvalidType(
+            format!("'{}' was defined without a setter", name),
+        )),
+        None => Err(EvaluatorError::InvalidType(format!(
+            "Cannot write private member {} to an object whose class did not declare it",
+            name
+        ))),
+    }
+}
+
+// A call through a member expression passes the object the function was read from as this
+fn eval_call_expr(
+    CallExpression { callee, arguments }: CallExpression,
     env: Env,
 ) -> EvaluatorResult {
-    let mut object_frame = HashMap::new();
+    if let NodeKind::Super(_) = callee.kind {
+        return eval_super_call(arguments, env);
+    }
+    let (function, this_value) = eval_callee(*callee, Rc::clone(&env))?;
+    let arg_values = eval_arguments(arguments, env)?;
+    call_value(function, this_value, arg_values)
+}
 
-    properties.into_iter().try_for_each(|p| {
-        let Property { key, value, .. } = p;
+// The function a call applies, along with the this value it is called with
+fn eval_callee(callee: Node, env: Env) -> Result<(EvaluatorValue, EvaluatorValue), EvaluatorError> {
+    Ok(match callee.kind {
+        NodeKind::MemberExpression(MemberExpression {
+            object,
+            property,
+            computed,
+        }) => match object.kind {
+            NodeKind::Super(_) => {
+                let key = run(eval_member_key(*property, computed, Rc::clone(&env), None))?;
+                let this_value = eval_this(&env)?;
+                (get_super_member(key, &env)?, this_value)
+            }
+            _ => {
+                let target = eval_expression(*object, Rc::clone(&env))?;
+                let function = match property.kind {
+                    NodeKind::PrivateIdentifier(id) => {
+                        get_private(target.clone(), &resolve_private_name(&id, &env)?)?
+                    }
+                    _ => {
+                        let key = run(eval_member_key(*property, computed, Rc::clone(&env), None))?;
+                        get_member(target.clone(), key)?
+                    }
+                };
+                (function, target)
+            }
+        },
+        _ => (
+            eval_expression(callee, Rc::clone(&env))?,
+            EvaluatorValue::from(JS_UNDEFINED),
+        ),
+    })
+}
 
-        let evaluated_value = evaluate(*value, Rc::clone(&env))?;
+// The cooked strings with the value of each substitution converted to a string between them
+// https://tc39.es/ecma262/#sec-template-literals-runtime-semantics-evaluation
+fn eval_template_literal(
+    ast::TemplateLiteral {
+        quasis,
+        expressions,
+        ..
+    }: ast::TemplateLiteral,
+    env: Env,
+) -> EvaluatorResult {
+    let mut result = String::new();
+    let mut expressions = expressions.into_iter();
+    for quasi in quasis {
+        result.push_str(quasi.value.cooked.as_deref().unwrap_or_default());
+        if let Some(expression) = expressions.next() {
+            result.push_str(&String::from(eval_expression(
+                *expression,
+                Rc::clone(&env),
+            )?));
+        }
+    }
+    Ok(EvaluatorValue::from(LiteralValue::String(result)))
+}
 
-        let key_string = match key.kind {
-            NodeKind::Identifier(id) => id.name,
-            NodeKind::Literal(l) => l.value.into(),
-            _ => evaluate(*key, Rc::clone(&env))?.into(),
-        };
-        object_frame.insert(key_string, evaluated_value);
-        Ok(())
-    })?;
+// tag`a${b}c` calls tag with the strings of the template followed by the substitution values
+// https://tc39.es/ecma262/#sec-tagged-templates-runtime-semantics-evaluation
+fn eval_tagged_template(
+    ast::TaggedTemplateExpression { tag, quasi }: ast::TaggedTemplateExpression,
+    env: Env,
+) -> EvaluatorResult {
+    let (function, this_value) = eval_callee(*tag, Rc::clone(&env))?;
+    let mut arg_values = vec![get_template_object(&quasi)];
+    for expression in quasi.expressions {
+        arg_values.push(eval_expression(*expression, Rc::clone(&env))?);
+    }
+    call_value(function, this_value, arg_values)
+}
 
-    Ok(EvaluatorValue::Object(Rc::new(RefCell::new(object_frame))))
+// A frozen array of the cooked strings, with a frozen array of the raw strings as its raw
+// property. It is created the first time a site is evaluated and reused after that.
+// https://tc39.es/ecma262/#sec-gettemplateobject
+fn get_template_object(template: &ast::TemplateLiteral) -> EvaluatorValue {
+    let intrinsics = intrinsics();
+    if let Some(strings) = intrinsics.template_map.borrow().get(&template.site) {
+        return EvaluatorValue::Array(Rc::clone(strings));
+    }
+    let frozen_array = |strings: Vec<EvaluatorValue>| {
+        let array = Rc::new(RefCell::new(Array::from(strings)));
+        array.borrow_mut().frozen = true;
+        array
+    };
+    let cooked = template
+        .quasis
+        .iter()
+        .map(|quasi| match &quasi.value.cooked {
+            Some(cooked) => EvaluatorValue::from(LiteralValue::String(cooked.to_owned())),
+            None => EvaluatorValue::from(JS_UNDEFINED),
+        })
+        .collect();
+    let raw = template
+        .quasis
+        .iter()
+        .map(|quasi| EvaluatorValue::from(LiteralValue::String(quasi.value.raw.to_owned())))
+        .collect();
+    let strings = frozen_array(cooked);
+    strings.borrow_mut().properties.insert(
+        PropertyKey::from("raw"),
+        EvaluatorValue::Array(frozen_array(raw)),
+    );
+    intrinsics
+        .template_map
+        .borrow_mut()
+        .insert(template.site, Rc::clone(&strings));
+    EvaluatorValue::Array(strings)
 }
 
-fn eval_member_expression(
-    MemberExpression {
-        object, property, ..
-    }: MemberExpression,
+// Spread arguments are expanded into as many arguments as their iterator produces
+// https://tc39.es/ecma262/#sec-runtime-semantics-argumentlistevaluation
+fn eval_arguments(
+    arguments: Vec<ast::Expression>,
     env: Env,
-) -> EvaluatorResult {
-    if let EvaluatorValue::Object(obj) = evaluate(*object, Rc::clone(&env))? {
-        if let NodeKind::Identifier(id) = property.kind {
-            let name = id.name;
-            let member = match obj.borrow().get(name.as_str()) {
-                Some(value) => match value {
-                    EvaluatorValue::Literal(_) | EvaluatorValue::Closure(_) => value.clone(),
-                    EvaluatorValue::Object(obj) => EvaluatorValue::Object(Rc::clone(&obj)),
-                },
-                None => {
-                    println!("here");
-                    EvaluatorValue::from(JS_UNDEFINED)
-                }
-            };
-            Ok(member)
-        } else {
-            unreachable!()
+) -> Result<Vec<EvaluatorValue>, EvaluatorError> {
+    let mut arg_values = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        match argument.kind {
+            NodeKind::SpreadElement(ast::SpreadElement { argument }) => {
+                let iterable = eval_expression(*argument, Rc::clone(&env))?;
+                arg_values.extend(iterate_to_list(iterable)?);
+            }
+            _ => arg_values.push(eval_expression(*argument, Rc::clone(&env))?),
         }
-    } else {
+    }
+    Ok(arg_values)
+}
+
+// https://tc39.es/ecma262/#sec-iterator-records
+pub struct IteratorRecord {
+    pub iterator: EvaluatorValue,
+    pub next_method: EvaluatorValue,
+    pub done: bool,
+}
+
+// https://tc39.es/ecma262/#sec-getiterator
+pub fn get_iterator(value: EvaluatorValue) -> Result<IteratorRecord, EvaluatorError> {
+    let method = match &value {
+        EvaluatorValue::Literal(Literal {
+            value: LiteralValue::Undefined | LiteralValue::Null,
+        }) => None,
+        _ => Some(get_member(
+            value.clone(),
+            intrinsics().symbol_iterator.clone(),
+        )?),
+    };
+    let Some(method) = method.filter(|method| method.is_callable()) else {
+        return Err(EvaluatorError::InvalidType(format!(
+            "{} is not iterable",
+            value
+        )));
+    };
+    let iterator = call_value(method, value, vec![])?;
+    if let EvaluatorValue::Literal(_) = iterator {
         return Err(EvaluatorError::InvalidType(String::from(
-            "Invalid object lookup",
+            "Result of the Symbol.iterator method is not an object",
         )));
     }
+    let next_method = get_member(iterator.clone(), String::from("next"))?;
+    Ok(IteratorRecord {
+        iterator,
+        next_method,
+        done: false,
+    })
 }
 
-fn eval_if_statement(
-    IfStatement {
-        test,
-        consequent,
-        alternate,
-    }: IfStatement,
+impl IteratorRecord {
+    // The next value, or None once the iterator is done. An iterator that throws is done as well,
+    // it is not closed afterwards.
+    // https://tc39.es/ecma262/#sec-iteratorstepvalue
+    pub fn step(&mut self) -> Result<Option<EvaluatorValue>, EvaluatorError> {
+        if self.done {
+            return Ok(None);
+        }
+        let value = self.next_value();
+        if !matches!(value, Ok(Some(_))) {
+            self.done = true;
+        }
+        value
+    }
+
+    fn next_value(&self) -> Result<Option<EvaluatorValue>, EvaluatorError> {
+        let result = call_value(self.next_method.clone(), self.iterator.clone(), vec![])?;
+        if let EvaluatorValue::Literal(_) = result {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Iterator result {} is not an object",
+                result
+            )));
+        }
+        if get_member(result.clone(), String::from("done"))?.into() {
+            return Ok(None);
+        }
+        get_member(result, String::from("value")).map(Some)
+    }
+
+    // Lets an iterator that was not run to completion release what it holds, by calling its
+    // return method
+    // https://tc39.es/ecma262/#sec-iteratorclose
+    pub fn close(&mut self) -> Result<(), EvaluatorError> {
+        if self.done {
+            return Ok(());
+        }
+        self.done = true;
+        let return_method = get_member(self.iterator.clone(), String::from("return"))?;
+        if let EvaluatorValue::Literal(Literal {
+            value: LiteralValue::Undefined | LiteralValue::Null,
+        }) = return_method
+        {
+            return Ok(());
+        }
+        match call_value(return_method, self.iterator.clone(), vec![])? {
+            EvaluatorValue::Literal(_) => Err(EvaluatorError::InvalidType(String::from(
+                "Iterator result is not an object",
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+// https://tc39.es/ecma262/#sec-iteratortolist
+fn iterate_to_list(value: EvaluatorValue) -> Result<Vec<EvaluatorValue>, EvaluatorError> {
+    let mut iterator = get_iterator(value)?;
+    let mut values = Vec::new();
+    while let Some(value) = iterator.step()? {
+        values.push(value);
+    }
+    Ok(values)
+}
+
+// Calls a function value, whether it was written in js or is built in
+pub fn call_value(
+    function: EvaluatorValue,
+    this_value: EvaluatorValue,
+    arg_values: Vec<EvaluatorValue>,
+) -> EvaluatorResult {
+    match function {
+        EvaluatorValue::Closure(closure) => call_function(closure, this_value, arg_values),
+        EvaluatorValue::NativeFunction(f) => (f.function)(this_value, arg_values),
+        _ => Err(EvaluatorError::InvalidType(String::from(
+            "Received a non callable value",
+        ))),
+    }
+}
+
+// https://tc39.es/ecma262/#sec-isconstructor
+fn is_constructor(value: &EvaluatorValue) -> bool {
+    match value {
+        EvaluatorValue::Closure(c) => {
+            !c.generator
+                && !c.is_async
+                && matches!(
+                    c.kind,
+                    FunctionKind::Normal | FunctionKind::ClassConstructor
+                )
+        }
+        EvaluatorValue::NativeFunction(f) => f.constructor,
+        _ => false,
+    }
+}
+
+fn eval_new_expression(
+    NewExpression { callee, arguments }: NewExpression,
     env: Env,
 ) -> EvaluatorResult {
-    let test_value: bool = evaluate(*test, Rc::clone(&env))?.into();
+    let callee_name = match &callee.kind {
+        NodeKind::Identifier(id) => Some(id.name.to_owned()),
+        _ => None,
+    };
+    let constructor = eval_expression(*callee, Rc::clone(&env))?;
+    let arg_values = eval_arguments(arguments, Rc::clone(&env))?;
 
-    if test_value {
-        evaluate(*consequent, Rc::clone(&env))?;
-    } else if let Some(alternate) = alternate {
-        evaluate(*alternate, Rc::clone(&env))?;
+    if !is_constructor(&constructor) {
+        return Err(EvaluatorError::InvalidType(format!(
+            "{} is not a constructor",
+            callee_name.unwrap_or_else(|| constructor.to_string())
+        )));
     }
+    construct(constructor.clone(), arg_values, constructor)
+}
 
-    Ok(EvaluatorValue::from(JS_UNDEFINED))
+// The new object inherits from the prototype property of new.target, which is the class new was
+// applied to even when the object is created by a parent class
+// https://tc39.es/ecma262/#sec-ordinarycreatefromconstructor
+fn create_from_constructor(new_target: &EvaluatorValue) -> EvaluatorResult {
+    let prototype = match get_member(new_target.clone(), String::from("prototype"))? {
+        EvaluatorValue::Object(prototype) => prototype,
+        _ => Rc::clone(&intrinsics().object_prototype),
+    };
+    Ok(EvaluatorValue::Object(Object::new(Some(prototype))))
 }
 
-fn eval_conditional_expression(
-    ConditionalExpression {
-        test,
-        alternate,
-        consequent,
-    }: ConditionalExpression,
-    env: Env,
+// The result is the object passed as this, unless the constructor returns an object of its own
+// https://tc39.es/ecma262/#sec-ecmascript-function-objects-construct-argumentslist
+pub fn construct(
+    constructor: EvaluatorValue,
+    arg_values: Vec<EvaluatorValue>,
+    new_target: EvaluatorValue,
 ) -> EvaluatorResult {
-    let test_value: bool = evaluate(*test, Rc::clone(&env))?.into();
+    match constructor {
+        EvaluatorValue::Closure(closure)
+            if closure.class.as_ref().is_some_and(|c| c.heritage.is_some()) =>
+        {
+            // constructor(...args) { super(...args) }
+            if let Some(class) = closure.class.as_ref().filter(|c| c.default_constructor) {
+                let this_value = construct(super_constructor(&closure)?, arg_values, new_target)?;
+                initialize_instance_elements(&this_value, class)?;
+                return Ok(this_value);
+            }
+            let (result, env) = run_function(
+                closure,
+                ThisBinding::Uninitialized,
+                arg_values,
+                Some(new_target),
+            )?;
+            match result {
+                Some(EvaluatorValue::Literal(Literal {
+                    value: LiteralValue::Undefined,
+                }))
+                | None => env
+                    .borrow()
+                    .this_binding()
+                    .map_err(EvaluatorError::EnvironmentError),
+                Some(EvaluatorValue::Literal(_)) => Err(EvaluatorError::InvalidType(String::from(
+                    "Derived constructors may only return object or undefined",
+                ))),
+                Some(result) => Ok(result),
+            }
+        }
+        EvaluatorValue::Closure(closure) => {
+            let this_value = create_from_constructor(&new_target)?;
+            if let Some(class) = &closure.class {
+                initialize_instance_elements(&this_value, class)?;
+            }
+            let (result, _) = run_function(
+                closure,
+                ThisBinding::Initialized(this_value.clone()),
+                arg_values,
+                Some(new_target),
+            )?;
+            match result {
+                None | Some(EvaluatorValue::Literal(_)) => Ok(this_value),
+                Some(result) => Ok(result),
+            }
+        }
+        EvaluatorValue::NativeFunction(f) => {
+            let this_value = create_from_constructor(&new_target)?;
+            match (f.function)(this_value.clone(), arg_values)? {
+                EvaluatorValue::Literal(_) => Ok(this_value),
+                result => Ok(result),
+            }
+        }
+        constructor => Err(EvaluatorError::InvalidType(format!(
+            "{} is not a constructor",
+            constructor
+        ))),
+    }
+}
 
-    let body_val = if test_value {
-        evaluate(*consequent, Rc::clone(&env))?
+// The constructor named in the extends clause of the class the function belongs to
+// https://tc39.es/ecma262/#sec-getsuperconstructor
+fn super_constructor(function: &Closure) -> EvaluatorResult {
+    match function.class.as_ref().and_then(|c| c.heritage.clone()) {
+        Some(parent) if is_constructor(&parent) => Ok(parent),
+        Some(parent) => Err(EvaluatorError::InvalidType(format!(
+            "Super constructor {} of anonymous class is not a constructor",
+            parent
+        ))),
+        None => Err(EvaluatorError::SyntaxError(String::from(
+            "'super' keyword unexpected here",
+        ))),
+    }
+}
+
+// super(...) constructs this with the parent class, passing on new.target
+// https://tc39.es/ecma262/#sec-super-keyword-runtime-semantics-evaluation
+fn eval_super_call(arguments: Vec<ast::Expression>, env: Env) -> EvaluatorResult {
+    let frame = env.borrow().this_environment();
+    let (function, new_target) = match frame {
+        Some(FunctionFrame {
+            function: Some(function),
+            new_target: Some(new_target),
+            ..
+        }) => (function, new_target),
+        _ => {
+            return Err(EvaluatorError::SyntaxError(String::from(
+                "'super' keyword unexpected here",
+            )))
+        }
+    };
+    let parent = super_constructor(&function)?;
+    let arg_values = eval_arguments(arguments, Rc::clone(&env))?;
+    let this_value = construct(parent, arg_values, new_target)?;
+    env.borrow_mut()
+        .bind_this(this_value.clone())
+        .map_err(EvaluatorError::EnvironmentError)?;
+    if let Some(class) = &function.class {
+        initialize_instance_elements(&this_value, class)?;
+    }
+    Ok(this_value)
+}
+
+// super.name looks the property up on the prototype of the object the method was defined on
+// https://tc39.es/ecma262/#sec-super-keyword
+fn get_super_member(key: PropertyKey, env: &Env) -> EvaluatorResult {
+    let home_object = env
+        .borrow()
+        .this_environment()
+        .and_then(|frame| frame.function)
+        .and_then(|function| function.home_object);
+    let base = match home_object {
+        Some(home_object) => home_object.borrow().prototype.clone(),
+        None => {
+            return Err(EvaluatorError::SyntaxError(String::from(
+                "'super' keyword unexpected here",
+            )))
+        }
+    };
+    match base {
+        Some(base) => get_property(&base, &key, eval_this(env)?),
+        None => Ok(EvaluatorValue::from(JS_UNDEFINED)),
+    }
+}
+
+fn eval_this(env: &Env) -> EvaluatorResult {
+    env.borrow()
+        .this_binding()
+        .map_err(EvaluatorError::EnvironmentError)
+}
+
+fn eval_meta_property(MetaProperty { meta, property }: MetaProperty, env: Env) -> EvaluatorResult {
+    match (meta.name.as_str(), property.name.as_str()) {
+        ("new", "target") => Ok(env
+            .borrow()
+            .new_target()
+            .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED))),
+        ("import", "meta") => import_meta(&env),
+        (meta, property) => unimplemented!("{}.{}", meta, property),
+    }
+}
+
+// https://tc39.es/ecma262/#sec-ordinarycallbindthis
+fn call_function(
+    closure: Closure,
+    this_value: EvaluatorValue,
+    arg_values: Vec<EvaluatorValue>,
+) -> EvaluatorResult {
+    let this = match closure.kind {
+        FunctionKind::ClassConstructor => {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Class constructor {} cannot be invoked without 'new'",
+                closure.name.as_deref().unwrap_or("(anonymous)")
+            )))
+        }
+        FunctionKind::Arrow => ThisBinding::Lexical,
+        _ if closure.strict => ThisBinding::Initialized(this_value),
+        _ => match this_value {
+            EvaluatorValue::Literal(Literal {
+                value: LiteralValue::Undefined | LiteralValue::Null,
+            }) => match closure.env.borrow().global_this() {
+                Some(global_this) => ThisBinding::Initialized(global_this),
+                None => ThisBinding::Initialized(this_value),
+            },
+            this_value => ThisBinding::Initialized(this_value),
+        },
+    };
+    // The parameters are bound right away, the body only runs once the generator is resumed
+    // https://tc39.es/ecma262/#sec-runtime-semantics-evaluategeneratorbody
+    if closure.generator && closure.is_async {
+        return Err(EvaluatorError::SyntaxError(String::from(
+            "async generator functions are not supported yet",
+        )));
+    }
+    if closure.generator {
+        let (_, body_env) = instantiate_function(&closure, this, arg_values, None)?;
+        return Ok(create_generator(closure, body_env));
+    }
+    // Errors from binding the parameters reject the promise as well
+    // https://tc39.es/ecma262/#sec-async-function-definitions-EvaluateAsyncFunctionBody
+    if closure.is_async {
+        let capability = new_promise_capability();
+        match instantiate_function(&closure, this, arg_values, None) {
+            Ok((_, body_env)) => start_async_function(closure.body, body_env, &capability)?,
+            Err(e) => capability.reject(e.into_exception()?)?,
+        }
+        return Ok(capability.promise);
+    }
+    let (result, _) = run_function(closure, this, arg_values, None)?;
+    Ok(result.unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED)))
+}
+
+// Evaluates the body of a function in a new frame, returning the value of its return statement
+// along with the frame so that constructors can read the this value it ended with.
+fn run_function(
+    closure: Closure,
+    this: ThisBinding,
+    arg_values: Vec<EvaluatorValue>,
+    new_target: Option<EvaluatorValue>,
+) -> Result<(Option<EvaluatorValue>, Env), EvaluatorError> {
+    let (new_env, body_env) = instantiate_function(&closure, this, arg_values, new_target)?;
+
+    // eval closure body with new env, a return completion hands its value back to the caller
+    match eval_block_statement(closure.body, body_env)? {
+        Completion::Return(value) => Ok((Some(value), new_env)),
+        Completion::Normal(_) => Ok((None, new_env)),
+        Completion::Break(_) | Completion::Continue(_) => {
+            unreachable!("break and continue cannot cross a function boundary")
+        }
+    }
+}
+
+// Creates the frame of a call and binds the parameters in it, returning the frame along with the
+// one the body is to be evaluated in.
+// If we call the function with fewer than required args, the rest should default to undefined
+// If we call the function with more than the required args, the rest should be ignored
+fn instantiate_function(
+    closure: &Closure,
+    this: ThisBinding,
+    arg_values: Vec<EvaluatorValue>,
+    new_target: Option<EvaluatorValue>,
+) -> Result<(Env, Env), EvaluatorError> {
+    let frame = FunctionFrame {
+        this,
+        strict: closure.strict,
+        new_target,
+        function: Some(closure.clone()),
+    };
+
+    // extend the closure's defining env with arg values, giving us lexical scope
+    let new_env = closure
+        .env
+        .borrow()
+        .extend_function(Rc::clone(&closure.env), frame);
+
+    let parameter_names = closure
+        .parameters
+        .iter()
+        .flat_map(|parameter| bound_names(parameter))
+        .collect::<Vec<_>>();
+    // Parameters are uninitialized until their turn comes, a default value can read the
+    // parameters before it but not the ones after it. Sloppy functions may repeat a parameter
+    // name, the parameters are then assigned in order so that the last one wins.
+    // https://tc39.es/ecma262/#sec-functiondeclarationinstantiation
+    let has_duplicates = parameter_names
+        .iter()
+        .enumerate()
+        .any(|(i, id)| parameter_names[..i].contains(id));
+    for id in &parameter_names {
+        let _ = match has_duplicates {
+            true => {
+                new_env
+                    .borrow_mut()
+                    .define(id.clone(), EvaluatorValue::from(JS_UNDEFINED), "let")
+            }
+            false => new_env
+                .borrow_mut()
+                .declare_lexical(id.clone(), DeclarationKind::Let),
+        };
+    }
+    let binding_kind = match has_duplicates {
+        true => BindingKind::Assignment,
+        false => BindingKind::Declaration("let"),
+    };
+    let has_parameter_expressions = closure.parameters.iter().any(|p| contains_expression(p));
+
+    // set arg values, a rest parameter collects the arguments left over
+    let mut arg_values = arg_values.into_iter();
+    closure.parameters.iter().try_for_each(|id| {
+        if let NodeKind::RestElement(ast::RestElement { argument }) = &id.kind {
+            let rest = arg_values.by_ref().map(Some).collect::<Vec<_>>();
+            return run(bind_pattern(
+                *argument.clone(),
+                EvaluatorValue::from(rest),
+                binding_kind,
+                Rc::clone(&new_env),
+                None,
+            ));
+        }
+        let value = arg_values
+            .next()
+            .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED));
+        run(bind_pattern(
+            *id.clone(),
+            value,
+            binding_kind,
+            Rc::clone(&new_env),
+            None,
+        ))
+    })?;
+
+    // Closures created by default values must not see the declarations of the body, so the body
+    // gets a frame of its own where vars named like a parameter start with its value
+    // https://tc39.es/ecma262/#sec-functiondeclarationinstantiation
+    let body_env = if has_parameter_expressions {
+        let var_env = new_env.borrow().extend(Rc::clone(&new_env));
+        for id in var_declared_names(&closure.body.body) {
+            let value = match parameter_names.contains(&id) {
+                true => eval_identifier(id.clone(), None, Rc::clone(&new_env))?,
+                false => EvaluatorValue::from(JS_UNDEFINED),
+            };
+            var_env
+                .borrow_mut()
+                .define(id, value, "var")
+                .map_err(EvaluatorError::EnvironmentError)?;
+        }
+        var_env
     } else {
-        evaluate(*alternate, Rc::clone(&env))?
+        hoist_var_declarations(&closure.body.body, &new_env);
+        Rc::clone(&new_env)
     };
+    Ok((new_env, body_env))
+}
 
-    Ok(body_val)
+async fn eval_return_statement(
+    r: ReturnStatement,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let value = match r.argument {
+        None => EvaluatorValue::from(JS_UNDEFINED),
+        Some(argument) => eval_expression_in(*argument, env, co).await?,
+    };
+    Ok(Completion::Return(value))
+}
+
+fn eval_object_expression(
+    ObjectExpression { properties }: ObjectExpression,
+    env: Env,
+) -> EvaluatorResult {
+    let object = Object::new(Some(Rc::clone(&intrinsics().object_prototype)));
+
+    properties.into_iter().try_for_each(|p| {
+        let p = match p {
+            ast::ObjectExpressionProperty::Property(p) => p,
+            ast::ObjectExpressionProperty::SpreadElement(ast::SpreadElement { argument }) => {
+                let source = eval_expression(*argument, Rc::clone(&env))?;
+                return copy_data_properties(&object, source, &[]);
+            }
+        };
+        let ast::Property {
+            key,
+            value,
+            kind,
+            computed,
+            shorthand,
+            ..
+        } = p;
+
+        let key_string = run(eval_member_key(*key, computed, Rc::clone(&env), None))?;
+        let evaluated_value = eval_expression(*value, Rc::clone(&env))?;
+        // __proto__: value sets the prototype of the literal instead of creating a property
+        // https://tc39.es/ecma262/#sec-__proto__-property-names-in-object-initializers
+        if key_string == "__proto__" && !computed && !shorthand {
+            match evaluated_value {
+                EvaluatorValue::Object(prototype) => {
+                    object.borrow_mut().prototype = Some(prototype)
+                }
+                EvaluatorValue::Literal(Literal {
+                    value: LiteralValue::Null,
+                }) => object.borrow_mut().prototype = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+        match kind.as_str() {
+            "get" => object
+                .borrow_mut()
+                .define_accessor(key_string, Some(evaluated_value), None),
+            "set" => object
+                .borrow_mut()
+                .define_accessor(key_string, None, Some(evaluated_value)),
+            _ => object.borrow_mut().set(key_string, evaluated_value),
+        }
+        Ok(())
+    })?;
+
+    Ok(EvaluatorValue::Object(object))
+}
+
+fn eval_array_expression(
+    ArrayExpression { elements }: ArrayExpression,
+    env: Env,
+) -> EvaluatorResult {
+    let elements = elements
+        .into_iter()
+        .map(|element| match element {
+            Some(element) => match element.kind {
+                // [...a] holds the values produced by iterating a, holes in a become undefined
+                NodeKind::SpreadElement(ast::SpreadElement { argument }) => {
+                    let iterable = eval_expression(*argument, Rc::clone(&env))?;
+                    Ok(iterate_to_list(iterable)?.into_iter().map(Some).collect())
+                }
+                _ => Ok(vec![Some(eval_expression(*element, Rc::clone(&env))?)]),
+            },
+            None => Ok(vec![None]),
+        })
+        .collect::<Result<Vec<_>, EvaluatorError>>()?;
+    Ok(EvaluatorValue::from(elements.concat()))
+}
+
+// https://262.ecma-international.org/5.1/#sec-15.4
+// A property name P is an array index iff ToString(ToUint32(P)) is equal to P and ToUint32(P) is not 2^32 - 1
+fn array_index(key: &PropertyKey) -> Option<usize> {
+    let PropertyKey::String(key) = key else {
+        return None;
+    };
+    match key.parse::<u32>() {
+        Ok(index) if index != u32::MAX && index.to_string() == *key => Some(index as usize),
+        _ => None,
+    }
+}
+
+// https://tc39.es/ecma262/#sec-topropertykey
+// The key used to look up a member, a.b is keyed by the identifier name while a[b] is keyed by the value of b
+async fn eval_member_key(
+    property: Node,
+    computed: bool,
+    env: Env,
+    co: Option<Coroutine>,
+) -> Result<PropertyKey, EvaluatorError> {
+    match property.kind {
+        NodeKind::Identifier(id) if !computed => Ok(PropertyKey::from(id.name)),
+        _ => Ok(eval_expression_in(property, env, co).await?.into()),
+    }
+}
+
+fn eval_member_expression(
+    MemberExpression {
+        object,
+        property,
+        computed,
+    }: MemberExpression,
+    env: Env,
+) -> EvaluatorResult {
+    if let NodeKind::Super(_) = object.kind {
+        let key = run(eval_member_key(*property, computed, Rc::clone(&env), None))?;
+        return get_super_member(key, &env);
+    }
+    let target = eval_expression(*object, Rc::clone(&env))?;
+    if let NodeKind::PrivateIdentifier(id) = property.kind {
+        return get_private(target, &resolve_private_name(&id, &env)?);
+    }
+    let key = run(eval_member_key(*property, computed, env, None))?;
+    get_member(target, key)
+}
+
+pub fn get_member(target: EvaluatorValue, key: impl Into<PropertyKey>) -> EvaluatorResult {
+    let key = key.into();
+    let member = match target {
+        EvaluatorValue::Object(ref obj) => return get_property(obj, &key, target.clone()),
+        EvaluatorValue::Array(ref arr) => {
+            let element = if key == "length" {
+                Some(EvaluatorValue::from(LiteralValue::from(
+                    arr.borrow().len() as f64
+                )))
+            } else {
+                array_index(&key).and_then(|index| arr.borrow().get(index).cloned())
+            };
+            let property = arr.borrow().properties.get(&key).cloned();
+            match (element, property) {
+                (Some(element), _) | (None, Some(element)) => Some(element),
+                (None, None) if array_index(&key).is_some() => None,
+                (None, None) => return get_property(&intrinsics().array_prototype, &key, target),
+            }
+        }
+        EvaluatorValue::Literal(Literal {
+            value: LiteralValue::String(ref s),
+        }) => {
+            let chars = s.chars().collect::<Vec<char>>();
+            if key == "length" {
+                Some(EvaluatorValue::from(LiteralValue::from(chars.len() as f64)))
+            } else if let Some(index) = array_index(&key) {
+                chars
+                    .get(index)
+                    .map(|c| EvaluatorValue::from(LiteralValue::String(c.to_string())))
+            } else {
+                return get_property(&intrinsics().string_prototype, &key, target);
+            }
+        }
+        target @ EvaluatorValue::Literal(Literal {
+            value: LiteralValue::Undefined | LiteralValue::Null,
+        }) => {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Cannot read properties of {} (reading '{}')",
+                String::from(target),
+                key
+            )))
+        }
+        EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_) => {
+            match target.function_object() {
+                Some(obj) => return get_property(&obj, &key, target),
+                None => None,
+            }
+        }
+        EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_) => None,
+    };
+    Ok(member.unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED)))
+}
+
+// Reads a property of an object or one of its prototypes, getters are called with the receiver
+// the lookup started from as this
+// https://tc39.es/ecma262/#sec-ordinaryget
+fn get_property(obj: &JsObject, key: &PropertyKey, receiver: EvaluatorValue) -> EvaluatorResult {
+    let property = obj.borrow().find(key);
+    match property {
+        Some(Property::Data(value)) => Ok(value),
+        Some(Property::Accessor {
+            get: Some(getter), ..
+        }) => call_value(getter, receiver, vec![]),
+        Some(Property::Accessor { get: None, .. }) | None => Ok(EvaluatorValue::from(JS_UNDEFINED)),
+    }
+}
+
+// Assignment calls a setter found on the object or its prototypes, and otherwise creates or
+// updates an own property. Assigning to an accessor without a setter does nothing. Module
+// namespace objects reject every write, and module code is always strict, so that is a TypeError.
+// https://tc39.es/ecma262/#sec-ordinaryset
+// https://tc39.es/ecma262/#sec-module-namespace-exotic-objects-set-p-v-receiver
+fn put_property(
+    obj: &JsObject,
+    key: PropertyKey,
+    value: EvaluatorValue,
+    receiver: EvaluatorValue,
+) -> Result<(), EvaluatorError> {
+    if let Some(InternalSlot::ModuleNamespace) = obj.borrow().internal_slot {
+        return Err(EvaluatorError::InvalidType(
+            match obj.borrow().properties.contains_key(&key) {
+                true => format!(
+                    "Cannot assign to read only property '{}' of object '[object Module]'",
+                    key
+                ),
+                false => format!("Cannot add property {}, object is not extensible", key),
+            },
+        ));
+    }
+    let property = obj.borrow().find(&key);
+    match property {
+        Some(Property::Accessor {
+            set: Some(setter), ..
+        }) => call_value(setter, receiver, vec![value]).map(|_| ()),
+        Some(Property::Accessor { set: None, .. }) => Ok(()),
+        Some(Property::Data(_)) | None => {
+            obj.borrow_mut().set(key, value);
+            Ok(())
+        }
+    }
+}
+
+fn set_member(
+    target: EvaluatorValue,
+    key: PropertyKey,
+    value: EvaluatorValue,
+) -> Result<(), EvaluatorError> {
+    match target {
+        EvaluatorValue::Object(ref obj) => put_property(obj, key, value, target.clone()),
+        EvaluatorValue::Array(arr) => set_array_member(arr, key, value),
+        target @ EvaluatorValue::Literal(Literal {
+            value: LiteralValue::Undefined | LiteralValue::Null,
+        }) => Err(EvaluatorError::InvalidType(format!(
+            "Cannot set properties of {} (setting '{}')",
+            String::from(target),
+            key
+        ))),
+        // Properties written to primitives are dropped along with the wrapper object
+        EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_) => Ok(()),
+        EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_) => {
+            match target.function_object() {
+                Some(obj) => put_property(&obj, key, value, target),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+// https://262.ecma-international.org/5.1/#sec-15.4.5.1
+// Writing past the end leaves holes behind, writing to length truncates or extends the array
+fn set_array_member(
+    arr: JsArray,
+    key: PropertyKey,
+    value: EvaluatorValue,
+) -> Result<(), EvaluatorError> {
+    let mut arr = arr.borrow_mut();
+    if arr.frozen {
+        return Ok(());
+    }
+    if key == "length" {
+        arr.set_len(array_length(value)?);
+    } else if let Some(index) = array_index(&key) {
+        arr.set(index, value);
+    } else {
+        arr.properties.insert(key, value);
+    }
+    Ok(())
+}
+
+// The length must be a whole number that fits in 32 bits, ToUint32 of the value has to give back
+// the same number
+// https://tc39.es/ecma262/#sec-arraysetlength
+fn array_length(value: EvaluatorValue) -> Result<usize, EvaluatorError> {
+    let number = match value {
+        EvaluatorValue::Literal(literal) => literal.value,
+        value => LiteralValue::from(String::from(value)),
+    };
+    match number.into() {
+        JsNumber::Number(n) if (0.0..=u32::MAX as f64).contains(&n) && n.fract() == 0.0 => {
+            Ok(n as usize)
+        }
+        _ => Err(EvaluatorError::Throw(create_error_object(
+            "RangeError",
+            String::from("Invalid array length"),
+        ))),
+    }
+}
+
+async fn eval_if_statement(
+    IfStatement {
+        test,
+        consequent,
+        alternate,
+    }: IfStatement,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let test_value: bool = eval_expression_in(*test, Rc::clone(&env), co.clone())
+        .await?
+        .into();
+
+    let completion = if test_value {
+        eval_statement(*consequent, Rc::clone(&env), co).await?
+    } else if let Some(alternate) = alternate {
+        eval_statement(*alternate, Rc::clone(&env), co).await?
+    } else {
+        Completion::empty()
+    };
+
+    // https://tc39.es/ecma262/#sec-if-statement-runtime-semantics-evaluation
+    Ok(completion.update_empty(Some(EvaluatorValue::from(JS_UNDEFINED))))
+}
+
+// Labels accumulate until the labelled statement is reached, loops use them to decide which
+// labelled continue statements target them. A break aimed at a label ends the labelled statement.
+// https://262.ecma-international.org/6.0/#sec-labelled-statements-runtime-semantics-labelledevaluation
+fn eval_labeled_statement(
+    LabeledStatement { label, body }: LabeledStatement,
+    env: Env,
+    co: Option<Coroutine>,
+    mut label_set: Vec<Identifier>,
+) -> Suspendable<CompletionResult> {
+    Box::pin(async move {
+        label_set.push(label.clone());
+        let completion = match body.kind {
+            NodeKind::LabeledStatement(s) => eval_labeled_statement(s, env, co, label_set).await?,
+            NodeKind::WhileStatement(s) => eval_while_statement(s, env, co, &label_set).await?,
+            NodeKind::DoWhileStatement(s) => {
+                eval_do_while_statement(s, env, co, &label_set).await?
+            }
+            NodeKind::ForStatement(s) => eval_for_statement(s, env, co, &label_set).await?,
+            NodeKind::ForOfStatement(s) => eval_for_of_statement(s, env, co, &label_set).await?,
+            _ => eval_statement(*body, env, co).await?,
+        };
+
+        match completion {
+            Completion::Break(Some(target)) if target == label => Ok(Completion::empty()),
+            completion => Ok(completion),
+        }
+    })
+}
+
+// https://262.ecma-international.org/5.1/#sec-12.6.2
+async fn eval_while_statement(
+    WhileStatement { test, body }: WhileStatement,
+    env: Env,
+    co: Option<Coroutine>,
+    label_set: &[Identifier],
+) -> CompletionResult {
+    let mut value = EvaluatorValue::from(JS_UNDEFINED);
+    loop {
+        let test_value: bool = eval_expression_in(*test.clone(), Rc::clone(&env), co.clone())
+            .await?
+            .into();
+        if !test_value {
+            return Ok(Completion::from(value));
+        }
+
+        let completion = eval_statement(*body.clone(), Rc::clone(&env), co.clone()).await?;
+        if let Completion::Normal(Some(ref v)) = completion {
+            value = v.clone();
+        }
+        if !completion.loop_continues(label_set) {
+            return Ok(completion.exit_breakable(value));
+        }
+    }
+}
+
+// https://262.ecma-international.org/5.1/#sec-12.6.1
+async fn eval_do_while_statement(
+    DoWhileStatement { body, test }: DoWhileStatement,
+    env: Env,
+    co: Option<Coroutine>,
+    label_set: &[Identifier],
+) -> CompletionResult {
+    let mut value = EvaluatorValue::from(JS_UNDEFINED);
+    loop {
+        let completion = eval_statement(*body.clone(), Rc::clone(&env), co.clone()).await?;
+        if let Completion::Normal(Some(ref v)) = completion {
+            value = v.clone();
+        }
+        if !completion.loop_continues(label_set) {
+            return Ok(completion.exit_breakable(value));
+        }
+
+        let test_value: bool = eval_expression_in(*test.clone(), Rc::clone(&env), co.clone())
+            .await?
+            .into();
+        if !test_value {
+            return Ok(Completion::from(value));
+        }
+    }
+}
+
+// Bindings declared with let in the head are copied into a fresh frame for every iteration
+// https://262.ecma-international.org/6.0/#sec-for-statement-runtime-semantics-labelledevaluation
+async fn eval_for_statement(
+    ForStatement {
+        init,
+        test,
+        update,
+        body,
+    }: ForStatement,
+    env: Env,
+    co: Option<Coroutine>,
+    label_set: &[Identifier],
+) -> CompletionResult {
+    let loop_env = env.borrow_mut().extend(Rc::clone(&env));
+    let mut per_iteration = false;
+
+    if let Some(init) = init {
+        let names = lexically_declared_names(std::iter::once(&*init));
+        hoist_lexical_declarations(names, &loop_env)?;
+        match init.kind {
+            NodeKind::VariableDeclaration(decl) => {
+                per_iteration = decl.kind == "let";
+                eval_variable_declaration(decl, Rc::clone(&loop_env), co.clone()).await?;
+            }
+            _ => {
+                eval_expression_in(*init, Rc::clone(&loop_env), co.clone()).await?;
+            }
+        }
+    }
+
+    let next_iteration_env = |current: &Env| {
+        if per_iteration {
+            current.borrow().copy_frame()
+        } else {
+            Rc::clone(current)
+        }
+    };
+
+    let mut iteration_env = next_iteration_env(&loop_env);
+    let mut value = EvaluatorValue::from(JS_UNDEFINED);
+    loop {
+        if let Some(test) = &test {
+            let test_value: bool =
+                eval_expression_in(*test.clone(), Rc::clone(&iteration_env), co.clone())
+                    .await?
+                    .into();
+            if !test_value {
+                return Ok(Completion::from(value));
+            }
+        }
+
+        let completion =
+            eval_statement(*body.clone(), Rc::clone(&iteration_env), co.clone()).await?;
+        if let Completion::Normal(Some(ref v)) = completion {
+            value = v.clone();
+        }
+        if !completion.loop_continues(label_set) {
+            return Ok(completion.exit_breakable(value));
+        }
+
+        iteration_env = next_iteration_env(&iteration_env);
+        if let Some(update) = &update {
+            eval_expression_in(*update.clone(), Rc::clone(&iteration_env), co.clone()).await?;
+        }
+    }
+}
+
+// Every iteration binds the next value of the iterator in a fresh frame. The iterator is closed
+// when the loop is left early through break, return or an exception.
+// https://tc39.es/ecma262/#sec-runtime-semantics-forin-div-ofbodyevaluation-lhs-stmt-iterator-lhskind-labelset
+async fn eval_for_of_statement(
+    ForInStatement { left, right, body }: ForInStatement,
+    env: Env,
+    co: Option<Coroutine>,
+    label_set: &[Identifier],
+) -> CompletionResult {
+    let iterable = eval_expression_in(*right, for_of_head_env(&left, &env)?, co.clone()).await?;
+    let mut iterator = get_iterator(iterable)?;
+    let mut value = EvaluatorValue::from(JS_UNDEFINED);
+    while let Some(next) = iterator.step()? {
+        let completion = match for_of_iteration_env(&left, next, &env, co.clone()).await {
+            Ok(iteration_env) => eval_statement(*body.clone(), iteration_env, co.clone()).await,
+            Err(e) => Err(e),
+        };
+        let completion = match completion {
+            Ok(completion) => completion,
+            Err(e) => {
+                let _ = iterator.close();
+                return Err(e);
+            }
+        };
+        if let Completion::Normal(Some(ref v)) = completion {
+            value = v.clone();
+        }
+        if !completion.loop_continues(label_set) {
+            iterator.close()?;
+            return Ok(completion.exit_breakable(value));
+        }
+    }
+    Ok(Completion::from(value))
+}
+
+// Names declared with let or const in the head are in their temporal dead zone while the
+// iterated expression is evaluated
+// https://tc39.es/ecma262/#sec-runtime-semantics-forinofheadevaluation
+fn for_of_head_env(left: &ForInLeftValue, env: &Env) -> Result<Env, EvaluatorError> {
+    match left {
+        ForInLeftValue::VariableDeclaration(d) if d.kind != "var" => {
+            let head_env = env.borrow().extend(Rc::clone(env));
+            hoist_lexical_declarations(lexical_declaration_names(d), &head_env)?;
+            Ok(head_env)
+        }
+        _ => Ok(Rc::clone(env)),
+    }
+}
+
+// Binds the value of one iteration, returning the frame the loop body runs in
+async fn for_of_iteration_env(
+    left: &ForInLeftValue,
+    value: EvaluatorValue,
+    env: &Env,
+    co: Option<Coroutine>,
+) -> Result<Env, EvaluatorError> {
+    match left {
+        ForInLeftValue::VariableDeclaration(d) => {
+            let Some(declarator) = d.declarations.first() else {
+                return Err(EvaluatorError::SyntaxError(String::from(
+                    "Invalid left-hand side in for-of loop",
+                )));
+            };
+            let iteration_env = env.borrow().extend(Rc::clone(env));
+            hoist_lexical_declarations(lexical_declaration_names(d), &iteration_env)?;
+            bind_pattern(
+                *declarator.id.clone(),
+                value,
+                BindingKind::Declaration(d.kind.as_str()),
+                Rc::clone(&iteration_env),
+                co,
+            )
+            .await?;
+            Ok(iteration_env)
+        }
+        ForInLeftValue::Pattern(pattern) => {
+            bind_pattern(
+                *pattern.clone(),
+                value,
+                BindingKind::Assignment,
+                Rc::clone(env),
+                co,
+            )
+            .await?;
+            Ok(Rc::clone(env))
+        }
+    }
+}
+
+// Cases are tested in order with strict equality, skipping the default clause. Evaluation starts
+// at the matching case, or the default clause if nothing matched, and falls through until a break.
+// All clauses share a single lexical scope.
+// https://262.ecma-international.org/6.0/#sec-runtime-semantics-caseblockevaluation
+async fn eval_switch_statement(
+    SwitchStatement {
+        discriminant,
+        cases,
+    }: SwitchStatement,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let input = eval_expression_in(*discriminant, Rc::clone(&env), co.clone()).await?;
+    let block_env = env.borrow_mut().extend(Rc::clone(&env));
+    let statements = || {
+        cases
+            .iter()
+            .flat_map(|case| case.consequent.iter().map(|c| c.as_ref()))
+    };
+    hoist_lexical_declarations(lexically_declared_names(statements()), &block_env)?;
+    hoist_function_declarations(function_declarations(statements()), &block_env);
+
+    let mut start = None;
+    for (i, case) in cases.iter().enumerate() {
+        if let Some(test) = &case.test {
+            let case_value =
+                eval_expression_in(*test.clone(), Rc::clone(&block_env), co.clone()).await?;
+            if input.strict_equals(&case_value) {
+                start = Some(i);
+                break;
+            }
+        }
+    }
+    let start = match start.or_else(|| cases.iter().position(|case| case.test.is_none())) {
+        Some(start) => start,
+        None => return Ok(Completion::from(EvaluatorValue::from(JS_UNDEFINED))),
+    };
+
+    let mut value = EvaluatorValue::from(JS_UNDEFINED);
+    for SwitchCase { consequent, .. } in cases.into_iter().skip(start) {
+        for statement in consequent {
+            let completion = eval_statement(*statement, Rc::clone(&block_env), co.clone()).await?;
+            if let Completion::Normal(Some(ref v)) = completion {
+                value = v.clone();
+            }
+            if completion.is_abrupt() {
+                return Ok(completion.exit_breakable(value));
+            }
+        }
+    }
+    Ok(Completion::from(value))
+}
+
+// https://262.ecma-international.org/5.1/#sec-12.13
+async fn eval_throw_statement(
+    ThrowStatement { argument }: ThrowStatement,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let value = eval_expression_in(*argument, env, co).await?;
+    Err(EvaluatorError::Throw(value))
+}
+
+// An abrupt completion from the finally block replaces whatever the try or catch blocks produced
+// https://262.ecma-international.org/6.0/#sec-try-statement-runtime-semantics-evaluation
+// A yield in a try block can be resumed with return, which runs the finally block but not the
+// catch clause
+async fn eval_try_statement(
+    TryStatement {
+        block,
+        handler,
+        finalizer,
+    }: TryStatement,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let mut result = eval_block(block, Rc::clone(&env), co.clone()).await;
+
+    if let Some(handler) = handler {
+        if let Err(e) = result {
+            result = match e.into_exception() {
+                Ok(exception) => {
+                    eval_catch_clause(handler, exception, Rc::clone(&env), co.clone()).await
+                }
+                Err(e) => Err(e),
+            };
+        }
+    }
+
+    if let Some(finalizer) = finalizer {
+        let finalizer_completion = eval_block(finalizer, Rc::clone(&env), co).await?;
+        if finalizer_completion.is_abrupt() {
+            return Ok(finalizer_completion);
+        }
+    }
+
+    result.map(|completion| completion.update_empty(Some(EvaluatorValue::from(JS_UNDEFINED))))
+}
+
+// https://262.ecma-international.org/6.0/#sec-runtime-semantics-catchclauseevaluation
+async fn eval_catch_clause(
+    CatchClause { param, body }: CatchClause,
+    exception: EvaluatorValue,
+    env: Env,
+    co: Option<Coroutine>,
+) -> CompletionResult {
+    let catch_env = env.borrow_mut().extend(Rc::clone(&env));
+    if let Some(param) = param {
+        bind_pattern(
+            *param,
+            exception,
+            BindingKind::Declaration("let"),
+            Rc::clone(&catch_env),
+            co.clone(),
+        )
+        .await?;
+    }
+    eval_block(body, catch_env, co).await
+}
+
+fn eval_conditional_expression(
+    ConditionalExpression {
+        test,
+        alternate,
+        consequent,
+    }: ConditionalExpression,
+    env: Env,
+) -> EvaluatorResult {
+    let test_value: bool = eval_expression(*test, Rc::clone(&env))?.into();
+
+    let body_val = if test_value {
+        eval_expression(*consequent, Rc::clone(&env))?
+    } else {
+        eval_expression(*alternate, Rc::clone(&env))?
+    };
+
+    Ok(body_val)
+}
+
+// The expressions are evaluated left to right, the value is the value of the last one
+// https://tc39.es/ecma262/#sec-comma-operator-runtime-semantics-evaluation
+fn eval_sequence_expression(
+    SequenceExpression { expressions }: SequenceExpression,
+    env: Env,
+) -> EvaluatorResult {
+    let mut value = EvaluatorValue::from(JS_UNDEFINED);
+    for expression in expressions {
+        value = eval_expression(expression, Rc::clone(&env))?;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_completion, assert_throws};
+
+    #[test]
+    pub fn while_loop() {
+        assert_completion(
+            r#"
+                let i = 0;
+                let sum = 0;
+                while (i < 5) { sum = sum + i; i = i + 1; }
+                sum;
+            "#,
+            10.0,
+        );
+    }
+
+    #[test]
+    pub fn do_while_runs_once() {
+        assert_completion(
+            r#"
+                let n = 0;
+                do { n = n + 1; } while (false);
+                n;
+            "#,
+            1.0,
+        );
+    }
+
+    #[test]
+    pub fn for_loop_break_continue() {
+        assert_completion(
+            r#"
+                let sum = 0;
+                for (let i = 0; i < 10; i = i + 1) {
+                    if (i == 2) { continue; }
+                    if (i == 4) { break; }
+                    sum = sum + i;
+                }
+                sum + 4;
+            "#,
+            8.0,
+        );
+    }
+
+    #[test]
+    pub fn for_let_binding_per_iteration() {
+        assert_completion(
+            r#"
+                let f0;
+                let f1;
+                for (let i = 0; i < 2; i = i + 1) {
+                    if (i == 0) { f0 = () => i; } else { f1 = () => i; }
+                }
+                f0() * 10 + f1();
+            "#,
+            1.0,
+        );
+    }
+
+    #[test]
+    pub fn comma_operator() {
+        assert_completion(
+            r#"
+                let i, j, steps = 0;
+                for (i = 0, j = 9; i < j; i++, j--) { steps++; }
+                let last = (steps, i, j);
+                steps * 100 + last;
+            "#,
+            504.0,
+        );
+    }
+
+    #[test]
+    pub fn labeled_continue() {
+        assert_completion(
+            r#"
+                let count = 0;
+                outer: for (let i = 0; i < 3; i = i + 1) {
+                    for (let j = 0; j < 3; j = j + 1) {
+                        if (j == 2) { continue outer; }
+                        count = count + 1;
+                    }
+                }
+                count;
+            "#,
+            6.0,
+        );
+    }
+
+    #[test]
+    pub fn labeled_block_break() {
+        assert_completion(
+            r#"
+                let x = 0;
+                block: {
+                    x = 1;
+                    if (x) { break block; }
+                    x = 2;
+                }
+                x;
+            "#,
+            1.0,
+        );
+    }
+
+    #[test]
+    pub fn labeled_break_nested_loops() {
+        assert_completion(
+            r#"
+                let n = 0;
+                outer: while (true) {
+                    while (true) {
+                        n = n + 1;
+                        if (n == 3) { break outer; }
+                    }
+                }
+                n;
+            "#,
+            3.0,
+        );
+    }
+
+    #[test]
+    pub fn switch_fall_through() {
+        assert_completion(
+            r#"
+                let s = "";
+                switch (2) {
+                    case 1: s = s + "a";
+                    case 2: s = s + "b";
+                    case 3: s = s + "c"; break;
+                    case 4: s = s + "d";
+                }
+                s;
+            "#,
+            "bc",
+        );
+    }
+
+    #[test]
+    pub fn switch_default_in_middle() {
+        assert_completion(
+            r#"
+                let s = "";
+                switch ("1") {
+                    case 1: s = "one"; break;
+                    default: s = s + "d";
+                    case 2: s = s + "z";
+                }
+                s;
+            "#,
+            "dz",
+        );
+    }
+
+    #[test]
+    pub fn switch_shared_scope() {
+        assert_completion(
+            r#"
+                function f(x) {
+                    switch (x) {
+                        case 0: let y = 5;
+                        case 1: return y;
+                    }
+                }
+                f(0);
+            "#,
+            5.0,
+        );
+    }
+
+    #[test]
+    pub fn try_catch() {
+        assert_completion("try { throw 5; } catch (e) { e + 1; }", 6.0);
+    }
+
+    #[test]
+    pub fn throw_across_calls() {
+        assert_completion(
+            r#"
+                function f() { throw "boom"; }
+                let r;
+                try { f(); } catch (e) { r = e; }
+                r;
+            "#,
+            "boom",
+        );
+    }
+
+    #[test]
+    pub fn finally_overrides_return() {
+        assert_completion(
+            r#"
+                function g() {
+                    try { return 1; } finally { return 2; }
+                }
+                g();
+            "#,
+            2.0,
+        );
+    }
+
+    #[test]
+    pub fn finally_runs_before_outer_catch() {
+        assert_completion(
+            r#"
+                let log = "";
+                try {
+                    try { throw 1; } finally { log = log + "f"; }
+                } catch (e) { log = log + "c"; }
+                log;
+            "#,
+            "fc",
+        );
+    }
+
+    #[test]
+    pub fn optional_catch_binding() {
+        assert_completion(
+            r#"
+                let x = 0;
+                try { throw 1; } catch { x = 2; }
+                x;
+            "#,
+            2.0,
+        );
+    }
+
+    #[test]
+    pub fn uncaught_exception() {
+        assert_throws(r#"throw "boom";"#, "Uncaught boom");
+    }
+
+    #[test]
+    pub fn const_reassignment_is_type_error() {
+        assert_completion(
+            r#"
+                const c = 1;
+                let err;
+                try { c = 2; } catch (e) { err = e; }
+                err.name;
+            "#,
+            "TypeError",
+        );
+    }
+
+    #[test]
+    pub fn computed_member_access() {
+        assert_completion(
+            r#"
+                const o = { a: 1 };
+                const k = "a";
+                o[k] + o["a"];
+            "#,
+            2.0,
+        );
+    }
+
+    #[test]
+    pub fn nested_property_assignment() {
+        assert_completion(
+            r#"
+                const a = { b: { c: 0 } };
+                a.b.c = 1;
+                a["b"]["d"] = 2;
+                a.b.c + a.b.d;
+            "#,
+            3.0,
+        );
+    }
+
+    #[test]
+    pub fn property_key_conversion() {
+        assert_completion(
+            r#"
+                const o = {};
+                o[1.0] = "a";
+                o[-0] = "b";
+                o[1e21] = "c";
+                o[{}] = "d";
+                o["1"] + o["0"] + o["1e+21"] + o["[object Object]"];
+            "#,
+            "abcd",
+        );
+    }
+
+    #[test]
+    pub fn computed_property_in_literal() {
+        assert_completion(
+            r#"
+                const k = "x";
+                const o = { [k]: 1, k: 2, 3: 4 };
+                o.x + o.k + o["3"];
+            "#,
+            7.0,
+        );
+    }
+
+    #[test]
+    pub fn member_of_undefined_is_type_error() {
+        assert_completion(
+            r#"
+                let r;
+                try { const o = {}; o.a.b; } catch (e) { r = e.name + ": " + e.message; }
+                r;
+            "#,
+            "TypeError: Cannot read properties of undefined (reading 'b')",
+        );
+    }
+
+    #[test]
+    pub fn string_length_and_index() {
+        assert_completion(r#""abc".length + "abc"[1];"#, "3b");
+    }
+
+    #[test]
+    pub fn number_to_string() {
+        assert_completion(
+            r#""" + 0.000001 + " " + 1e-7 + " " + 123.456 + " " + 1e300;"#,
+            "0.000001 1e-7 123.456 1e+300",
+        );
+    }
+
+    #[test]
+    pub fn compound_assignment() {
+        assert_completion(
+            r#"
+                let a = 1;
+                a += 2;
+                a *= 3;
+                a -= 1;
+                a /= 2;
+                a %= 3;
+                a **= 3;
+                a <<= 2;
+                a |= 1;
+                a ^= 3;
+                a &= 6;
+                a;
+            "#,
+            6.0,
+        );
+    }
+
+    #[test]
+    pub fn compound_string_concatenation() {
+        assert_completion(
+            r#"
+                let s = "a";
+                s += 1;
+                s += true;
+                s;
+            "#,
+            "a1true",
+        );
+    }
+
+    #[test]
+    pub fn prefix_and_postfix_update() {
+        assert_completion(
+            r#"
+                let i = 0;
+                const a = i++;
+                const b = ++i;
+                const c = i--;
+                const d = --i;
+                "" + a + b + c + d + i;
+            "#,
+            "02200",
+        );
+    }
+
+    #[test]
+    pub fn update_member_targets() {
+        assert_completion(
+            r#"
+                const o = { n: 1 };
+                const arr = [5];
+                o.n++;
+                ++o["n"];
+                arr[0]--;
+                o.n + arr[0];
+            "#,
+            7.0,
+        );
+    }
+
+    #[test]
+    pub fn compound_target_evaluated_once() {
+        assert_completion(
+            r#"
+                let calls = 0;
+                const o = { a: [1] };
+                function key() { calls = calls + 1; return "a"; }
+                o[key()][0] += 5;
+                o[key()][0]++;
+                calls * 10 + o.a[0];
+            "#,
+            27.0,
+        );
+    }
+
+    #[test]
+    pub fn logical_assignment() {
+        assert_completion(
+            r#"
+                let a = 0;
+                let b = 1;
+                let c = null;
+                a ||= 5;
+                b &&= 6;
+                c ??= 7;
+                b ||= 8;
+                "" + a + b + c;
+            "#,
+            "567",
+        );
+    }
+
+    #[test]
+    pub fn exponentiation() {
+        assert_completion("2 ** 3 ** 2 + (-2) ** 2 + 4 ** 0.5;", 518.0);
+    }
+
+    #[test]
+    pub fn shift_operators() {
+        assert_completion(
+            r#""" + (-1 >>> 0) + " " + (-8 >> 1) + " " + (1 << 31) + " " + (1 << 33) + " " + (2 ** 32 + 5 | 0);"#,
+            "4294967295 -4 -2147483648 2 5",
+        );
+    }
+
+    #[test]
+    pub fn assignment_and_logical_values() {
+        assert_completion(
+            r#"
+                let a;
+                let b;
+                a = b = 3;
+                "" + (0 || "x") + (1 && "y") + (null ?? "z") + a + b;
+            "#,
+            "xyz33",
+        );
+    }
+
+    #[test]
+    pub fn call_before_declaration() {
+        assert_completion(
+            r#"
+                const r = f(2);
+                function f(n) { return n * 10; }
+                r;
+            "#,
+            20.0,
+        );
+    }
+
+    #[test]
+    pub fn mutual_recursion() {
+        assert_completion(
+            r#"
+                function isEven(n) {
+                    return n === 0 ? true : isOdd(n - 1);
+                }
+                function isOdd(n) {
+                    return n === 0 ? false : isEven(n - 1);
+                }
+                isEven(10) && isOdd(7);
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn var_hoisted_as_undefined() {
+        assert_completion(
+            r#"
+                const before = x;
+                var x = 1;
+                before === undefined && x === 1;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn var_is_function_scoped() {
+        assert_completion(
+            r#"
+                function f() {
+                    { var v = 1; }
+                    for (var i = 0; i < 3; i++) {}
+                    if (true) { var v = v + i; }
+                    return v;
+                }
+                f();
+            "#,
+            4.0,
+        );
+    }
+
+    #[test]
+    pub fn var_does_not_leak_from_function() {
+        assert_completion(
+            r#"
+                var x = "outer";
+                function f() { x = "inner"; var x; return x; }
+                f() + " " + x;
+            "#,
+            "inner outer",
+        );
+    }
+
+    #[test]
+    pub fn var_keeps_parameter_value() {
+        assert_completion(
+            r#"
+                function f(a) { var a; return a; }
+                f(3);
+            "#,
+            3.0,
+        );
+    }
+
+    #[test]
+    pub fn function_hoisted_within_block() {
+        assert_completion(
+            r#"
+                let r;
+                {
+                    r = g();
+                    function g() { return "block"; }
+                }
+                r;
+            "#,
+            "block",
+        );
+    }
+
+    #[test]
+    pub fn read_before_let_is_reference_error() {
+        assert_completion(
+            r#"
+                let r;
+                try { x; let x = 1; } catch (e) { r = e.name + ": " + e.message; }
+                r;
+            "#,
+            "ReferenceError: Cannot access 'x' before initialization",
+        );
+    }
+
+    #[test]
+    pub fn tdz_shadows_outer_binding() {
+        assert_completion(
+            r#"
+                const x = "outer";
+                let r;
+                {
+                    try { r = x; } catch (e) { r = e.name; }
+                    const x = "inner";
+                }
+                r;
+            "#,
+            "ReferenceError",
+        );
+    }
+
+    #[test]
+    pub fn closure_read_before_initialization() {
+        assert_completion(
+            r#"
+                let r = "";
+                function read() { return v; }
+                try { read(); } catch (e) { r = r + e.name; }
+                let v = 1;
+                r + read();
+            "#,
+            "ReferenceError1",
+        );
+    }
+
+    #[test]
+    pub fn assignment_in_tdz() {
+        assert_completion(
+            r#"
+                let r;
+                try { y = 2; let y; } catch (e) { r = e.name; }
+                r;
+            "#,
+            "ReferenceError",
+        );
+    }
+
+    #[test]
+    pub fn switch_cases_share_tdz() {
+        assert_completion(
+            r#"
+                let r;
+                switch (1) {
+                    case 0: let z = 0;
+                    case 1: try { z; } catch (e) { r = e.name; }
+                }
+                r;
+            "#,
+            "ReferenceError",
+        );
+    }
+
+    #[test]
+    pub fn catch_reference_error() {
+        assert_completion(
+            r#"
+                let r;
+                try { missing; } catch (e) { r = e.name + ": " + e.message; }
+                r;
+            "#,
+            "ReferenceError: missing is not defined",
+        );
+    }
+
+    #[test]
+    pub fn typeof_undeclared_and_objects() {
+        assert_completion(
+            r#"typeof missing + " " + typeof function() {} + " " + typeof {} + " " + typeof [] + " " + typeof undefined + " " + typeof NaN;"#,
+            "undefined function object object undefined number",
+        );
+    }
+
+    #[test]
+    pub fn assignment_to_undeclared() {
+        assert_completion(
+            r#"
+                "use strict";
+                let r;
+                try { nope = 1; } catch (e) { r = e.name; }
+                r;
+            "#,
+            "ReferenceError",
+        );
+    }
+
+    #[test]
+    pub fn undeclared_identifier_is_reference_error() {
+        assert_throws(
+            "const a = 1;\nconst b = a + foo;\n",
+            "Uncaught ReferenceError: foo is not defined at 2:15",
+        );
+    }
+
+    #[test]
+    pub fn sloppy_assignment_to_undeclared_creates_a_global() {
+        assert_completion(
+            r#"
+                function f() { created = 1; }
+                f();
+                created += 1;
+                `${created} ${typeof created}`;
+            "#,
+            "2 number",
+        );
+        assert_throws(
+            "function f() { 'use strict'; nope = 1; } f();",
+            "ReferenceError: nope is not defined",
+        );
+    }
+
+    #[test]
+    pub fn method_call_binds_this() {
+        assert_completion(
+            r#"
+                const o = {
+                    n: 2,
+                    get() { return this.n; },
+                    nested: { n: 3, get: function() { return this.n; } },
+                };
+                o.get() * 10 + o["nested"].get();
+            "#,
+            23.0,
+        );
+    }
+
+    #[test]
+    pub fn sloppy_call_binds_global_object() {
+        assert_completion(
+            r#"
+                function f() { return this; }
+                f() === globalThis && this === globalThis;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn strict_call_binds_undefined() {
+        assert_completion(
+            r#"
+                function f() { "use strict"; return this; }
+                function g() { return this; }
+                f() === undefined && g() !== undefined;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn strict_program() {
+        assert_completion(
+            r#"
+                "use strict";
+                function f() { return this; }
+                const o = { f };
+                f() === undefined && o.f() === o;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn arrow_captures_lexical_this() {
+        assert_completion(
+            r#"
+                const o = {
+                    n: 1,
+                    m() {
+                        const inner = () => () => this.n;
+                        return inner()();
+                    },
+                };
+                const top = () => this;
+                o.m() === 1 && top() === globalThis;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn detached_method_loses_this() {
+        assert_completion(
+            r#"
+                "use strict";
+                const o = { n: 1, get() { return this.n; } };
+                const detached = o.get;
+                let r;
+                try { detached(); } catch (e) { r = e.name; }
+                r;
+            "#,
+            "TypeError",
+        );
+    }
+
+    #[test]
+    pub fn call_returned_function() {
+        assert_completion(
+            r#"
+                function make() { return function() { return 5; }; }
+                make()();
+            "#,
+            5.0,
+        );
+    }
+
+    #[test]
+    pub fn new_creates_instance_with_prototype_methods() {
+        assert_completion(
+            r#"
+                function Point(x, y) { this.x = x; this.y = y; }
+                Point.prototype.sum = function() { return this.x + this.y; };
+                const p = new Point(1, 2);
+                p.sum();
+            "#,
+            3.0,
+        );
+    }
+
+    #[test]
+    pub fn inherited_property_lookup() {
+        assert_completion(
+            r#"
+                const base = { greet: "hi", n: 0 };
+                const o = Object.create(base);
+                o.n = 1;
+                o.greet + " " + o.n + " " + (Object.getPrototypeOf(o) === base && base.n === 0);
+            "#,
+            "hi 1 true",
+        );
+    }
+
+    #[test]
+    pub fn new_target_is_the_constructor() {
+        assert_completion(
+            r#"
+                let seen;
+                function F() { seen = new.target; }
+                new F();
+                const constructed = seen === F;
+                F();
+                constructed && seen === undefined;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn constructor_returning_object_replaces_instance() {
+        assert_completion(
+            r#"
+                function A() {
+                    this.v = "kept";
+                    return { v: "replaced" };
+                }
+                function B() { this.v = "kept"; return 1; }
+                (new A()).v + " " + (new B()).v;
+            "#,
+            "replaced kept",
+        );
+    }
+
+    #[test]
+    pub fn instanceof_walks_prototype_chain() {
+        assert_completion(
+            r#"
+                function Animal() {}
+                function Dog() {}
+                Dog.prototype = Object.create(Animal.prototype);
+                const d = new Dog();
+                (d instanceof Dog) + " " + (d instanceof Animal) + " " + (Object.create(null) instanceof Object) + " " + ("greet" in Object.create({ greet: 1 }));
+            "#,
+            "true true false true",
+        );
+    }
+
+    #[test]
+    pub fn loose_equality_compares_objects_by_identity() {
+        assert_completion(
+            r#"
+                const o = {};
+                const f = () => 1;
+                `${({}) == ({})} ${[1] == [1]} ${f == (() => 1)} ${o == o} ${o != {}} ${[1] == 1} ${null == o}`;
+            "#,
+            "false false false true true true false",
+        );
+    }
+
+    #[test]
+    pub fn operators_call_value_of_and_to_string() {
+        assert_completion(
+            r#"
+                function Money(cents) { this.cents = cents; }
+                Money.prototype.valueOf = function () { return this.cents; };
+                const a = new Money(150);
+                const b = new Money(250);
+                const named = { toString() { return "named"; } };
+                `${a + b} ${a < b} ${a == 150} ${-a} ${named + "!"} ${named == "named"}`;
+            "#,
+            "400 true true -150 named! true",
+        );
+        assert_throws(
+            "({ valueOf() { return {}; }, toString() { return {}; } }) + 1;",
+            "Cannot convert object to primitive value",
+        );
+    }
+
+    #[test]
+    pub fn new_arrow_function_throws() {
+        assert_throws(
+            r#"
+                const f = () => 1;
+                new f();
+            "#,
+            "f is not a constructor",
+        );
+    }
+
+    #[test]
+    pub fn only_built_in_constructors_can_be_called_with_new() {
+        assert_throws("new String.raw({ raw: [] });", "is not a constructor");
+        assert_throws(
+            "new setTimeout(() => {});",
+            "setTimeout is not a constructor",
+        );
+        assert_throws("new Promise.resolve(1);", "is not a constructor");
+        assert_throws(
+            "class A extends clearTimeout {}",
+            "Class extends value [Function: clearTimeout] is not a constructor or null",
+        );
+        assert_completion(
+            r#"
+                const s = new String("a");
+                const r = new RegExp("b");
+                `${typeof s} ${r.test("abc")} ${new Object() instanceof Object}`;
+            "#,
+            "object true true",
+        );
+    }
+
+    #[test]
+    pub fn class_methods_accessors_and_statics() {
+        assert_completion(
+            r#"
+                class Point {
+                    constructor(x, y) { this.x = x; this.y = y; }
+                    sum() { return this.x + this.y; }
+                    get doubled() { return this.sum() * 2; }
+                    set both(v) { this.x = v; this.y = v; }
+                    static origin() { return "origin"; }
+                }
+                const p = new Point(1, 2);
+                const a = p.sum();
+                const b = p.doubled;
+                p.both = 5;
+                a + " " + b + " " + p.sum() + " " + Point.origin();
+            "#,
+            "3 6 10 origin",
+        );
+    }
+
+    #[test]
+    pub fn class_extends_with_super_calls() {
+        assert_completion(
+            r#"
+                class Animal {
+                    constructor(name) { this.name = name; }
+                    speak() { return this.name + " makes a sound"; }
+                    static legs() { return 4; }
+                }
+                class Dog extends Animal {
+                    constructor(name) { super(name); }
+                    speak() {
+                        return this.name + " barks, " + super.speak();
+                    }
+                }
+                const d = new Dog("Rex");
+                d.speak() + " " + Dog.legs() + " " + (d instanceof Animal);
+            "#,
+            "Rex barks, Rex makes a sound 4 true",
+        );
+    }
+
+    #[test]
+    pub fn derived_class_default_constructor() {
+        assert_completion(
+            r#"
+                let target;
+                class Base {
+                    constructor(a, b) {
+                        this.args = [a, b];
+                        target = new.target;
+                    }
+                }
+                class Derived extends Base {}
+                const d = new Derived(1, 2);
+                d.args + " " + (target === Derived);
+            "#,
+            "1,2 true",
+        );
+    }
+
+    #[test]
+    pub fn this_before_super_throws() {
+        assert_completion(
+            r#"
+                class A {}
+                class B extends A {
+                    constructor() { this.x = 1; super(); }
+                }
+                let r;
+                try { new B(); } catch (e) { r = e.name; }
+                r;
+            "#,
+            "ReferenceError",
+        );
+    }
+
+    #[test]
+    pub fn class_constructor_requires_new() {
+        assert_throws(
+            r#"
+                class A {}
+                A();
+            "#,
+            "Class constructor A cannot be invoked without 'new'",
+        );
+    }
+
+    #[test]
+    pub fn class_name_binding_inside_body() {
+        assert_completion(
+            r#"
+                let r;
+                try { new C(); } catch (e) { r = e.name; }
+                class C {
+                    static make() { return new C(); }
+                    describe() { return "made"; }
+                }
+                const D = C;
+                C = null;
+                D.make().describe() + " " + r;
+            "#,
+            "made ReferenceError",
+        );
+    }
+
+    #[test]
+    pub fn class_fields_and_private_members() {
+        assert_completion(
+            r#"
+                class Counter {
+                    count = 1;
+                    #step = 2;
+                    #total = 0;
+                    #advance() {
+                        this.#total += this.#step;
+                        return this;
+                    }
+                    get #doubled() { return this.#total * 2; }
+                    set #reset(v) { this.#total = v; }
+                    run() {
+                        this.#advance();
+                        const afterOne = this.#total;
+                        this.#reset = 6;
+                        return this.count + " " + afterOne + " " + (this.#step + 1) + " " + this.#doubled;
+                    }
+                }
+                (new Counter()).run();
+            "#,
+            "1 2 3 12",
+        );
+    }
+
+    #[test]
+    pub fn static_fields_and_blocks() {
+        assert_completion(
+            r#"
+                class Config {
+                    static base = 2;
+                    static derived = Config.base + 1;
+                    static #secret = "secret";
+                    static { this.fromBlock = this.derived + 1; }
+                    static reveal() { return Config.#secret; }
+                }
+                Config.base + " " + Config.derived + " " + Config.fromBlock + " " + Config.reveal();
+            "#,
+            "2 3 4 secret",
+        );
+    }
+
+    #[test]
+    pub fn private_brand_check_with_in() {
+        assert_completion(
+            r#"
+                class A {
+                    #x = 1;
+                    static has(o) { return #x in o; }
+                }
+                class B {
+                    #x = 1;
+                }
+                A.has(new A()) + " " + A.has(new B()) + " " + A.has({});
+            "#,
+            "true false false",
+        );
+    }
+
+    #[test]
+    pub fn derived_fields_initialized_after_super() {
+        assert_completion(
+            r#"
+                class Base {
+                    tag = "base:" + this.constructor.n;
+                }
+                class Derived extends Base {
+                    static n = 1;
+                    label = "derived:" + this.tag;
+                    constructor() { super(); }
+                }
+                const d = new Derived();
+                d.tag + " " + d.label;
+            "#,
+            "base:1 derived:base:1",
+        );
+    }
+
+    #[test]
+    pub fn private_access_on_wrong_object_throws() {
+        assert_completion(
+            r#"
+                class A {
+                    #x = 1;
+                    #m() {}
+                    static read(o) { return o.#x; }
+                    static overwrite(o) { o.#m = 1; }
+                }
+                let r = [];
+                try { A.read({}); } catch (e) { r[r.length] = e.name; }
+                try { A.overwrite(new A()); } catch (e) { r[r.length] = e.name; }
+                r[0] + " " + r[1];
+            "#,
+            "TypeError TypeError",
+        );
+    }
+
+    #[test]
+    pub fn destructures_nested_declarations_with_defaults() {
+        assert_completion(
+            r#"
+                const { a, b: { c }, d = 9, e: [f, , g = 4] = [] } = { a: 1, b: { c: 2 }, e: [3] };
+                a + " " + c + " " + d + " " + g;
+            "#,
+            "1 2 9 4",
+        );
+    }
+
+    #[test]
+    pub fn destructures_function_parameters_and_catch() {
+        assert_completion(
+            r#"
+                function add({ a, b }, [c] = [0]) { return a + b + c; }
+                let message;
+                try { throw { reason: "boom" }; } catch ({ reason }) { message = reason; }
+                add({ a: 1, b: 2 }) + " " + message;
+            "#,
+            "3 boom",
+        );
+    }
+
+    #[test]
+    pub fn destructuring_assignment_swaps_and_targets_members() {
+        assert_completion(
+            r#"
+                let a = 1, b = 2;
+                [a, b] = [b, a];
+                const o = {};
+                ({ v: o.w } = { v: 5 });
+                a + " " + b + " " + o.w;
+            "#,
+            "2 1 5",
+        );
+    }
+
+    #[test]
+    pub fn destructuring_undefined_throws_type_error() {
+        assert_throws("const { a } = undefined;", "Cannot destructure");
+    }
+
+    #[test]
+    pub fn destructures_rest_elements() {
+        assert_completion(
+            r#"
+                let [first, ...others] = [1, 2, 3];
+                let { x, ...more } = { x: 1, y: 2 };
+                first + " " + others + " " + more.y + " " + ("x" in more);
+            "#,
+            "1 2,3 2 false",
+        );
+    }
+
+    #[test]
+    pub fn default_parameters_see_earlier_parameters() {
+        assert_completion(
+            r#"
+                function f(a, b = a + 1, [c] = [b + 1]) { return [a, b, c]; }
+                f(1) + "";
+            "#,
+            "1,2,3",
+        );
+    }
+
+    #[test]
+    pub fn default_parameters_do_not_see_body_declarations() {
+        assert_completion(
+            r#"
+                const x = "outer";
+                function f(read = () => x, y = 1) {
+                    var x = "inner";
+                    var y;
+                    const seen = y;
+                    y = 2;
+                    return read() + " " + seen + " " + y;
+                }
+                f();
+            "#,
+            "outer 1 2",
+        );
+    }
+
+    #[test]
+    pub fn default_parameters_apply_to_undefined_arguments() {
+        assert_completion(
+            r#"
+                function f(a, b = 2) { return a + b; }
+                function g(x = null) { return x; }
+                f(1) + " " + f(1, undefined) + " " + f(2, 3) + " " + g(undefined);
+            "#,
+            "3 3 5 null",
+        );
+    }
+
+    #[test]
+    pub fn default_parameter_reading_a_later_parameter_throws() {
+        assert_throws(
+            r#"
+                let b = 1;
+                function f(a = b, b) { return a; }
+                f();
+            "#,
+            "Uncaught ReferenceError: Cannot access 'b' before initialization",
+        );
+    }
+
+    #[test]
+    pub fn uncaught_environment_errors_print_their_messages() {
+        assert_throws(
+            "x; let x = 1;",
+            "Uncaught ReferenceError: Cannot access 'x' before initialization",
+        );
+        assert_throws(
+            r#"
+                class A {}
+                class B extends A { constructor() {} }
+                new B();
+            "#,
+            "Uncaught ReferenceError: Must call super constructor in derived class",
+        );
+        assert_throws(
+            "const c = 1; c = 2;",
+            "Uncaught TypeError: Assignment to constant variable.",
+        );
+    }
+
+    #[test]
+    pub fn duplicate_parameters_bind_the_last_argument() {
+        assert_completion(
+            r#"
+                function f(a, a) { return a; }
+                `${f(1, 2)} ${f(1)}`;
+            "#,
+            "2 undefined",
+        );
+    }
+
+    #[test]
+    pub fn tagged_template_strings_are_cached_and_frozen() {
+        assert_completion(
+            r#"
+                const seen = [];
+                function tag(strings) {
+                    seen[seen.length] = strings;
+                    return strings;
+                }
+                for (let i = 0; i < 2; i++) { tag`a`; }
+                const s = seen[0];
+                s[0] = "changed";
+                s.raw[0] = "changed";
+                (seen[0] === seen[1]) + " " + (tag`a` !== s) + " " + s[0] + " " + s.length;
+            "#,
+            "true true a 1",
+        );
+    }
+
+    #[test]
+    pub fn tagged_template_calls_methods_with_this() {
+        assert_completion(
+            r#"
+                const o = { name: "x", tag(strings, v) { return this.name + ":" + v; } };
+                o.tag`${1}`;
+            "#,
+            "x:1",
+        );
+    }
+
+    #[test]
+    pub fn evaluates_template_literals() {
+        assert_completion(
+            r#"
+                const b = [1, 2];
+                `a${b[0]}b${b}c ${b.length === 2} ${{}}`;
+            "#,
+            "a1b1,2c true [object Object]",
+        );
+    }
+
+    #[test]
+    pub fn tagged_template_receives_strings_and_values() {
+        assert_completion(
+            r#"
+                function tag(strings, ...values) {
+                    return strings[0] + "|" + strings[1] + "|" + strings[2] + " " + values;
+                }
+                function raw(strings) { return strings.raw[0]; }
+                tag`a${1}b${2}c` + " " + raw`a\nb`;
+            "#,
+            "a|b|c 1,2 a\\nb",
+        );
+    }
+
+    #[test]
+    pub fn for_of_loops() {
+        assert_completion(
+            r#"
+                let out = "";
+                for (const [k, v] of [[1, 2], [3, 4]]) { out += k + v; }
+                let it = {
+                    i: 0,
+                    closed: false,
+                    [Symbol.iterator]() { return this; },
+                    next() {
+                        this.i++;
+                        return { value: this.i, done: false };
+                    },
+                    return() { this.closed = true; return {}; },
+                };
+                for (let x of it) { if (x > 2) break; out += x; }
+                let fs = [];
+                let n = 0;
+                for (let c of "ab") { fs[n] = () => c; n++; }
+                var y;
+                for (y of [7]) ;
+                out + it.closed + fs[0]() + fs[1]() + y;
+            "#,
+            "3712trueab7",
+        );
+    }
+
+    #[test]
+    pub fn for_of_hoists_var_declarations() {
+        assert_completion("for (var x of [1]) x", 1.0);
+        assert_completion(
+            r#"
+                "use strict";
+                function f() {
+                    const before = typeof x;
+                    for (var x of [1, 2]);
+                    return `${before} ${x}`;
+                }
+                f();
+            "#,
+            "undefined 2",
+        );
+    }
+
+    #[test]
+    pub fn early_return() {
+        assert_completion(
+            r#"
+                function f(x) { if (x) { return 1; } return 2; }
+                f(true) + f(false) * 10;
+            "#,
+            21.0,
+        );
+    }
+
+    #[test]
+    pub fn return_from_nested_blocks() {
+        assert_completion(
+            r#"
+                function g() { { { return 3; } } return 4; }
+                g();
+            "#,
+            3.0,
+        );
+    }
+
+    #[test]
+    pub fn completion_value() {
+        assert_completion(
+            r#"
+                1;
+                let x = 2;
+            "#,
+            1.0,
+        );
+    }
 }
@@ -1,17 +1,112 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::BTreeMap;
+use std::{cell::RefCell, fmt, rc::Rc};
 
+use indexmap::IndexMap;
 use lib_ir::ast::{literal::Literal, literal_value::LiteralValue};
 
-use crate::closure::Closure;
+use crate::builtins::error::error_description;
+use crate::closure::{Closure, NativeFunction};
+use crate::object::{InternalSlot, JsObject, Property, PropertyKey, Symbol};
+use crate::realm::intrinsics;
 
-type JsObject = Rc<RefCell<HashMap<String, EvaluatorValue>>>;
+pub type JsArray = Rc<RefCell<Array>>;
+
+// Writing an index at most this far past the dense elements fills the holes in between, writing
+// further past them stores the element sparsely
+const MAX_DENSE_GAP: usize = 1024;
+
+// The elements of an array, holes in sparse arrays are represented by None. Elements written far
+// past the end are kept by index, so that `a[4294967294] = 1` does not allocate every hole before
+// it.
+#[derive(Debug, Default)]
+pub struct Array {
+    elements: Vec<Option<EvaluatorValue>>,
+    // Elements at or past the end of `elements`, only used once an index has been written too far
+    // past it
+    sparse: BTreeMap<usize, EvaluatorValue>,
+    length: usize,
+    // Own properties that are not indices, like the raw strings of a template strings array
+    pub properties: IndexMap<PropertyKey, EvaluatorValue>,
+    // A frozen array ignores writes to its elements and properties
+    pub frozen: bool,
+}
+
+impl Array {
+    fn new(elements: Vec<Option<EvaluatorValue>>) -> Self {
+        Array {
+            length: elements.len(),
+            elements,
+            ..Array::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    // The element at the index, None for a hole
+    pub fn get(&self, index: usize) -> Option<&EvaluatorValue> {
+        match self.elements.get(index) {
+            Some(element) => element.as_ref(),
+            None => self.sparse.get(&index),
+        }
+    }
+
+    // Writing past the end grows the array to hold the index
+    pub fn set(&mut self, index: usize, value: EvaluatorValue) {
+        if index < self.elements.len() {
+            self.elements[index] = Some(value);
+        } else if self.sparse.is_empty() && index - self.elements.len() <= MAX_DENSE_GAP {
+            self.elements.resize(index, None);
+            self.elements.push(Some(value));
+        } else {
+            self.sparse.insert(index, value);
+        }
+        self.length = self.length.max(index + 1);
+    }
+
+    // Shortening the array deletes the elements past the new length, lengthening it adds holes
+    // https://tc39.es/ecma262/#sec-arraysetlength
+    pub fn set_len(&mut self, length: usize) {
+        self.elements.truncate(length);
+        self.sparse.split_off(&length);
+        self.length = length;
+    }
+
+    // Every element up to the length, holes included
+    pub fn iter(&self) -> impl Iterator<Item = Option<&EvaluatorValue>> {
+        (0..self.length).map(|index| self.get(index))
+    }
+
+    // The elements that are not holes, with their index, in index order
+    pub fn entries(&self) -> impl Iterator<Item = (usize, &EvaluatorValue)> {
+        self.elements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, element)| Some((index, element.as_ref()?)))
+            .chain(self.sparse.iter().map(|(index, value)| (*index, value)))
+    }
+}
+
+impl From<Vec<EvaluatorValue>> for Array {
+    fn from(elements: Vec<EvaluatorValue>) -> Self {
+        Array::new(elements.into_iter().map(Some).collect())
+    }
+}
 
 // An internal representation of js values, including primitives, functions, objects
 #[derive(Clone, Debug)]
 pub enum EvaluatorValue {
     Literal(Literal),
     Closure(Closure),
+    NativeFunction(NativeFunction),
     Object(JsObject),
+    Array(JsArray),
+    Symbol(Symbol),
 }
 
 impl From<Literal> for EvaluatorValue {
@@ -32,44 +127,340 @@ impl From<Closure> for EvaluatorValue {
     }
 }
 
+impl From<NativeFunction> for EvaluatorValue {
+    fn from(f: NativeFunction) -> Self {
+        EvaluatorValue::NativeFunction(f)
+    }
+}
+
+impl From<Vec<Option<EvaluatorValue>>> for EvaluatorValue {
+    fn from(elements: Vec<Option<EvaluatorValue>>) -> Self {
+        EvaluatorValue::Array(Rc::new(RefCell::new(Array::new(elements))))
+    }
+}
+
+impl EvaluatorValue {
+    // https://262.ecma-international.org/5.1/#sec-11.9.6
+    pub fn strict_equals(&self, other: &EvaluatorValue) -> bool {
+        match (self, other) {
+            (EvaluatorValue::Literal(l1), EvaluatorValue::Literal(l2)) => l1.value.eq(&l2.value),
+            (EvaluatorValue::Object(o1), EvaluatorValue::Object(o2)) => Rc::ptr_eq(o1, o2),
+            (EvaluatorValue::Array(a1), EvaluatorValue::Array(a2)) => Rc::ptr_eq(a1, a2),
+            (EvaluatorValue::Symbol(s1), EvaluatorValue::Symbol(s2)) => s1 == s2,
+            (
+                EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_),
+                EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_),
+            ) => match (self.function_object(), other.function_object()) {
+                (Some(f1), Some(f2)) => Rc::ptr_eq(&f1, &f2),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    // The object holding the own properties of a function
+    pub fn function_object(&self) -> Option<JsObject> {
+        match self {
+            EvaluatorValue::Closure(c) => Some(Rc::clone(&c.object)),
+            EvaluatorValue::NativeFunction(f) => Some(Rc::clone(&f.object)),
+            _ => None,
+        }
+    }
+
+    // The [[Prototype]] of an object, function or array. Primitives have none until they are
+    // wrapped in objects.
+    pub fn prototype(&self) -> Option<JsObject> {
+        match self {
+            EvaluatorValue::Object(obj) => obj.borrow().prototype.clone(),
+            EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_) => {
+                self.function_object()?.borrow().prototype.clone()
+            }
+            EvaluatorValue::Array(_) => Some(intrinsics().array_prototype.clone()),
+            EvaluatorValue::Literal(_) | EvaluatorValue::Symbol(_) => None,
+        }
+    }
+
+    pub fn is_callable(&self) -> bool {
+        matches!(
+            self,
+            EvaluatorValue::Closure(_) | EvaluatorValue::NativeFunction(_)
+        )
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        matches!(
+            self,
+            EvaluatorValue::Literal(Literal {
+                value: LiteralValue::Undefined
+            })
+        )
+    }
+}
+
 #[allow(clippy::from_over_into)]
 impl Into<bool> for EvaluatorValue {
     fn into(self) -> bool {
         match self {
             EvaluatorValue::Literal(l) => l.value.into(),
             EvaluatorValue::Closure(c) => c.into(),
-            EvaluatorValue::Object(_) => true,
+            EvaluatorValue::NativeFunction(_)
+            | EvaluatorValue::Object(_)
+            | EvaluatorValue::Array(_)
+            | EvaluatorValue::Symbol(_) => true,
         }
     }
 }
 
-impl Into<String> for EvaluatorValue {
-    fn into(self) -> String {
-        match self {
+// https://tc39.es/ecma262/#sec-topropertykey
+impl From<EvaluatorValue> for PropertyKey {
+    fn from(value: EvaluatorValue) -> Self {
+        match value {
+            EvaluatorValue::Symbol(symbol) => PropertyKey::Symbol(symbol),
+            value => PropertyKey::String(value.into()),
+        }
+    }
+}
+
+impl From<EvaluatorValue> for String {
+    fn from(value: EvaluatorValue) -> Self {
+        match value {
             EvaluatorValue::Literal(l) => l.value.into(),
             EvaluatorValue::Closure(c) => c.to_string(),
-            EvaluatorValue::Object(_) => String::from("[object Object]"),
+            EvaluatorValue::NativeFunction(f) => f.to_string(),
+            EvaluatorValue::Symbol(symbol) => symbol.to_string(),
+            EvaluatorValue::Object(obj) => match &obj.borrow().internal_slot {
+                // https://tc39.es/ecma262/#sec-regexp.prototype.tostring
+                Some(InternalSlot::RegExp(regexp)) => {
+                    format!("/{}/{}", regexp.source, regexp.flags)
+                }
+                // https://tc39.es/ecma262/#sec-error.prototype.tostring
+                Some(InternalSlot::Error) => {
+                    let obj = obj.borrow();
+                    let part = |key: &str| obj.get(key).filter(|value| !value.is_undefined());
+                    error_description(
+                        part("name").map_or(String::from("Error"), String::from),
+                        part("message").map_or(String::new(), String::from),
+                    )
+                }
+                _ => String::from("[object Object]"),
+            },
+            // https://262.ecma-international.org/5.1/#sec-15.4.4.5
+            EvaluatorValue::Array(arr) => arr
+                .borrow()
+                .iter()
+                .map(|element| match element {
+                    None
+                    | Some(EvaluatorValue::Literal(Literal {
+                        value: LiteralValue::Undefined | LiteralValue::Null,
+                    })) => String::new(),
+                    Some(value) => String::from(value.clone()),
+                })
+                .collect::<Vec<String>>()
+                .join(","),
         }
     }
 }
 
-impl ToString for EvaluatorValue {
-    fn to_string(&self) -> String {
+impl fmt::Display for EvaluatorValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            EvaluatorValue::Literal(l) => l.value.to_owned().into(),
-            EvaluatorValue::Closure(c) => c.to_string(),
+            EvaluatorValue::Literal(l) => {
+                let s: String = l.value.to_owned().into();
+                write!(f, "{}", s)
+            }
+            EvaluatorValue::Closure(c) => write!(f, "{}", c),
+            EvaluatorValue::NativeFunction(native) => write!(f, "{}", native),
+            EvaluatorValue::Symbol(symbol) => write!(f, "{}", symbol),
             EvaluatorValue::Object(obj) => {
-                let mut s = String::from("{");
-                obj.borrow().iter().for_each(|(k, v)| {
-                    s.push_str(k.as_str());
-                    s.push_str(":");
-                    s.push_str(v.to_string().as_str());
-                    s.push_str(",");
-                });
-                s.pop();
-                s.push_str("}");
-                s
+                if let Some(InternalSlot::RegExp(regexp)) = &obj.borrow().internal_slot {
+                    return write!(f, "/{}/{}", regexp.source, regexp.flags);
+                }
+                let entries = obj
+                    .borrow()
+                    .properties
+                    .iter()
+                    .map(|(k, v)| {
+                        let key = match k {
+                            PropertyKey::String(k) => k.to_owned(),
+                            PropertyKey::Symbol(symbol) => format!("[{}]", symbol),
+                        };
+                        let value = match v {
+                            Property::Data(v) => v.to_string(),
+                            Property::Accessor { get, set } => String::from(match (get, set) {
+                                (Some(_), Some(_)) => "[Getter/Setter]",
+                                (Some(_), None) => "[Getter]",
+                                _ => "[Setter]",
+                            }),
+                        };
+                        format!("{}:{}", key, value)
+                    })
+                    .collect::<Vec<String>>();
+                write!(f, "{{{}}}", entries.join(","))
+            }
+            // Printed the way node does: [ 1, <1 empty item>, 'a' ]
+            EvaluatorValue::Array(arr) => {
+                let mut items = Vec::new();
+                let mut holes = 0;
+                let flush_holes = |holes: &mut usize, items: &mut Vec<String>| {
+                    match *holes {
+                        0 => {}
+                        1 => items.push(String::from("<1 empty item>")),
+                        n => items.push(format!("<{} empty items>", n)),
+                    }
+                    *holes = 0;
+                };
+                let arr = arr.borrow();
+                let mut next = 0;
+                for (index, value) in arr.entries() {
+                    holes += index - next;
+                    next = index + 1;
+                    flush_holes(&mut holes, &mut items);
+                    items.push(match value {
+                        EvaluatorValue::Literal(Literal {
+                            value: LiteralValue::String(s),
+                        }) => format!("'{}'", s),
+                        value => value.to_string(),
+                    });
+                }
+                holes += arr.len() - next;
+                flush_holes(&mut holes, &mut items);
+                if items.is_empty() {
+                    write!(f, "[]")
+                } else {
+                    write!(f, "[ {} ]", items.join(", "))
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_completion, assert_throws, eval};
+
+    #[test]
+    pub fn array_literal_with_holes() {
+        assert_completion(
+            r#"
+                const a = [1, , 3];
+                a[1] === undefined && a.length === 3;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn array_write_past_end_extends() {
+        assert_completion(
+            r#"
+                const a = [1, 2];
+                a[4] = 5;
+                a.length + a[4];
+            "#,
+            10.0,
+        );
+    }
+
+    #[test]
+    pub fn array_length_truncates() {
+        assert_completion(
+            r#"
+                const a = [1, 2, 3, 4];
+                a.length = 2;
+                a[3] === undefined && a.length === 2;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn array_to_string() {
+        assert_completion(
+            r#"
+                const a = [1, [2, 3], null, "x"];
+                a + "";
+            "#,
+            "1,2,3,,x",
+        );
+    }
+
+    #[test]
+    pub fn invalid_array_length() {
+        assert_throws(
+            r#"
+                const a = [1];
+                a.length = -1;
+            "#,
+            "RangeError: Invalid array length",
+        );
+    }
+
+    #[test]
+    pub fn array_index_keys() {
+        assert_completion(
+            r#"
+                const a = [1, 2, 3];
+                const i = 1;
+                a["02"] === undefined && a["2"] + a[i + 1] === 6;
+            "#,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn high_indices_are_stored_sparsely() {
+        assert_completion(
+            r#"
+                const a = [1];
+                a[4294967294] = 2;
+                a[1e9] = 3;
+                a[4294967295] = 4;
+                `${a.length} ${a[1e9]} ${a[4294967294]} ${a[4294967295]} ${5 in a} ${1e9 in a}`;
+            "#,
+            "4294967295 3 2 4 false true",
+        );
+        assert_completion(
+            r#"
+                const a = [];
+                a[1e9] = 1;
+                a.length = 10;
+                a[3] = 2;
+                `${a.length} ${a[1e9]} ${a[3]}`;
+            "#,
+            "10 undefined 2",
+        );
+    }
+
+    #[test]
+    pub fn length_is_converted_to_a_whole_number() {
+        assert_completion(
+            r#"
+                const a = [1, 2, 3, 4];
+                a.length = "3";
+                const b = [];
+                b.length = 4294967295;
+                const c = [1, 2, 3];
+                c.length = [1];
+                `${a.length} ${b.length} ${c.length}`;
+            "#,
+            "3 4294967295 1",
+        );
+        assert_throws(
+            "[].length = 4294967296;",
+            "RangeError: Invalid array length",
+        );
+        assert_throws("[].length = 1.5;", "RangeError: Invalid array length");
+        assert_throws("[].length = 'x';", "RangeError: Invalid array length");
+    }
+
+    #[test]
+    pub fn objects_print_their_properties_in_insertion_order() {
+        let printed = |source| eval(source).expect("Unable to eval").to_string();
+        assert_eq!(printed("({})"), "{}");
+        assert_eq!(
+            printed("const o = { zebra: 1, apple: 2, mango: 3 }; o.banana = 4; o.zebra = 5; o"),
+            "{zebra:5,apple:2,mango:3,banana:4}"
+        );
+        assert_throws("for (const x of {}) {}", "{} is not iterable");
+    }
+}
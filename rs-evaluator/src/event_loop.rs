@@ -0,0 +1,181 @@
+use wasm_bindgen::prelude::*;
+
+use crate::constants::JS_UNDEFINED;
+use crate::evaluator::{call_value, EvaluatorError};
+use crate::evaluator_value::EvaluatorValue;
+use crate::realm::{intrinsics, run_jobs};
+
+// How the event loop waits for timers. With the virtual clock time jumps straight to the next
+// timer that is due, so a program that sleeps finishes right away and always does the same thing.
+// Real time waits for the delays to pass.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Clock {
+    #[default]
+    Virtual,
+    RealTime,
+}
+
+// A callback scheduled by setTimeout or setInterval
+struct Timer {
+    id: u32,
+    // In milliseconds since the program started
+    due: f64,
+    // Tells apart timers that are due at the same time, the one scheduled first runs first
+    sequence: u64,
+    interval: Option<f64>,
+    callback: EvaluatorValue,
+    arguments: Vec<EvaluatorValue>,
+}
+
+// What the timer that runs next is waiting for
+enum NextTimer {
+    Due(Timer),
+    // In milliseconds from now
+    Waiting(f64),
+    None,
+}
+
+// The timers waiting to run, and the time on the virtual clock
+#[derive(Default)]
+pub struct Timers {
+    clock: Clock,
+    // The time on the host when the program started, for the real-time clock
+    start: f64,
+    now: f64,
+    next_id: u32,
+    next_sequence: u64,
+    pending: Vec<Timer>,
+}
+
+impl Timers {
+    pub fn new(clock: Clock) -> Timers {
+        Timers {
+            clock,
+            start: host_time(),
+            ..Default::default()
+        }
+    }
+
+    // In milliseconds since the program started
+    fn now(&self) -> f64 {
+        match self.clock {
+            Clock::Virtual => self.now,
+            Clock::RealTime => host_time() - self.start,
+        }
+    }
+
+    // Delays are clamped to at least a millisecond the way node does, so a zero delay still runs
+    // after every job queued before it
+    pub fn schedule(
+        &mut self,
+        callback: EvaluatorValue,
+        delay: f64,
+        repeat: bool,
+        arguments: Vec<EvaluatorValue>,
+    ) -> u32 {
+        let delay = match delay {
+            delay if delay >= 1.0 && delay <= i32::MAX as f64 => delay.trunc(),
+            _ => 1.0,
+        };
+        self.next_id += 1;
+        let id = self.next_id;
+        self.push(Timer {
+            id,
+            due: self.now() + delay,
+            sequence: 0,
+            interval: repeat.then_some(delay),
+            callback,
+            arguments,
+        });
+        id
+    }
+
+    pub fn clear(&mut self, id: u32) {
+        self.pending.retain(|timer| timer.id != id);
+    }
+
+    fn push(&mut self, mut timer: Timer) {
+        self.next_sequence += 1;
+        timer.sequence = self.next_sequence;
+        self.pending.push(timer);
+    }
+
+    // Removes the timer that runs next once it is due, an interval is scheduled again before its
+    // callback runs so that the callback can clear it. The virtual clock is always moved forward
+    // to the next timer.
+    fn pop(&mut self) -> NextTimer {
+        let Some(index) = (0..self.pending.len()).min_by(|&a, &b| {
+            let (a, b) = (&self.pending[a], &self.pending[b]);
+            a.due.total_cmp(&b.due).then(a.sequence.cmp(&b.sequence))
+        }) else {
+            return NextTimer::None;
+        };
+        let due = self.pending[index].due;
+        match self.clock {
+            Clock::Virtual => self.now = self.now.max(due),
+            Clock::RealTime => {
+                let now = self.now();
+                if due > now {
+                    return NextTimer::Waiting(due - now);
+                }
+            }
+        }
+        let timer = self.pending.remove(index);
+        if let Some(interval) = timer.interval {
+            self.push(Timer {
+                id: timer.id,
+                due: self.now() + interval,
+                sequence: 0,
+                interval: timer.interval,
+                callback: timer.callback.clone(),
+                arguments: timer.arguments.clone(),
+            });
+        }
+        NextTimer::Due(timer)
+    }
+}
+
+// Runs the program to completion: jobs first, then timers in the order they fall due, each
+// followed by the jobs it queued. Waiting for real time blocks the thread, hosts that cannot
+// block drive the loop with run_due_tasks instead. An exception thrown by a timer callback ends
+// the program.
+// https://html.spec.whatwg.org/multipage/webappapis.html#event-loop-processing-model
+pub fn run_event_loop() -> Result<(), EvaluatorError> {
+    while let Some(delay) = run_due_tasks()? {
+        std::thread::sleep(std::time::Duration::from_secs_f64(delay / 1000.0));
+    }
+    Ok(())
+}
+
+// Runs the jobs and the timers that are due, returning how many milliseconds are left until the
+// next timer falls due, or None once no timers are left
+pub fn run_due_tasks() -> Result<Option<f64>, EvaluatorError> {
+    loop {
+        run_jobs()?;
+        let timer = intrinsics().timers.borrow_mut().pop();
+        let timer = match timer {
+            NextTimer::Due(timer) => timer,
+            NextTimer::Waiting(delay) => return Ok(Some(delay)),
+            NextTimer::None => return Ok(None),
+        };
+        call_value(
+            timer.callback,
+            EvaluatorValue::from(JS_UNDEFINED),
+            timer.arguments,
+        )?;
+    }
+}
+
+// The time on the host in milliseconds
+#[cfg(not(target_arch = "wasm32"))]
+fn host_time() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |time| time.as_secs_f64() * 1000.0)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn host_time() -> f64 {
+    js_sys::Date::now()
+}
@@ -0,0 +1,948 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+use lib_ir::ast::literal::Literal;
+use lib_ir::ast::literal_value::LiteralValue;
+use lib_ir::ast::{
+    self, BlockStatement, Identifier, Node, NodeKind, UnaryOperator, YieldExpression,
+};
+
+use crate::builtins::iterator::create_iter_result_object;
+use crate::closure::{Closure, NativeFunction};
+use crate::completion::Completion;
+use crate::constants::JS_UNDEFINED;
+use crate::evaluator::{
+    call_value, eval_assignment_expr, eval_block, eval_expression, eval_update_expression,
+    get_iterator, get_member, short_circuits, CompletionResult, Env, EvaluatorError,
+    EvaluatorResult,
+};
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::{InternalSlot, Object};
+use crate::promise::{as_promise, perform_then, promise_resolve, Capability};
+use crate::realm::intrinsics;
+
+// Statements are evaluated by Rust async functions, so that a yield or an await can suspend a
+// generator or async function body halfway through a statement and carry on from there when it
+// is resumed. Nothing waits on these futures, they are polled by hand: by next, throw and return
+// for a generator, and by the reactions to the awaited promise for an async function.
+pub type Suspendable<T> = Pin<Box<dyn Future<Output = T>>>;
+
+// Code outside generator and async function bodies has no coroutine to suspend, so it completes
+// the first time it is polled
+pub fn run<T>(future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(value) => value,
+        Poll::Pending => unreachable!("only generator and async function bodies suspend"),
+    }
+}
+
+// https://tc39.es/ecma262/#sec-properties-of-generator-instances
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GeneratorState {
+    SuspendedStart,
+    SuspendedYield,
+    Executing,
+    Completed,
+}
+
+// The completion a suspended generator is resumed with, by its next, throw or return method
+pub enum Resumption {
+    Next(EvaluatorValue),
+    Throw(EvaluatorValue),
+    Return(EvaluatorValue),
+}
+
+#[derive(Default)]
+struct Channel {
+    yielded: Option<EvaluatorValue>,
+    resumption: Option<Resumption>,
+}
+
+// Shared by a generator and its body, values are yielded out and resumptions handed in through it
+#[derive(Clone, Default)]
+pub struct Coroutine(Rc<RefCell<Channel>>);
+
+impl Coroutine {
+    async fn suspend(&self, value: EvaluatorValue) -> Resumption {
+        self.0.borrow_mut().yielded = Some(value);
+        Suspend {
+            channel: Rc::clone(&self.0),
+            suspended: false,
+        }
+        .await
+    }
+
+    // Runs a body until it suspends again or completes, handing it the resumption
+    fn resume<T>(&self, body: &mut Suspendable<T>, resumption: Resumption) -> Poll<T> {
+        self.0.borrow_mut().resumption = Some(resumption);
+        body.as_mut().poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    // Suspends an async body until the value settles, and evaluates to what it was fulfilled with
+    // https://tc39.es/ecma262/#await
+    pub async fn await_value(&self, value: EvaluatorValue) -> EvaluatorResult {
+        resumed_value(self.suspend(value).await)
+    }
+
+    // The value the body suspended with
+    fn take_yielded(&self) -> EvaluatorValue {
+        self.0
+            .borrow_mut()
+            .yielded
+            .take()
+            .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED))
+    }
+}
+
+// What a yield or await evaluates to once the body is resumed
+fn resumed_value(resumption: Resumption) -> EvaluatorResult {
+    match resumption {
+        Resumption::Next(value) => Ok(value),
+        Resumption::Throw(exception) => Err(EvaluatorError::Throw(exception)),
+        Resumption::Return(value) => Err(EvaluatorError::GeneratorReturn(value)),
+    }
+}
+
+// Pending the first time it is polled, then ready with whatever the generator was resumed with
+struct Suspend {
+    channel: Rc<RefCell<Channel>>,
+    suspended: bool,
+}
+
+impl Future for Suspend {
+    type Output = Resumption;
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Resumption> {
+        if !self.suspended {
+            self.suspended = true;
+            return Poll::Pending;
+        }
+        match self.channel.borrow_mut().resumption.take() {
+            Some(resumption) => Poll::Ready(resumption),
+            None => Poll::Pending,
+        }
+    }
+}
+
+pub struct Generator {
+    state: GeneratorState,
+    coroutine: Coroutine,
+    body: Option<Suspendable<CompletionResult>>,
+}
+
+// The generator returned by calling a generator function, whose parameters are already bound
+// in `env`. It inherits from the prototype property of the function.
+// https://tc39.es/ecma262/#sec-generatorstart
+pub fn create_generator(closure: Closure, env: Env) -> EvaluatorValue {
+    let prototype = match closure.object.borrow().get("prototype") {
+        Some(EvaluatorValue::Object(prototype)) => prototype,
+        _ => Rc::clone(&intrinsics().generator_prototype),
+    };
+    let coroutine = Coroutine::default();
+    let generator = Generator {
+        state: GeneratorState::SuspendedStart,
+        body: Some(Box::pin(eval_block(
+            closure.body,
+            env,
+            Some(coroutine.clone()),
+        ))),
+        coroutine,
+    };
+    let object = Object::new(Some(prototype));
+    object.borrow_mut().internal_slot =
+        Some(InternalSlot::Generator(Rc::new(RefCell::new(generator))));
+    EvaluatorValue::Object(object)
+}
+
+// Runs the body until its next yield or its end, returning the iterator result for the caller
+// https://tc39.es/ecma262/#sec-generatorresume
+// https://tc39.es/ecma262/#sec-generatorresumeabrupt
+pub fn resume(generator: &Rc<RefCell<Generator>>, resumption: Resumption) -> EvaluatorResult {
+    let state = generator.borrow().state;
+    let resumption = match (state, resumption) {
+        (GeneratorState::Executing, _) => {
+            return Err(EvaluatorError::InvalidType(String::from(
+                "Generator is already running",
+            )))
+        }
+        (GeneratorState::SuspendedStart | GeneratorState::Completed, resumption) => {
+            match resumption {
+                Resumption::Next(_) if state == GeneratorState::SuspendedStart => resumption,
+                Resumption::Next(_) => {
+                    return Ok(create_iter_result_object(
+                        EvaluatorValue::from(JS_UNDEFINED),
+                        true,
+                    ))
+                }
+                // A generator that has not started has no finally blocks to run
+                Resumption::Return(value) => {
+                    complete(generator);
+                    return Ok(create_iter_result_object(value, true));
+                }
+                Resumption::Throw(exception) => {
+                    complete(generator);
+                    return Err(EvaluatorError::Throw(exception));
+                }
+            }
+        }
+        (GeneratorState::SuspendedYield, resumption) => resumption,
+    };
+
+    // The body is taken out while it runs, it may call back into this generator
+    let (mut body, coroutine) = {
+        let mut generator = generator.borrow_mut();
+        generator.state = GeneratorState::Executing;
+        let body = generator
+            .body
+            .take()
+            .expect("a suspended generator has a body");
+        (body, generator.coroutine.clone())
+    };
+    let poll = coroutine.resume(&mut body, resumption);
+
+    let mut generator = generator.borrow_mut();
+    match poll {
+        Poll::Pending => {
+            generator.state = GeneratorState::SuspendedYield;
+            generator.body = Some(body);
+            Ok(create_iter_result_object(coroutine.take_yielded(), false))
+        }
+        Poll::Ready(result) => {
+            generator.state = GeneratorState::Completed;
+            match result {
+                Ok(Completion::Return(value)) | Err(EvaluatorError::GeneratorReturn(value)) => {
+                    Ok(create_iter_result_object(value, true))
+                }
+                Ok(_) => Ok(create_iter_result_object(
+                    EvaluatorValue::from(JS_UNDEFINED),
+                    true,
+                )),
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
+
+fn complete(generator: &Rc<RefCell<Generator>>) {
+    let mut generator = generator.borrow_mut();
+    generator.state = GeneratorState::Completed;
+    generator.body = None;
+}
+
+struct AsyncFunction {
+    coroutine: Coroutine,
+    body: Option<Suspendable<CompletionResult>>,
+    capability: Capability,
+}
+
+// Runs the body of an async function until its first await, the rest runs in the jobs that
+// react to the awaited promises. The capability's promise settles with the outcome of the body.
+// https://tc39.es/ecma262/#sec-asyncblockstart
+pub fn start_async_function(
+    body: BlockStatement,
+    env: Env,
+    capability: &Capability,
+) -> Result<(), EvaluatorError> {
+    start_async_body(
+        |coroutine| Box::pin(eval_block(body, env, Some(coroutine))),
+        capability,
+    )
+}
+
+// Runs any body that may await, like the body of a module that awaits at its top level. The
+// capability's promise is fulfilled with the value of a return completion.
+pub fn start_async_body(
+    body: impl FnOnce(Coroutine) -> Suspendable<CompletionResult>,
+    capability: &Capability,
+) -> Result<(), EvaluatorError> {
+    let coroutine = Coroutine::default();
+    let task = AsyncFunction {
+        body: Some(body(coroutine.clone())),
+        coroutine,
+        capability: capability.clone(),
+    };
+    step_async_function(
+        &Rc::new(RefCell::new(task)),
+        Resumption::Next(EvaluatorValue::from(JS_UNDEFINED)),
+    )
+}
+
+fn step_async_function(
+    task: &Rc<RefCell<AsyncFunction>>,
+    resumption: Resumption,
+) -> Result<(), EvaluatorError> {
+    let (mut body, coroutine, capability) = {
+        let mut task = task.borrow_mut();
+        let body = task
+            .body
+            .take()
+            .expect("a suspended async function has a body");
+        (body, task.coroutine.clone(), task.capability.clone())
+    };
+    match coroutine.resume(&mut body, resumption) {
+        Poll::Pending => {
+            task.borrow_mut().body = Some(body);
+            await_value(task, coroutine.take_yielded())
+        }
+        Poll::Ready(Ok(Completion::Return(value))) => capability.resolve(value),
+        Poll::Ready(Ok(_)) => capability.resolve(EvaluatorValue::from(JS_UNDEFINED)),
+        Poll::Ready(Err(e)) => capability.reject(e.into_exception()?),
+    }
+}
+
+// The function carries on once the awaited value settles, even when it is not a promise
+// https://tc39.es/ecma262/#await
+fn await_value(
+    task: &Rc<RefCell<AsyncFunction>>,
+    value: EvaluatorValue,
+) -> Result<(), EvaluatorError> {
+    let promise = promise_resolve(value)?;
+    let resume_with = |resumption: fn(EvaluatorValue) -> Resumption| {
+        let task = Rc::clone(task);
+        EvaluatorValue::from(NativeFunction::new("", move |_, args| {
+            let value = args
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED));
+            step_async_function(&task, resumption(value))?;
+            Ok(EvaluatorValue::from(JS_UNDEFINED))
+        }))
+    };
+    let promise = as_promise(&promise).expect("promise_resolve returns a promise");
+    perform_then(
+        &promise,
+        resume_with(Resumption::Next),
+        resume_with(Resumption::Throw),
+        None,
+    );
+    Ok(())
+}
+
+// Whether evaluating a node may suspend, functions and classes nested in it have bodies of their
+// own. Only the heritage and computed keys of a class are evaluated as part of the enclosing body.
+pub fn may_suspend(node: &Node) -> bool {
+    match &node.kind {
+        NodeKind::YieldExpression(_) | NodeKind::AwaitExpression(_) => true,
+        NodeKind::FunctionDeclaration(_)
+        | NodeKind::FunctionExpression(_)
+        | NodeKind::ArrowFunctionExpression(_) => false,
+        NodeKind::Class(c) | NodeKind::ClassExpression(c) => {
+            class_may_suspend(&c.super_class, &c.body)
+        }
+        NodeKind::ClassDeclaration(c) => class_may_suspend(&c.super_class, &c.body),
+        _ => node.children().into_iter().any(may_suspend),
+    }
+}
+
+fn class_may_suspend(super_class: &Option<Box<Node>>, body: &ast::ClassBody) -> bool {
+    super_class.as_deref().is_some_and(may_suspend)
+        || body.body.iter().any(|element| match &element.kind {
+            NodeKind::MethodDefinition(m) => m.computed && may_suspend(&m.key),
+            NodeKind::PropertyDefinition(p) => p.computed && may_suspend(&p.key),
+            _ => false,
+        })
+}
+
+// Expressions of generator and async function bodies may suspend them, those of other code are
+// left to the evaluator
+pub async fn eval_expression_in(node: Node, env: Env, co: Option<Coroutine>) -> EvaluatorResult {
+    match co {
+        Some(co) => eval_expr(node, env, co).await,
+        None => eval_expression(node, env),
+    }
+}
+
+// Expressions that can hold a yield are evaluated here, the others are left to the evaluator
+fn eval_expr(node: Node, env: Env, co: Coroutine) -> Suspendable<EvaluatorResult> {
+    Box::pin(async move {
+        if !may_suspend(&node) {
+            return eval_expression(node, env);
+        }
+        match node.kind {
+            NodeKind::YieldExpression(y) => eval_yield(y, env, co).await,
+            NodeKind::AwaitExpression(a) => {
+                let value = eval_expr(*a.argument, env, co.clone()).await?;
+                co.await_value(value).await
+            }
+            NodeKind::LogicalExpression(e) => {
+                let left_value = eval_expr(*e.left, Rc::clone(&env), co.clone()).await?;
+                if short_circuits(&e.operator, &left_value) {
+                    Ok(left_value)
+                } else {
+                    eval_expr(*e.right, env, co).await
+                }
+            }
+            NodeKind::ConditionalExpression(e) => {
+                let test_value: bool = eval_expr(*e.test, Rc::clone(&env), co.clone())
+                    .await?
+                    .into();
+                match test_value {
+                    true => eval_expr(*e.consequent, env, co).await,
+                    false => eval_expr(*e.alternate, env, co).await,
+                }
+            }
+            NodeKind::AssignmentExpression(e) => eval_assignment_expr(e, env, Some(co)).await,
+            NodeKind::UpdateExpression(e) => eval_update_expression(e, env, Some(co)).await,
+            kind => {
+                eval_operands(
+                    Node {
+                        loc: node.loc,
+                        kind,
+                    },
+                    env,
+                    co,
+                )
+                .await
+            }
+        }
+    })
+}
+
+// The operands of an expression are evaluated in order up to the last one that holds a yield,
+// and replaced by bindings of their values. What is left of the expression has no yield in it
+// and is evaluated by the evaluator, in a frame that has those bindings.
+async fn eval_operands(mut node: Node, env: Env, co: Coroutine) -> EvaluatorResult {
+    let scope = env.borrow().extend(Rc::clone(&env));
+    let operands = operands_mut(&mut node.kind);
+    let Some(last) = operands.iter().rposition(|operand| may_suspend(operand)) else {
+        return Err(EvaluatorError::SyntaxError(String::from(
+            "yield is not supported in this position",
+        )));
+    };
+    for (i, operand) in operands.into_iter().take(last + 1).enumerate() {
+        // Not a valid identifier, so it cannot clash with a name from the source
+        let id = Identifier {
+            name: format!("%{}", i),
+        };
+        let placeholder = Node {
+            loc: None,
+            kind: NodeKind::Identifier(id.clone()),
+        };
+        let operand = std::mem::replace(operand, placeholder);
+        let value = eval_expr(operand, Rc::clone(&scope), co.clone()).await?;
+        scope
+            .borrow_mut()
+            .define(id, value, "const")
+            .map_err(EvaluatorError::EnvironmentError)?;
+    }
+    eval_expression(node, scope)
+}
+
+// The subexpressions of an expression that are always evaluated, in the order they are evaluated
+fn operands_mut(kind: &mut NodeKind) -> Vec<&mut Node> {
+    match kind {
+        NodeKind::BinaryExpression(e) => match e.left.kind {
+            NodeKind::PrivateIdentifier(_) => vec![&mut e.right],
+            _ => vec![&mut e.left, &mut e.right],
+        },
+        NodeKind::UnaryExpression(e) if !matches!(e.operator, UnaryOperator::Delete) => {
+            vec![&mut e.argument]
+        }
+        NodeKind::MemberExpression(e) => member_operands(e),
+        NodeKind::CallExpression(e) => {
+            let mut operands = callee_operands(&mut e.callee);
+            operands.extend(e.arguments.iter_mut().map(spread_operand));
+            operands
+        }
+        NodeKind::NewExpression(e) => {
+            let mut operands = vec![&mut *e.callee];
+            operands.extend(e.arguments.iter_mut().map(spread_operand));
+            operands
+        }
+        NodeKind::ArrayExpression(e) => e
+            .elements
+            .iter_mut()
+            .flatten()
+            .map(spread_operand)
+            .collect(),
+        NodeKind::ObjectExpression(e) => e
+            .properties
+            .iter_mut()
+            .flat_map(|property| match property {
+                ast::ObjectExpressionProperty::Property(p) => match p.computed {
+                    true => vec![&mut *p.key, &mut *p.value],
+                    false => vec![&mut *p.value],
+                },
+                ast::ObjectExpressionProperty::SpreadElement(s) => vec![&mut *s.argument],
+            })
+            .collect(),
+        NodeKind::TemplateLiteral(t) => t.expressions.iter_mut().map(|e| &mut **e).collect(),
+        NodeKind::TaggedTemplateExpression(t) => {
+            let mut operands = callee_operands(&mut t.tag);
+            operands.extend(t.quasi.expressions.iter_mut().map(|e| &mut **e));
+            operands
+        }
+        NodeKind::SequenceExpression(e) => e.expressions.iter_mut().collect(),
+        // The heritage of a class is evaluated before anything in its body, then the computed
+        // keys of its elements in order. Field values are evaluated later, as functions would be.
+        NodeKind::ClassExpression(c) => {
+            let mut operands: Vec<&mut Node> = c.super_class.iter_mut().map(|c| &mut **c).collect();
+            operands.extend(
+                c.body
+                    .body
+                    .iter_mut()
+                    .filter_map(|element| match element.kind {
+                        NodeKind::MethodDefinition(ref mut m) if m.computed => Some(&mut *m.key),
+                        NodeKind::PropertyDefinition(ref mut p) if p.computed => Some(&mut *p.key),
+                        _ => None,
+                    }),
+            );
+            operands
+        }
+        _ => vec![],
+    }
+}
+
+// The object and computed key of a member expression, super is not a value of its own
+fn member_operands(e: &mut ast::MemberExpression) -> Vec<&mut Node> {
+    let mut operands = vec![];
+    if !matches!(e.object.kind, NodeKind::Super(_)) {
+        operands.push(&mut *e.object);
+    }
+    if e.computed {
+        operands.push(&mut *e.property);
+    }
+    operands
+}
+
+// A method call keeps its member expression, so that the method is called on its object
+fn callee_operands(callee: &mut Node) -> Vec<&mut Node> {
+    match callee.kind {
+        NodeKind::MemberExpression(ref mut e) => member_operands(e),
+        NodeKind::Super(_) => vec![],
+        _ => vec![callee],
+    }
+}
+
+fn spread_operand(argument: &mut Box<Node>) -> &mut Node {
+    match argument.kind {
+        NodeKind::SpreadElement(ref mut s) => &mut s.argument,
+        _ => argument,
+    }
+}
+
+// https://tc39.es/ecma262/#sec-generator-function-definitions-runtime-semantics-evaluation
+async fn eval_yield(
+    YieldExpression { argument, delegate }: YieldExpression,
+    env: Env,
+    co: Coroutine,
+) -> EvaluatorResult {
+    let value = match argument {
+        Some(argument) => eval_expr(*argument, env, co.clone()).await?,
+        None => EvaluatorValue::from(JS_UNDEFINED),
+    };
+    if delegate {
+        return eval_yield_delegate(value, co).await;
+    }
+    resumed_value(co.suspend(value).await)
+}
+
+// yield* hands every value of the inner iterator out, and passes what the generator is resumed
+// with on to the inner iterator's next, throw and return methods
+async fn eval_yield_delegate(iterable: EvaluatorValue, co: Coroutine) -> EvaluatorResult {
+    let mut iterator = get_iterator(iterable)?;
+    let mut received = Resumption::Next(EvaluatorValue::from(JS_UNDEFINED));
+    loop {
+        let returning = matches!(received, Resumption::Return(_));
+        let result = match received {
+            Resumption::Next(value) => call_value(
+                iterator.next_method.clone(),
+                iterator.iterator.clone(),
+                vec![value],
+            )?,
+            Resumption::Throw(exception) => match get_method(&iterator.iterator, "throw")? {
+                Some(throw) => call_value(throw, iterator.iterator.clone(), vec![exception])?,
+                None => {
+                    iterator.close()?;
+                    return Err(EvaluatorError::InvalidType(String::from(
+                        "The iterator does not provide a 'throw' method",
+                    )));
+                }
+            },
+            Resumption::Return(value) => match get_method(&iterator.iterator, "return")? {
+                Some(r#return) => call_value(r#return, iterator.iterator.clone(), vec![value])?,
+                None => return Err(EvaluatorError::GeneratorReturn(value)),
+            },
+        };
+        if let EvaluatorValue::Literal(_) = result {
+            return Err(EvaluatorError::InvalidType(format!(
+                "Iterator result {} is not an object",
+                result
+            )));
+        }
+        let done: bool = get_member(result.clone(), String::from("done"))?.into();
+        let value = get_member(result, String::from("value"))?;
+        match (done, returning) {
+            (true, true) => return Err(EvaluatorError::GeneratorReturn(value)),
+            (true, false) => return Ok(value),
+            (false, _) => received = co.suspend(value).await,
+        }
+    }
+}
+
+// A method that may be missing, undefined and null count as missing
+// https://tc39.es/ecma262/#sec-getmethod
+fn get_method(value: &EvaluatorValue, key: &str) -> Result<Option<EvaluatorValue>, EvaluatorError> {
+    let method = get_member(value.clone(), String::from(key))?;
+    if let EvaluatorValue::Literal(Literal {
+        value: LiteralValue::Undefined | LiteralValue::Null,
+    }) = method
+    {
+        return Ok(None);
+    }
+    if !method.is_callable() {
+        return Err(EvaluatorError::InvalidType(format!(
+            "{} is not a function",
+            method
+        )));
+    }
+    Ok(Some(method))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::assert_completion;
+
+    #[test]
+    pub fn generator_next_and_return_value() {
+        assert_completion(
+            r#"
+                function* count(n) {
+                    for (let i = 0; i < n; i++) {
+                        const got = yield i * 10;
+                        if (got) n = got;
+                    }
+                    return "end";
+                }
+                let g = count(2);
+                let s = "";
+                let r = g.next();
+                s += r.value + ":" + r.done + ",";
+                r = g.next();
+                s += r.value + ",";
+                r = g.next(3);
+                s += r.value + ",";
+                r = g.next();
+                s += r.value + ":" + r.done + ",";
+                r = g.next();
+                s += r.value + ":" + r.done;
+                s;
+            "#,
+            "0:false,10,20,end:true,undefined:true",
+        );
+    }
+
+    #[test]
+    pub fn generator_delegation() {
+        assert_completion(
+            r#"
+                function* inner() {
+                    const x = yield 1;
+                    yield x + 1;
+                    return "inner done";
+                }
+                function* outer() {
+                    const r = yield* inner();
+                    yield r;
+                    yield* "ab";
+                }
+                let s = "";
+                let g = outer();
+                s += g.next().value + ";";
+                s += g.next(1).value + ";";
+                for (const v of g) s += v + ";";
+                s;
+            "#,
+            "1;2;inner done;a;b;",
+        );
+    }
+
+    #[test]
+    pub fn generator_return_runs_finally() {
+        assert_completion(
+            r#"
+                let s = "";
+                function* fin() {
+                    try { yield 1; yield 2; } finally { s += "cleanup"; }
+                }
+                let f = fin();
+                f.next();
+                let r = f.return(42);
+                s += r.value + "" + r.done + ",";
+                for (const v of fin()) { s += "after break "; break; }
+                s;
+            "#,
+            "cleanup42true,after break cleanup",
+        );
+    }
+
+    #[test]
+    pub fn generator_throw() {
+        assert_completion(
+            r#"
+                function* catcher() {
+                    try { yield 1; } catch (e) { yield "caught " + e; }
+                }
+                let c = catcher();
+                c.next();
+                let s = c.throw("boom").value + "|";
+                function* g() { yield 1; }
+                let t = g();
+                try { t.throw("x"); } catch (e) { s += e + "|" + t.next().done; }
+                s;
+            "#,
+            "caught boom|x|true",
+        );
+    }
+
+    #[test]
+    pub fn generator_not_constructible() {
+        assert_completion(
+            r#"
+                function* g() { yield 1; }
+                let out = "";
+                try { new g(); } catch (e) { out += e.message + ";"; }
+                let it;
+                function* reenter() { it.next(); yield 1; }
+                it = reenter();
+                try { it.next(); } catch (e) { out += e.message + ";"; }
+                out + (Object.getPrototypeOf(g()) === g.prototype);
+            "#,
+            "g is not a constructor;Generator is already running;true",
+        );
+    }
+
+    #[test]
+    pub fn yield_in_switch_statements() {
+        assert_completion(
+            r#"
+                function* pick(v) {
+                    switch (v) {
+                        case (yield "test"): return "matched";
+                        default: yield "default";
+                    }
+                    return "done";
+                }
+                let a = pick(1);
+                let b = pick(2);
+                `${a.next().value} ${a.next(1).value} ${b.next().value} ${b.next(3).value} ${b.next().value}`;
+            "#,
+            "test matched test default done",
+        );
+    }
+
+    #[test]
+    pub fn compound_assignment_reads_the_target_before_yielding() {
+        assert_completion(
+            r#"
+                function* g() {
+                    let x = 1;
+                    x += yield 5;
+                    let o = { a: 1 };
+                    o[yield "key"] += yield "value";
+                    return `${x} ${o.a}`;
+                }
+                let it = g();
+                it.next();
+                it.next(5);
+                it.next("a");
+                it.next(10).value;
+            "#,
+            "6 11",
+        );
+    }
+
+    #[test]
+    pub fn yield_in_updates_sequences_and_logical_assignments() {
+        assert_completion(
+            r#"
+                function* g() {
+                    let o = { n: 0 };
+                    o[yield]++;
+                    let x = 0;
+                    x ||= yield;
+                    return (yield, x + o.n);
+                }
+                let it = g();
+                it.next();
+                it.next("n");
+                it.next(2);
+                it.next().value;
+            "#,
+            3.0,
+        );
+    }
+
+    #[test]
+    pub fn yield_in_class_heritage() {
+        assert_completion(
+            r#"
+                function* g() {
+                    class A extends (yield) { m() { return this.base(); } }
+                    return new A().m();
+                }
+                let it = g();
+                it.next();
+                it.next(class { base() { return "base"; } }).value;
+            "#,
+            "base",
+        );
+    }
+
+    #[test]
+    pub fn yield_in_destructuring_defaults() {
+        assert_completion(
+            r#"
+                function* g() {
+                    const { a = yield 1 } = {};
+                    const [b = yield 2] = [];
+                    let c;
+                    ({ c = yield 3 } = {});
+                    for (const { d = yield 4 } of [{}]) {
+                        return a + b + c + d;
+                    }
+                }
+                let it = g();
+                it.next();
+                it.next(1);
+                it.next(2);
+                it.next(3);
+                it.next(4).value;
+            "#,
+            10.0,
+        );
+    }
+
+    #[test]
+    pub fn yield_in_computed_pattern_keys() {
+        assert_completion(
+            r#"
+                function* g(o) {
+                    const { [yield "key"]: v, ...rest } = o;
+                    return `${v} ${rest.a} ${rest.b}`;
+                }
+                let it = g({ a: 1, b: 2 });
+                it.next();
+                it.next("b").value;
+            "#,
+            "2 1 undefined",
+        );
+    }
+
+    #[test]
+    pub fn yield_in_class_computed_keys() {
+        assert_completion(
+            r#"
+                function* g() {
+                    class A {
+                        [yield "method"]() { return "m"; }
+                        static [yield "field"] = "f";
+                    }
+                    return new A().run() + A.value;
+                }
+                let it = g();
+                it.next();
+                it.next("run");
+                it.next("value").value;
+            "#,
+            "mf",
+        );
+    }
+
+    #[test]
+    pub fn async_await() {
+        assert_completion(
+            r#"
+                const later = v => new Promise(resolve => resolve(v));
+                async function add(a, b) {
+                    const x = await later(a);
+                    return x + await b;
+                }
+                async function main() {
+                    let s = "";
+                    s += await add(1, 2) + ";";
+                    try { await Promise.reject("no"); } catch (e) { s += "caught " + e + ";"; }
+                    s += await (async () => "arrow")() + ";";
+                    return s;
+                }
+                main();
+            "#,
+            "3;caught no;arrow;",
+        );
+    }
+
+    #[test]
+    pub fn await_in_switch_statements() {
+        assert_completion(
+            r#"
+                let log = "";
+                async function pick(v) {
+                    switch (await v) {
+                        case await Promise.resolve(1): log += "one "; break;
+                        default: log += (await "other") + " ";
+                    }
+                    return log;
+                }
+                pick(1).then(() => pick(2));
+            "#,
+            "one other ",
+        );
+    }
+
+    #[test]
+    pub fn compound_assignment_reads_the_target_before_awaiting() {
+        assert_completion(
+            r#"
+                let x = 1;
+                async function add(p) { x += await p; return x; }
+                const result = add(Promise.resolve(5));
+                x = 100;
+                result;
+            "#,
+            6.0,
+        );
+    }
+
+    #[test]
+    pub fn await_in_destructuring_defaults() {
+        assert_completion(
+            r#"
+                async function f(xs) {
+                    const { a = await 5 } = {};
+                    const [b = await Promise.resolve(6)] = [];
+                    let sum = a + b;
+                    for (const { c = await 1 } of xs) {
+                        sum += c;
+                    }
+                    return sum;
+                }
+                f([{}, { c: 10 }]);
+            "#,
+            22.0,
+        );
+    }
+
+    #[test]
+    pub fn await_in_class_computed_keys() {
+        assert_completion(
+            r#"
+                async function f() {
+                    class A {
+                        static [await Promise.resolve("key")] = "static";
+                        [await "method"]() { return "method"; }
+                    }
+                    return A.key + " " + new A().method();
+                }
+                f();
+            "#,
+            "static method",
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use lib_ir::ast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::module::ModuleLoader;
+
+// A loader written by the host in Javascript: an object with a resolve(specifier, referrer)
+// method returning the key of a module, and a load(key) method returning its serialized syntax
+// tree. Exceptions they throw are reported as errors loading the module.
+pub struct JsLoader(pub JsValue);
+
+impl JsLoader {
+    fn call(&self, method: &str, args: &[&str]) -> Result<String, String> {
+        let function = js_sys::Reflect::get(&self.0, &JsValue::from_str(method))
+            .map_err(describe)?
+            .dyn_into::<js_sys::Function>()
+            .map_err(|_| format!("The module loader has no {} method", method))?;
+        let args: js_sys::Array = args.iter().map(|arg| JsValue::from_str(arg)).collect();
+        function
+            .apply(&self.0, &args)
+            .map_err(describe)?
+            .as_string()
+            .ok_or_else(|| {
+                format!(
+                    "The {} method of the module loader must return a string",
+                    method
+                )
+            })
+    }
+}
+
+impl ModuleLoader for JsLoader {
+    fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, String> {
+        self.call("resolve", &[specifier, referrer])
+    }
+
+    fn load(&self, key: &str) -> Result<ast::Node, String> {
+        let ast = self.call("load", &[key])?;
+        lib_ir::serialize(ast).map_err(|e| e.to_string())
+    }
+}
+
+fn describe(exception: JsValue) -> String {
+    match exception.dyn_ref::<js_sys::Error>() {
+        Some(error) => String::from(error.message()),
+        None => exception
+            .as_string()
+            .unwrap_or_else(|| format!("{:?}", exception)),
+    }
+}
@@ -8,6 +8,7 @@ use crate::evaluator_value::EvaluatorValue;
 pub fn map_rust_value(eval_result: EvaluatorValue) -> JsValue {
     match eval_result {
         EvaluatorValue::Closure(c) => JsValue::from_str(c.to_string().as_str()),
+        EvaluatorValue::NativeFunction(f) => JsValue::from_str(f.to_string().as_str()),
         EvaluatorValue::Literal(l) => match l.value {
             LiteralValue::String(s) => JsValue::from(s),
             LiteralValue::Boolean(b) => match b {
@@ -19,9 +20,15 @@ pub fn map_rust_value(eval_result: EvaluatorValue) -> JsValue {
                 JsNumber::Number(n) => JsValue::from_f64(n),
                 JsNumber::Nan => JsValue::from("NaN"),
             },
-            LiteralValue::RegExp => unreachable!(),
+            regexp @ LiteralValue::RegExp { .. } => {
+                let source: String = regexp.into();
+                JsValue::from(source)
+            }
             LiteralValue::Undefined => JsValue::UNDEFINED,
         },
-        EvaluatorValue::Object(_) => JsValue::from_str(eval_result.to_string().as_str()),
+        EvaluatorValue::Symbol(symbol) => JsValue::from_str(symbol.to_string().as_str()),
+        EvaluatorValue::Object(_) | EvaluatorValue::Array(_) => {
+            JsValue::from_str(eval_result.to_string().as_str())
+        }
     }
 }
@@ -1,13 +1,33 @@
+use std::rc::Rc;
+
+use evaluator_value::EvaluatorValue;
+use event_loop::{run_due_tasks, run_event_loop, Clock};
+use js_loader::JsLoader;
 use js_value::map_rust_value;
 
 use wasm_bindgen::prelude::*;
 
+mod builtins;
 mod closure;
+mod commonjs;
+mod completion;
 mod constants;
+mod declarations;
+mod early_errors;
 mod environment;
 mod evaluator;
 mod evaluator_value;
+mod event_loop;
+mod generator;
+mod js_loader;
 mod js_value;
+mod module;
+mod object;
+mod promise;
+mod realm;
+mod regexp;
+#[cfg(test)]
+mod testing;
 
 #[allow(unused_variables)]
 #[wasm_bindgen]
@@ -20,7 +40,61 @@ pub fn evaluate(ast: String) -> Result<JsValue, JsError> {
     Ok(js_value)
 }
 
+// Runs the file at `path` as a script or a module, the modules it imports are found through
+// `loader`, see JsLoader. With the real-time clock the result is a promise, the timers are run
+// from the host's own timers so that waiting for them does not block it.
+#[wasm_bindgen]
+pub fn evaluate_file(
+    ast: String,
+    path: String,
+    loader: JsValue,
+    clock: Clock,
+) -> Result<JsValue, JsError> {
+    let ast = lib_ir::serialize(ast).map_err(JsError::from)?;
+
+    let host = evaluator::Host {
+        clock,
+        loader: Some(Rc::new(JsLoader(loader))),
+        path,
+    };
+    let value = evaluator::start_eval(ast, host).map_err(|e| JsError::new(&e.as_str()))?;
+    if clock == Clock::RealTime {
+        let promise =
+            js_sys::Promise::new(&mut |resolve, reject| run_timers(value.clone(), resolve, reject));
+        return Ok(promise.into());
+    }
+    run_event_loop().map_err(|e| JsError::new(&e.as_str()))?;
+    let eval_result = evaluator::program_result(value).map_err(|e| JsError::new(&e.as_str()))?;
+
+    let js_value = map_rust_value(eval_result);
+    Ok(js_value)
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: JsValue, delay: f64);
+}
+
+// Runs the timers that are due, then hands control back to the host until the next one is
+fn run_timers(value: EvaluatorValue, resolve: js_sys::Function, reject: js_sys::Function) {
+    let result = match run_due_tasks() {
+        Ok(Some(delay)) => {
+            let next = Closure::once_into_js(move || run_timers(value, resolve, reject));
+            set_timeout(next, delay);
+            return;
+        }
+        Ok(None) => evaluator::program_result(value),
+        Err(e) => Err(e),
+    };
+    let _ = match result {
+        Ok(value) => resolve.call1(&JsValue::UNDEFINED, &map_rust_value(value)),
+        Err(e) => reject.call1(&JsValue::UNDEFINED, &JsError::new(&e.as_str()).into()),
+    };
+}
+
 #[cfg(test)]
+#[allow(clippy::collapsible_match)]
 mod tests {
     use crate::{constants::JS_UNDEFINED, evaluator, evaluator_value::EvaluatorValue};
     use lib_ir::ast::{literal::JsNumber, literal_value::LiteralValue};
@@ -0,0 +1,1048 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use lib_ir::ast::literal::Literal;
+use lib_ir::ast::literal_value::LiteralValue;
+use lib_ir::ast::{
+    self, ExportDefaultDeclaration, Identifier, ImportDeclarationSpecifiers, ImportExpression,
+    Node, NodeKind,
+};
+
+use crate::closure::NativeFunction;
+use crate::completion::Completion;
+use crate::constants::JS_UNDEFINED;
+use crate::declarations::{bound_names, function_declarations, lexically_declared_names};
+use crate::early_errors::{check_declarations, check_labels};
+use crate::environment::{DeclarationKind, EnvironmentError, FunctionFrame, ThisBinding, Variable};
+use crate::evaluator::{
+    create_error_object, eval_expression, eval_sequence, eval_statement_list, evaluate,
+    hoist_function_declarations, hoist_lexical_declarations, hoist_var_declarations,
+    CompletionResult, Env, EvaluatorError, EvaluatorResult,
+};
+use crate::evaluator_value::EvaluatorValue;
+use crate::generator::{may_suspend, start_async_body};
+use crate::object::{InternalSlot, JsObject, Object};
+use crate::promise::{as_promise, new_promise_capability, perform_then};
+use crate::realm::{enqueue_job, intrinsics};
+
+// Finds the modules a program imports or requires. Modules are known by a key, such as their
+// absolute path, that the loader resolves the specifiers written in import declarations and
+// passed to require to.
+// https://tc39.es/ecma262/#sec-HostLoadImportedModule
+pub trait ModuleLoader {
+    // The key of the module that `specifier` refers to when it is imported by `referrer`
+    fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, String>;
+    // The syntax tree of the module with this key
+    fn load(&self, key: &str) -> Result<ast::Node, String>;
+}
+
+pub type ModuleRef = Rc<RefCell<Module>>;
+
+// What an import or indirect export takes from the other module: one of its exports, or its
+// namespace object
+#[derive(Clone)]
+enum ImportName {
+    Name(String),
+    Namespace,
+}
+
+// import { import_name as local_name } from "specifier"
+// https://tc39.es/ecma262/#table-importentry-record-fields
+#[derive(Clone)]
+struct ImportEntry {
+    specifier: String,
+    request: String,
+    import_name: ImportName,
+    local_name: Identifier,
+}
+
+// export { local_name as export_name }
+struct LocalExport {
+    export_name: String,
+    local_name: Identifier,
+}
+
+// export { import_name as export_name } from "specifier", or export * as export_name
+struct IndirectExport {
+    export_name: String,
+    request: String,
+    import_name: ImportName,
+}
+
+// https://tc39.es/ecma262/#sec-cyclic-module-records
+enum ModuleStatus {
+    New,
+    Linking,
+    Linked,
+    Evaluating,
+    // The module awaits, or imports a module that does. The promise settles once its body has
+    // run, and the modules importing it wait for that.
+    EvaluatingAsync(EvaluatorValue),
+    Evaluated,
+    // Importing the module again throws the same exception
+    Errored(EvaluatorValue),
+}
+
+// A module's top level frame is created when it is loaded, so that modules importing it can bind
+// to it before its own bindings exist. Imported names read the exporting module's binding every
+// time, they see the value it has at the time rather than when it was imported.
+// https://tc39.es/ecma262/#sec-source-text-module-records
+pub struct Module {
+    key: String,
+    body: Vec<Node>,
+    status: ModuleStatus,
+    env: Env,
+    // The keys of the modules this one imports from, in the order they are first imported
+    requested: Vec<String>,
+    imports: Vec<ImportEntry>,
+    local_exports: Vec<LocalExport>,
+    indirect_exports: Vec<IndirectExport>,
+    // The keys of the modules named by export * from "specifier"
+    star_exports: Vec<String>,
+    namespace: Option<JsObject>,
+    meta: Option<JsObject>,
+}
+
+impl Module {
+    // Resolves a specifier the module imports from, returning the key of the module it names
+    fn request(&mut self, specifier: &str) -> Result<String, EvaluatorError> {
+        let request = resolve(specifier, &self.key)?;
+        if !self.requested.contains(&request) {
+            self.requested.push(request.clone());
+        }
+        Ok(request)
+    }
+}
+
+// Where an exported name resolves to
+// https://tc39.es/ecma262/#resolvedbinding-record
+enum Resolution {
+    Binding(ModuleRef, Identifier),
+    Namespace(ModuleRef),
+    NotFound,
+    // Two export * declarations provide different bindings for the name
+    Ambiguous,
+}
+
+impl Resolution {
+    fn is_same(&self, other: &Resolution) -> bool {
+        match (self, other) {
+            (Resolution::Binding(a, a_name), Resolution::Binding(b, b_name)) => {
+                Rc::ptr_eq(a, b) && a_name == b_name
+            }
+            (Resolution::Namespace(a), Resolution::Namespace(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+// Runs the program as a module, along with everything it imports
+pub fn evaluate_entry_module(key: String, body: Vec<Node>) -> CompletionResult {
+    let module = create_module(key.clone(), body)?;
+    intrinsics()
+        .module_map
+        .borrow_mut()
+        .insert(key, Rc::clone(&module));
+    link(&module)?;
+    evaluate_module(&module).map(Completion::Normal)
+}
+
+// Records what the module imports and exports, resolving the specifiers it imports from. The
+// module's frame extends the global frame rather than the frame of the code importing it.
+// https://tc39.es/ecma262/#sec-parsemodule
+fn create_module(key: String, body: Vec<Node>) -> Result<ModuleRef, EvaluatorError> {
+    let global = intrinsics().global_env();
+    let env = global.borrow_mut().extend(Rc::clone(&global));
+    // Modules are strict mode code, and their top level this is undefined
+    env.borrow_mut().set_function_frame(FunctionFrame {
+        this: ThisBinding::Initialized(EvaluatorValue::from(JS_UNDEFINED)),
+        strict: true,
+        new_target: None,
+        function: None,
+    });
+    env.borrow_mut().set_script_or_module(key.clone());
+    let mut module = Module {
+        key,
+        body: Vec::new(),
+        status: ModuleStatus::New,
+        env,
+        requested: Vec::new(),
+        imports: Vec::new(),
+        local_exports: Vec::new(),
+        indirect_exports: Vec::new(),
+        star_exports: Vec::new(),
+        namespace: None,
+        meta: None,
+    };
+    for statement in &body {
+        match &statement.kind {
+            NodeKind::ImportDeclaration(d) => {
+                let specifier = string_value(&d.source);
+                let request = module.request(&specifier)?;
+                for import in &d.specifiers {
+                    let (import_name, local_name) = match import {
+                        ImportDeclarationSpecifiers::ImportSpecifier(s) => {
+                            (ImportName::Name(s.imported.name.clone()), s.local.clone())
+                        }
+                        ImportDeclarationSpecifiers::ImportDefaultSpecifier(s) => {
+                            (ImportName::Name(String::from("default")), s.local.clone())
+                        }
+                        ImportDeclarationSpecifiers::ImportNamespaceSpecifier(s) => {
+                            (ImportName::Namespace, s.local.clone())
+                        }
+                    };
+                    module.imports.push(ImportEntry {
+                        specifier: specifier.clone(),
+                        request: request.clone(),
+                        import_name,
+                        local_name,
+                    });
+                }
+            }
+            NodeKind::ExportNamedDeclaration(d) => match (&d.declaration, &d.source) {
+                (Some(declaration), _) => {
+                    for name in declared_names(declaration) {
+                        module.local_exports.push(LocalExport {
+                            export_name: name.name.clone(),
+                            local_name: name,
+                        });
+                    }
+                }
+                (None, None) => {
+                    for s in &d.specifiers {
+                        module.local_exports.push(LocalExport {
+                            export_name: s.exported.name.clone(),
+                            local_name: s.local.clone(),
+                        });
+                    }
+                }
+                (None, Some(source)) => {
+                    let request = module.request(&string_value(source))?;
+                    for s in &d.specifiers {
+                        module.indirect_exports.push(IndirectExport {
+                            export_name: s.exported.name.clone(),
+                            request: request.clone(),
+                            import_name: ImportName::Name(s.local.name.clone()),
+                        });
+                    }
+                }
+            },
+            NodeKind::ExportDefaultDeclaration(d) => {
+                let local_name = match &d.declaration.kind {
+                    NodeKind::FunctionDeclaration(f) => f.id.clone(),
+                    NodeKind::ClassDeclaration(c) => c.id.clone(),
+                    _ => default_binding(),
+                };
+                module.local_exports.push(LocalExport {
+                    export_name: String::from("default"),
+                    local_name,
+                });
+            }
+            NodeKind::ExportAllDeclaration(d) => {
+                let request = module.request(&string_value(&d.source))?;
+                match &d.exported {
+                    Some(exported) => module.indirect_exports.push(IndirectExport {
+                        export_name: exported.name.clone(),
+                        request,
+                        import_name: ImportName::Namespace,
+                    }),
+                    None => module.star_exports.push(request),
+                }
+            }
+            _ => {}
+        }
+    }
+    // Exporting an imported name re-exports whatever the name was imported from
+    let local_exports = std::mem::take(&mut module.local_exports);
+    for export in local_exports {
+        let import = module
+            .imports
+            .iter()
+            .find(|import| import.local_name == export.local_name);
+        match import {
+            Some(import) if !matches!(import.import_name, ImportName::Namespace) => {
+                module.indirect_exports.push(IndirectExport {
+                    export_name: export.export_name,
+                    request: import.request.clone(),
+                    import_name: import.import_name.clone(),
+                })
+            }
+            _ => module.local_exports.push(export),
+        }
+    }
+    module.body = body;
+    Ok(Rc::new(RefCell::new(module)))
+}
+
+// The value of an expression exported by export default is held in a binding named default,
+// which no declaration can have
+fn default_binding() -> Identifier {
+    Identifier {
+        name: String::from("default"),
+    }
+}
+
+fn declared_names(declaration: &Node) -> Vec<Identifier> {
+    match &declaration.kind {
+        NodeKind::VariableDeclaration(d) => d
+            .declarations
+            .iter()
+            .flat_map(|declarator| bound_names(&declarator.id))
+            .collect(),
+        NodeKind::FunctionDeclaration(f) => vec![f.id.clone()],
+        NodeKind::ClassDeclaration(c) => vec![c.id.clone()],
+        _ => Vec::new(),
+    }
+}
+
+// The declaration an export declaration wraps, any other statement is returned as it is
+fn unwrap_export(statement: &Node) -> &Node {
+    match &statement.kind {
+        NodeKind::ExportNamedDeclaration(d) => d.declaration.as_deref().unwrap_or(statement),
+        NodeKind::ExportDefaultDeclaration(d) => match &d.declaration.kind {
+            NodeKind::FunctionDeclaration(_) | NodeKind::ClassDeclaration(_) => &d.declaration,
+            _ => statement,
+        },
+        _ => statement,
+    }
+}
+
+fn string_value(literal: &Literal) -> String {
+    String::from(EvaluatorValue::from(literal.clone()))
+}
+
+pub fn resolve(specifier: &str, referrer: &str) -> Result<String, EvaluatorError> {
+    let loader = intrinsics().module_loader.borrow().clone();
+    let key = match loader {
+        Some(loader) => loader.resolve(specifier, referrer),
+        None => Err(format!(
+            "Cannot find module '{}' imported from {}",
+            specifier, referrer
+        )),
+    };
+    key.map_err(|message| EvaluatorError::Throw(create_error_object("Error", message)))
+}
+
+// The syntax tree of the file with this key, checked for early errors
+pub fn load(key: &str) -> Result<Node, EvaluatorError> {
+    let loader = intrinsics().module_loader.borrow().clone();
+    let tree = match loader {
+        Some(loader) => loader.load(key),
+        None => Err(format!("Cannot find module '{}'", key)),
+    }
+    .map_err(|message| EvaluatorError::Throw(create_error_object("Error", message)))?;
+    check_labels(&tree)?;
+    check_declarations(&tree)?;
+    Ok(tree)
+}
+
+// The module with this key, loading it the first time it is asked for
+fn get_module(key: &str) -> Result<ModuleRef, EvaluatorError> {
+    let intrinsics = intrinsics();
+    if let Some(module) = intrinsics.module_map.borrow().get(key) {
+        return Ok(Rc::clone(module));
+    }
+    let tree = load(key)?;
+    let body = match tree.kind {
+        NodeKind::Program(program) => program.body,
+        NodeKind::BlockStatement(block) => block.body,
+        _ => vec![tree],
+    };
+    let module = create_module(String::from(key), body)?;
+    intrinsics
+        .module_map
+        .borrow_mut()
+        .insert(String::from(key), Rc::clone(&module));
+    Ok(module)
+}
+
+// Links the module and the modules it imports, depth first. A module reached again through a
+// cycle is already linking, its frame exists for imports to bind to.
+// https://tc39.es/ecma262/#sec-moduledeclarationlinking
+fn link(module: &ModuleRef) -> Result<(), EvaluatorError> {
+    if !matches!(module.borrow().status, ModuleStatus::New) {
+        return Ok(());
+    }
+    module.borrow_mut().status = ModuleStatus::Linking;
+    let requested = module.borrow().requested.clone();
+    for request in requested {
+        link(&get_module(&request)?)?;
+    }
+    initialize_environment(module)?;
+    module.borrow_mut().status = ModuleStatus::Linked;
+    Ok(())
+}
+
+// Binds the imported names, then hoists the module's own declarations. Functions are created
+// here, so modules in a cycle can call each other's functions before either has run.
+// https://tc39.es/ecma262/#sec-source-text-module-record-initialize-environment
+fn initialize_environment(module: &ModuleRef) -> Result<(), EvaluatorError> {
+    // The module stays unborrowed while its imports are bound, it may import its own namespace
+    let (env, imports, indirect_exports) = {
+        let module = module.borrow();
+        let indirect_exports: Vec<(String, ImportName)> = module
+            .indirect_exports
+            .iter()
+            .map(|export| (export.request.clone(), export.import_name.clone()))
+            .collect();
+        (
+            Rc::clone(&module.env),
+            module.imports.clone(),
+            indirect_exports,
+        )
+    };
+    for (request, import_name) in indirect_exports {
+        if let ImportName::Name(name) = import_name {
+            let resolution = resolve_export(&get_module(&request)?, &name, &mut Vec::new())?;
+            check_resolution(resolution, &request, &name)?;
+        }
+    }
+    for import in imports {
+        let target = get_module(&import.request)?;
+        let resolution = match &import.import_name {
+            ImportName::Namespace => Resolution::Namespace(target),
+            ImportName::Name(name) => check_resolution(
+                resolve_export(&target, name, &mut Vec::new())?,
+                &import.specifier,
+                name,
+            )?,
+        };
+        match resolution {
+            Resolution::Binding(target, name) => {
+                let target_env = Rc::clone(&target.borrow().env);
+                env.borrow_mut()
+                    .declare_import(import.local_name, target_env, name)
+            }
+            Resolution::Namespace(target) => {
+                let namespace = get_namespace(&target)?;
+                env.borrow_mut()
+                    .define(import.local_name, namespace, "const")
+            }
+            Resolution::NotFound | Resolution::Ambiguous => unreachable!("checked above"),
+        }
+        .map_err(EvaluatorError::EnvironmentError)?;
+    }
+
+    let module = module.borrow();
+    let env = &env;
+    let statements: Vec<&Node> = module.body.iter().map(unwrap_export).collect();
+    hoist_var_declarations(&module.body, env);
+    let mut lexical_names = lexically_declared_names(statements.iter().copied());
+    let exports_expression = module.body.iter().any(|statement| {
+        matches!(&statement.kind, NodeKind::ExportDefaultDeclaration(d)
+            if !matches!(d.declaration.kind, NodeKind::FunctionDeclaration(_) | NodeKind::ClassDeclaration(_)))
+    });
+    if exports_expression {
+        lexical_names.push((default_binding(), DeclarationKind::Let));
+    }
+    hoist_lexical_declarations(lexical_names, env)?;
+    hoist_function_declarations(function_declarations(statements.iter().copied()), env);
+    Ok(())
+}
+
+// Names that cannot be resolved are early errors of the importing module
+fn check_resolution(
+    resolution: Resolution,
+    specifier: &str,
+    name: &str,
+) -> Result<Resolution, EvaluatorError> {
+    match resolution {
+        Resolution::NotFound => Err(EvaluatorError::SyntaxError(format!(
+            "The requested module '{}' does not provide an export named '{}'",
+            specifier, name
+        ))),
+        Resolution::Ambiguous => Err(EvaluatorError::SyntaxError(format!(
+            "The requested module '{}' contains conflicting star exports for name '{}'",
+            specifier, name
+        ))),
+        resolution => Ok(resolution),
+    }
+}
+
+// Follows re-exports to the module that declares the binding. The names already visited stop
+// a cycle of re-exports from recursing forever.
+// https://tc39.es/ecma262/#sec-resolveexport
+fn resolve_export(
+    module: &ModuleRef,
+    name: &str,
+    resolve_set: &mut Vec<(String, String)>,
+) -> Result<Resolution, EvaluatorError> {
+    let key = module.borrow().key.clone();
+    if resolve_set.contains(&(key.clone(), name.to_owned())) {
+        return Ok(Resolution::NotFound);
+    }
+    resolve_set.push((key, name.to_owned()));
+
+    let (local, indirect, star_exports) = {
+        let module = module.borrow();
+        let local = module
+            .local_exports
+            .iter()
+            .find(|export| export.export_name == name)
+            .map(|export| export.local_name.clone());
+        let indirect = module
+            .indirect_exports
+            .iter()
+            .find(|export| export.export_name == name)
+            .map(|export| (export.request.clone(), export.import_name.clone()));
+        (local, indirect, module.star_exports.clone())
+    };
+    if let Some(local_name) = local {
+        return Ok(Resolution::Binding(Rc::clone(module), local_name));
+    }
+    if let Some((request, import_name)) = indirect {
+        let target = get_module(&request)?;
+        return match import_name {
+            ImportName::Namespace => Ok(Resolution::Namespace(target)),
+            ImportName::Name(import_name) => resolve_export(&target, &import_name, resolve_set),
+        };
+    }
+    // export * does not re-export default
+    if name == "default" {
+        return Ok(Resolution::NotFound);
+    }
+    let mut star_resolution = Resolution::NotFound;
+    for request in star_exports {
+        let target = get_module(&request)?;
+        match resolve_export(&target, name, resolve_set)? {
+            Resolution::Ambiguous => return Ok(Resolution::Ambiguous),
+            Resolution::NotFound => {}
+            resolution => match star_resolution {
+                Resolution::NotFound => star_resolution = resolution,
+                _ if star_resolution.is_same(&resolution) => {}
+                _ => return Ok(Resolution::Ambiguous),
+            },
+        }
+    }
+    Ok(star_resolution)
+}
+
+// The names a module exports, including those of the modules it exports * from
+// https://tc39.es/ecma262/#sec-getexportednames
+fn exported_names(
+    module: &ModuleRef,
+    export_star_set: &mut Vec<String>,
+) -> Result<Vec<String>, EvaluatorError> {
+    let module = module.borrow();
+    if export_star_set.contains(&module.key) {
+        return Ok(Vec::new());
+    }
+    export_star_set.push(module.key.clone());
+    let mut names: Vec<String> = module
+        .local_exports
+        .iter()
+        .map(|export| export.export_name.clone())
+        .chain(
+            module
+                .indirect_exports
+                .iter()
+                .map(|export| export.export_name.clone()),
+        )
+        .collect();
+    for request in &module.star_exports {
+        for name in exported_names(&get_module(request)?, export_star_set)? {
+            if name != "default" && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    Ok(names)
+}
+
+// An object with the module's exports as properties, in alphabetical order. Reading a property
+// reads the exported binding, so the object stays up to date with the module.
+// https://tc39.es/ecma262/#sec-module-namespace-objects
+pub fn get_namespace(module: &ModuleRef) -> EvaluatorResult {
+    if let Some(namespace) = &module.borrow().namespace {
+        return Ok(EvaluatorValue::Object(Rc::clone(namespace)));
+    }
+    let namespace = Object::new(None);
+    namespace.borrow_mut().internal_slot = Some(InternalSlot::ModuleNamespace);
+    // Set before the exports are resolved, a module may export its own namespace
+    module.borrow_mut().namespace = Some(Rc::clone(&namespace));
+    let mut names = exported_names(module, &mut Vec::new())?;
+    names.sort();
+    for name in names {
+        match resolve_export(module, &name, &mut Vec::new())? {
+            Resolution::Binding(target, binding) => {
+                let env = Rc::clone(&target.borrow().env);
+                let getter = NativeFunction::new(&name, move |_, _| read_binding(&env, &binding));
+                namespace.borrow_mut().define_accessor(
+                    name,
+                    Some(EvaluatorValue::from(getter)),
+                    None,
+                );
+            }
+            Resolution::Namespace(target) => {
+                let value = get_namespace(&target)?;
+                namespace.borrow_mut().set(name, value);
+            }
+            // Ambiguous names are left out
+            Resolution::NotFound | Resolution::Ambiguous => {}
+        }
+    }
+    Ok(EvaluatorValue::Object(namespace))
+}
+
+fn read_binding(env: &Env, name: &Identifier) -> EvaluatorResult {
+    match env.borrow().lookup(name) {
+        Some(Variable {
+            value: Some(value), ..
+        }) => Ok(value),
+        _ => Err(EvaluatorError::EnvironmentError(
+            EnvironmentError::UninitializedVariable(name.name.clone()),
+        )),
+    }
+}
+
+// Evaluates the modules a module imports before the module itself, each of them once. A module
+// reached again through a cycle while it is still evaluating is skipped, the module importing it
+// runs first and sees the bindings it has not initialized yet as uninitialized.
+// https://tc39.es/ecma262/#sec-moduleevaluation
+fn evaluate_module(module: &ModuleRef) -> Result<Option<EvaluatorValue>, EvaluatorError> {
+    match &module.borrow().status {
+        ModuleStatus::Evaluating | ModuleStatus::EvaluatingAsync(_) | ModuleStatus::Evaluated => {
+            return Ok(None)
+        }
+        ModuleStatus::Errored(exception) => return Err(EvaluatorError::Throw(exception.clone())),
+        _ => {}
+    }
+    module.borrow_mut().status = ModuleStatus::Evaluating;
+    let result = run_module(module);
+    // A module that runs as an async body settles its status itself once the body has run
+    let evaluating = matches!(module.borrow().status, ModuleStatus::Evaluating);
+    if evaluating {
+        module.borrow_mut().status = module_status(&result);
+    }
+    result
+}
+
+fn module_status(result: &Result<Option<EvaluatorValue>, EvaluatorError>) -> ModuleStatus {
+    match result {
+        Ok(_) => ModuleStatus::Evaluated,
+        Err(EvaluatorError::Throw(exception)) => ModuleStatus::Errored(exception.clone()),
+        Err(_) => ModuleStatus::Evaluated,
+    }
+}
+
+// The promise of a module that is still evaluating asynchronously
+fn evaluation_promise(module: &ModuleRef) -> Option<EvaluatorValue> {
+    match &module.borrow().status {
+        ModuleStatus::EvaluatingAsync(promise) => Some(promise.clone()),
+        _ => None,
+    }
+}
+
+// A module that awaits at its top level, or imports one that does, runs as an async body. It
+// evaluates to a promise that settles with its completion value once the body has run.
+// https://tc39.es/ecma262/#sec-async-module-execution-fulfilled
+fn run_module(module: &ModuleRef) -> Result<Option<EvaluatorValue>, EvaluatorError> {
+    let requested = module.borrow().requested.clone();
+    let mut pending = Vec::new();
+    for request in requested {
+        let dependency = get_module(&request)?;
+        evaluate_module(&dependency)?;
+        pending.extend(evaluation_promise(&dependency));
+    }
+    let (body, env) = {
+        let mut module = module.borrow_mut();
+        (std::mem::take(&mut module.body), Rc::clone(&module.env))
+    };
+    if pending.is_empty() && !body.iter().any(may_suspend) {
+        return module_result(eval_sequence(body, env));
+    }
+
+    let capability = new_promise_capability();
+    let promise = capability.promise.clone();
+    module.borrow_mut().status = ModuleStatus::EvaluatingAsync(promise.clone());
+    let module = Rc::clone(module);
+    start_async_body(
+        |co| {
+            Box::pin(async move {
+                let completion = async {
+                    for promise in pending {
+                        co.await_value(promise).await?;
+                    }
+                    eval_statement_list(body, env, Some(co.clone())).await
+                }
+                .await;
+                let result = module_result(completion);
+                module.borrow_mut().status = module_status(&result);
+                // The promise is fulfilled with the value of a return completion
+                result.map(|value| {
+                    Completion::Return(value.unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED)))
+                })
+            })
+        },
+        &capability,
+    )?;
+    Ok(Some(promise))
+}
+
+fn module_result(completion: CompletionResult) -> Result<Option<EvaluatorValue>, EvaluatorError> {
+    match completion {
+        Ok(Completion::Normal(value)) => Ok(value),
+        Ok(_) => Ok(None),
+        // Exceptions are stored as values, so that importing the module again rethrows them
+        Err(e) => Err(EvaluatorError::Throw(e.into_exception()?)),
+    }
+}
+
+// Functions and classes are bound when the module is linked and when the class declaration is
+// evaluated, an expression is evaluated into the default binding
+// https://tc39.es/ecma262/#sec-exports-runtime-semantics-evaluation
+pub fn eval_export_default(
+    ExportDefaultDeclaration { declaration }: ExportDefaultDeclaration,
+    env: Env,
+) -> CompletionResult {
+    match declaration.kind {
+        NodeKind::FunctionDeclaration(_) => Ok(Completion::empty()),
+        NodeKind::ClassDeclaration(_) => evaluate(*declaration, env),
+        _ => {
+            let anonymous = match &declaration.kind {
+                NodeKind::FunctionExpression(f) => f.id.is_none(),
+                NodeKind::ClassExpression(c) => c.id.is_none(),
+                NodeKind::ArrowFunctionExpression(_) => true,
+                _ => false,
+            };
+            let value = match eval_expression(*declaration, Rc::clone(&env))? {
+                EvaluatorValue::Closure(mut closure) if anonymous => {
+                    closure.name = Some(String::from("default"));
+                    EvaluatorValue::Closure(closure)
+                }
+                value => value,
+            };
+            env.borrow_mut()
+                .initialize(default_binding(), value, "let")
+                .map_err(EvaluatorError::EnvironmentError)?;
+            Ok(Completion::empty())
+        }
+    }
+}
+
+// Loads, links and evaluates the module in a job of its own, settling the returned promise with
+// its namespace object or with the exception that stopped it
+// https://tc39.es/ecma262/#sec-import-calls
+pub fn eval_import_call(
+    ImportExpression { source }: ImportExpression,
+    env: Env,
+) -> EvaluatorResult {
+    let referrer = env.borrow().active_script_or_module().unwrap_or_default();
+    let specifier = String::from(eval_expression(*source, env)?);
+    let capability = new_promise_capability();
+    let promise = capability.promise.clone();
+    enqueue_job(move || {
+        let namespace = resolve(&specifier, &referrer).and_then(|key| {
+            let module = get_module(&key)?;
+            link(&module)?;
+            evaluate_module(&module)?;
+            Ok((get_namespace(&module)?, evaluation_promise(&module)))
+        });
+        match namespace {
+            // A module evaluating asynchronously gives its namespace once its body has run
+            Ok((namespace, Some(evaluated))) => {
+                let evaluated = as_promise(&evaluated).expect("modules evaluate to promises");
+                let fulfilled = NativeFunction::new("", move |_, _| Ok(namespace.clone()));
+                perform_then(
+                    &evaluated,
+                    EvaluatorValue::from(fulfilled),
+                    EvaluatorValue::from(JS_UNDEFINED),
+                    Some(capability),
+                );
+                Ok(())
+            }
+            Ok((namespace, None)) => capability.resolve(namespace),
+            Err(EvaluatorError::SyntaxError(message)) => {
+                capability.reject(create_error_object("SyntaxError", message))
+            }
+            Err(e) => capability.reject(e.into_exception()?),
+        }
+    });
+    Ok(promise)
+}
+
+// An object the module can keep information about itself on, its url is the module's key
+// https://tc39.es/ecma262/#sec-meta-properties-runtime-semantics-evaluation
+pub fn import_meta(env: &Env) -> EvaluatorResult {
+    let key = env.borrow().active_script_or_module();
+    let module = key.and_then(|key| intrinsics().module_map.borrow().get(&key).cloned());
+    let Some(module) = module else {
+        return Err(EvaluatorError::SyntaxError(String::from(
+            "Cannot use 'import.meta' outside a module",
+        )));
+    };
+    let mut module = module.borrow_mut();
+    let meta = match &module.meta {
+        Some(meta) => Rc::clone(meta),
+        None => {
+            let meta = Object::new(None);
+            meta.borrow_mut().set(
+                String::from("url"),
+                EvaluatorValue::from(LiteralValue::from(module.key.as_str())),
+            );
+            module.meta = Some(Rc::clone(&meta));
+            meta
+        }
+    };
+    Ok(EvaluatorValue::Object(meta))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_error, assert_value, eval_with_modules};
+
+    #[test]
+    pub fn module_imports_are_live_bindings() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    import { count, increment } from "./counter.js";
+                    const before = count;
+                    increment();
+                    increment();
+                    before + "," + count;
+                "#,
+                &[(
+                    "./counter.js",
+                    r#"
+                        export let count = 0;
+                        export function increment() { count++; }
+                    "#,
+                )],
+            ),
+            "0,2",
+        );
+    }
+
+    #[test]
+    pub fn cyclic_modules_call_hoisted_functions() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    import { fromB } from "./a.js";
+                    fromB;
+                "#,
+                &[
+                    (
+                        "./a.js",
+                        r#"
+                            import { b } from "./b.js";
+                            export function a() { return "a"; }
+                            export const fromB = b();
+                        "#,
+                    ),
+                    (
+                        "./b.js",
+                        r#"
+                            import { a } from "./a.js";
+                            export function b() { return "b" + a(); }
+                        "#,
+                    ),
+                ],
+            ),
+            "ba",
+        );
+    }
+
+    #[test]
+    pub fn dynamic_import_and_import_meta() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    let log = "before,";
+                    async function main() {
+                        const lazy = await import("./lazy.js");
+                        return log + lazy.url + "," + lazy.default;
+                    }
+                    const done = main();
+                    log += "after,";
+                    done;
+                "#,
+                &[(
+                    "./lazy.js",
+                    r#"
+                        export const url = import.meta.url;
+                        export default "lazy";
+                    "#,
+                )],
+            ),
+            "before,after,./lazy.js,lazy",
+        );
+    }
+
+    #[test]
+    pub fn import_of_missing_export() {
+        assert_error(
+            eval_with_modules(
+                r#"
+                    import { count, missing } from "./counter.js";
+                    count;
+                "#,
+                &[(
+                    "./counter.js",
+                    r#"
+                        export let count = 0;
+                        export function increment() { count++; }
+                    "#,
+                )],
+            ),
+            "does not provide an export named 'missing'",
+        );
+    }
+
+    #[test]
+    pub fn namespace_objects_and_re_exports() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    import * as lib from "./index.js";
+                    import area from "./math.js";
+                    lib.math.pi + lib.square(lib.pi) + area(2) + "," + typeof lib.default;
+                "#,
+                &[
+                    (
+                        "./index.js",
+                        r#"
+                            export * from "./math.js";
+                            export { default as square } from "./math.js";
+                            export * as math from "./math.js";
+                        "#,
+                    ),
+                    (
+                        "./math.js",
+                        r#"
+                            export const pi = 3;
+                            export default function square(x) { return x * x; }
+                        "#,
+                    ),
+                ],
+            ),
+            "16,undefined",
+        );
+    }
+
+    #[test]
+    pub fn cyclic_import_before_initialization() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    import "./a.js";
+                    import { seen } from "./b.js";
+                    seen;
+                "#,
+                &[
+                    (
+                        "./a.js",
+                        r#"
+                            import "./b.js";
+                            export const value = 1;
+                        "#,
+                    ),
+                    (
+                        "./b.js",
+                        r#"
+                            import { value } from "./a.js";
+                            export let seen;
+                            try { seen = value; } catch (e) { seen = e.name; }
+                        "#,
+                    ),
+                ],
+            ),
+            "ReferenceError",
+        );
+    }
+
+    #[test]
+    pub fn namespace_objects_reject_writes() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    import * as ns from "./a.js";
+                    let assigned;
+                    try { ns.a = 5; } catch (e) { assigned = e.name + ": " + e.message; }
+                    let added;
+                    try { ns.b = 1; } catch (e) { added = e.message; }
+                    `${assigned}|${added}|${ns.a}`;
+                "#,
+                &[("./a.js", "export let a = 1;")],
+            ),
+            "TypeError: Cannot assign to read only property 'a' of object '[object Module]'|Cannot add property b, object is not extensible|1",
+        );
+    }
+
+    #[test]
+    pub fn top_level_await_delays_the_modules_importing_it() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    import { record, log } from "./log.js";
+                    import { value } from "./slow.js";
+                    import "./fast.js";
+                    record("main " + value);
+                    const later = await Promise.resolve("later");
+                    log + later;
+                "#,
+                &[
+                    (
+                        "./log.js",
+                        r#"
+                            export let log = "";
+                            export function record(entry) { log += entry + ","; }
+                        "#,
+                    ),
+                    (
+                        "./slow.js",
+                        r#"
+                            import { record } from "./log.js";
+                            record("slow start");
+                            export const value = await new Promise(resolve => setTimeout(() => resolve(42), 10));
+                            record("slow end");
+                        "#,
+                    ),
+                    (
+                        "./fast.js",
+                        r#"
+                            import { record } from "./log.js";
+                            record("fast");
+                        "#,
+                    ),
+                ],
+            ),
+            "slow start,fast,slow end,main 42,later",
+        );
+    }
+
+    #[test]
+    pub fn dynamic_import_waits_for_top_level_await() {
+        assert_value(
+            eval_with_modules(
+                r#"
+                    const ns = await import("./slow.js");
+                    ns.value;
+                "#,
+                &[(
+                    "./slow.js",
+                    "export const value = await Promise.resolve(7);",
+                )],
+            ),
+            7.0,
+        );
+    }
+
+    #[test]
+    pub fn rejected_top_level_await_fails_the_importing_module() {
+        assert_error(
+            eval_with_modules(
+                r#"
+                    import "./failing.js";
+                    "unreachable";
+                "#,
+                &[(
+                    "./failing.js",
+                    r#"await Promise.reject(new Error("boom"));"#,
+                )],
+            ),
+            "Uncaught Error: boom",
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use std::hash::{Hash, Hasher};
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use indexmap::IndexMap;
+
+use crate::evaluator_value::EvaluatorValue;
+use crate::generator::Generator;
+use crate::promise::Promise;
+use crate::regexp::RegExp;
+
+pub type JsObject = Rc<RefCell<Object>>;
+
+// https://262.ecma-international.org/5.1/#sec-8.6.1
+#[derive(Clone, Debug)]
+pub enum Property {
+    Data(EvaluatorValue),
+    // Functions called with the object as this when the property is read or written
+    Accessor {
+        get: Option<EvaluatorValue>,
+        set: Option<EvaluatorValue>,
+    },
+}
+
+// Every evaluation of a class creates new private names, two classes with a #x field each have
+// different names even when they come from the same source
+// https://tc39.es/ecma262/#sec-private-names
+#[derive(Clone, Debug)]
+pub struct PrivateName(Rc<str>);
+
+impl PrivateName {
+    pub fn new(description: &str) -> Self {
+        PrivateName(Rc::from(description))
+    }
+}
+
+impl PartialEq for PrivateName {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Display for PrivateName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+// Every symbol is unique, two symbols with the same description are different keys. Symbols are
+// the only property keys that are not strings, so no string can name the property of a symbol.
+// https://tc39.es/ecma262/#sec-ecmascript-language-types-symbol-type
+#[derive(Clone, Debug)]
+pub struct Symbol(Rc<str>);
+
+impl Symbol {
+    pub fn new(description: &str) -> Self {
+        Symbol(Rc::from(description))
+    }
+
+    pub fn description(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.0).hash(state)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Symbol({})", self.0)
+    }
+}
+
+// https://tc39.es/ecma262/#sec-object-type
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PropertyKey {
+    String(String),
+    Symbol(Symbol),
+}
+
+impl From<String> for PropertyKey {
+    fn from(key: String) -> Self {
+        PropertyKey::String(key)
+    }
+}
+
+impl From<&str> for PropertyKey {
+    fn from(key: &str) -> Self {
+        PropertyKey::String(String::from(key))
+    }
+}
+
+impl From<Symbol> for PropertyKey {
+    fn from(symbol: Symbol) -> Self {
+        PropertyKey::Symbol(symbol)
+    }
+}
+
+impl From<&PropertyKey> for PropertyKey {
+    fn from(key: &PropertyKey) -> Self {
+        key.clone()
+    }
+}
+
+impl PartialEq<&str> for PropertyKey {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, PropertyKey::String(key) if key == other)
+    }
+}
+
+impl PropertyKey {
+    // The name of a function defined under this key, symbols give their description in brackets
+    // https://tc39.es/ecma262/#sec-setfunctionname
+    pub fn function_name(&self) -> String {
+        match self {
+            PropertyKey::String(key) => key.to_owned(),
+            PropertyKey::Symbol(symbol) => format!("[{}]", symbol.description()),
+        }
+    }
+}
+
+impl fmt::Display for PropertyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyKey::String(key) => write!(f, "{}", key),
+            PropertyKey::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+// https://tc39.es/ecma262/#sec-privateelement-specification-type
+#[derive(Clone, Debug)]
+pub enum PrivateElement {
+    Field(EvaluatorValue),
+    Method(EvaluatorValue),
+    Accessor {
+        get: Option<EvaluatorValue>,
+        set: Option<EvaluatorValue>,
+    },
+}
+
+// State that built in objects keep out of reach of their properties
+// https://tc39.es/ecma262/#sec-object-internal-methods-and-internal-slots
+pub enum InternalSlot {
+    // Marks the objects created by the Error constructors
+    Error,
+    // Marks module namespace objects, whose properties cannot be written or added
+    ModuleNamespace,
+    Generator(Rc<RefCell<Generator>>),
+    Promise(Rc<RefCell<Promise>>),
+    RegExp(Rc<RegExp>),
+}
+
+// An object's own properties in the order they were created, and the [[Prototype]] that property
+// lookups fall back to
+// https://262.ecma-international.org/5.1/#sec-8.6.2
+#[derive(Default)]
+pub struct Object {
+    pub properties: IndexMap<PropertyKey, Property>,
+    pub prototype: Option<JsObject>,
+    // Private elements are never inherited, they are added by the constructor of the class that
+    // declares them
+    pub private_elements: Vec<(PrivateName, PrivateElement)>,
+    pub internal_slot: Option<InternalSlot>,
+}
+
+impl Object {
+    pub fn new(prototype: Option<JsObject>) -> JsObject {
+        Rc::new(RefCell::new(Object {
+            properties: IndexMap::new(),
+            prototype,
+            private_elements: Vec::new(),
+            internal_slot: None,
+        }))
+    }
+
+    // https://262.ecma-international.org/5.1/#sec-8.12.2
+    pub fn find(&self, key: impl Into<PropertyKey>) -> Option<Property> {
+        let key = key.into();
+        match self.properties.get(&key) {
+            Some(property) => Some(property.clone()),
+            None => self.prototype.as_ref()?.borrow().find(key),
+        }
+    }
+
+    // The value of a data property, own or inherited. Reading an accessor needs the receiver to
+    // call its getter with, so those are left to the evaluator.
+    pub fn get(&self, key: impl Into<PropertyKey>) -> Option<EvaluatorValue> {
+        match self.find(key)? {
+            Property::Data(value) => Some(value),
+            Property::Accessor { .. } => None,
+        }
+    }
+
+    // https://262.ecma-international.org/5.1/#sec-8.12.6
+    pub fn has_property(&self, key: impl Into<PropertyKey>) -> bool {
+        let key = key.into();
+        self.properties.contains_key(&key)
+            || self
+                .prototype
+                .as_ref()
+                .is_some_and(|prototype| prototype.borrow().has_property(key))
+    }
+
+    // Creates or replaces an own data property
+    pub fn set(&mut self, key: impl Into<PropertyKey>, value: EvaluatorValue) {
+        self.properties.insert(key.into(), Property::Data(value));
+    }
+
+    // A getter and setter for the same key are defined separately and end up in one property,
+    // which keeps the place of the first
+    pub fn define_accessor(
+        &mut self,
+        key: impl Into<PropertyKey>,
+        get: Option<EvaluatorValue>,
+        set: Option<EvaluatorValue>,
+    ) {
+        let key = key.into();
+        let (get, set) = match self.properties.get(&key).cloned() {
+            Some(Property::Accessor {
+                get: old_get,
+                set: old_set,
+            }) => (get.or(old_get), set.or(old_set)),
+            _ => (get, set),
+        };
+        self.properties.insert(key, Property::Accessor { get, set });
+    }
+
+    // https://tc39.es/ecma262/#sec-privateelementfind
+    pub fn find_private(&self, name: &PrivateName) -> Option<&PrivateElement> {
+        self.private_elements
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, element)| element)
+    }
+
+    // Returns false when the object already has an element with this name
+    // https://tc39.es/ecma262/#sec-privatefieldadd
+    pub fn add_private(&mut self, name: PrivateName, element: PrivateElement) -> bool {
+        if self.find_private(&name).is_some() {
+            return false;
+        }
+        self.private_elements.push((name, element));
+        true
+    }
+
+    pub fn set_private_field(&mut self, name: &PrivateName, value: EvaluatorValue) {
+        if let Some((_, element)) = self.private_elements.iter_mut().find(|(n, _)| n == name) {
+            *element = PrivateElement::Field(value);
+        }
+    }
+
+    // Whether `prototype` appears anywhere on the prototype chain of this object
+    pub fn inherits_from(&self, prototype: &JsObject) -> bool {
+        match &self.prototype {
+            Some(p) => Rc::ptr_eq(p, prototype) || p.borrow().inherits_from(prototype),
+            None => false,
+        }
+    }
+}
+
+// Objects routinely refer back to themselves through their prototype's constructor, only the
+// own property names are printed
+impl fmt::Debug for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Object")
+            .field("properties", &self.properties.keys())
+            .finish()
+    }
+}
@@ -0,0 +1,260 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::closure::NativeFunction;
+use crate::constants::JS_UNDEFINED;
+use crate::evaluator::{call_value, create_error_object, get_member, EvaluatorError};
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::{InternalSlot, JsObject, Object};
+use crate::realm::{enqueue_job, intrinsics};
+
+// https://tc39.es/ecma262/#sec-properties-of-promise-instances
+#[derive(Clone)]
+pub enum PromiseState {
+    Pending,
+    Fulfilled(EvaluatorValue),
+    Rejected(EvaluatorValue),
+}
+
+pub struct Promise {
+    state: PromiseState,
+    fulfill_reactions: Vec<Reaction>,
+    reject_reactions: Vec<Reaction>,
+}
+
+// What to do once a promise settles: call the handler with its value, and settle the derived
+// promise with the result. A missing handler passes the value on as it is.
+// https://tc39.es/ecma262/#sec-promisereaction-records
+#[derive(Clone)]
+struct Reaction {
+    capability: Option<Capability>,
+    handler: Option<EvaluatorValue>,
+}
+
+// A promise along with the functions that settle it
+// https://tc39.es/ecma262/#sec-promisecapability-records
+#[derive(Clone)]
+pub struct Capability {
+    pub promise: EvaluatorValue,
+    pub resolve: EvaluatorValue,
+    pub reject: EvaluatorValue,
+}
+
+impl Capability {
+    pub fn resolve(&self, value: EvaluatorValue) -> Result<(), EvaluatorError> {
+        call_value(
+            self.resolve.clone(),
+            EvaluatorValue::from(JS_UNDEFINED),
+            vec![value],
+        )
+        .map(drop)
+    }
+
+    pub fn reject(&self, reason: EvaluatorValue) -> Result<(), EvaluatorError> {
+        call_value(
+            self.reject.clone(),
+            EvaluatorValue::from(JS_UNDEFINED),
+            vec![reason],
+        )
+        .map(drop)
+    }
+}
+
+// Turns a pending object created by the Promise constructor into a promise
+pub fn initialize_promise(object: &JsObject) {
+    object.borrow_mut().internal_slot =
+        Some(InternalSlot::Promise(Rc::new(RefCell::new(Promise {
+            state: PromiseState::Pending,
+            fulfill_reactions: Vec::new(),
+            reject_reactions: Vec::new(),
+        }))));
+}
+
+// Only the built in Promise constructor is supported, so capabilities are created directly
+// https://tc39.es/ecma262/#sec-newpromisecapability
+pub fn new_promise_capability() -> Capability {
+    let promise = Object::new(Some(Rc::clone(&intrinsics().promise_prototype)));
+    initialize_promise(&promise);
+    let (resolve, reject) = create_resolving_functions(&promise);
+    Capability {
+        promise: EvaluatorValue::Object(promise),
+        resolve,
+        reject,
+    }
+}
+
+pub fn as_promise(value: &EvaluatorValue) -> Option<Rc<RefCell<Promise>>> {
+    match value {
+        EvaluatorValue::Object(obj) => promise_slot(obj),
+        _ => None,
+    }
+}
+
+fn promise_slot(obj: &JsObject) -> Option<Rc<RefCell<Promise>>> {
+    match &obj.borrow().internal_slot {
+        Some(InternalSlot::Promise(promise)) => Some(Rc::clone(promise)),
+        _ => None,
+    }
+}
+
+pub fn promise_state(value: &EvaluatorValue) -> Option<PromiseState> {
+    as_promise(value).map(|promise| promise.borrow().state.clone())
+}
+
+// Only the first call to either function has an effect
+// https://tc39.es/ecma262/#sec-createresolvingfunctions
+pub fn create_resolving_functions(promise: &JsObject) -> (EvaluatorValue, EvaluatorValue) {
+    let already_resolved = Rc::new(Cell::new(false));
+    let resolve = {
+        let promise = Rc::clone(promise);
+        let already_resolved = Rc::clone(&already_resolved);
+        NativeFunction::new("", move |_, args| {
+            if !already_resolved.replace(true) {
+                resolve_promise(&promise, first_argument(args))?;
+            }
+            Ok(EvaluatorValue::from(JS_UNDEFINED))
+        })
+    };
+    let reject = {
+        let promise = Rc::clone(promise);
+        NativeFunction::new("", move |_, args| {
+            if !already_resolved.replace(true) {
+                settle(&promise, PromiseState::Rejected(first_argument(args)));
+            }
+            Ok(EvaluatorValue::from(JS_UNDEFINED))
+        })
+    };
+    (EvaluatorValue::from(resolve), EvaluatorValue::from(reject))
+}
+
+fn first_argument(args: Vec<EvaluatorValue>) -> EvaluatorValue {
+    args.into_iter()
+        .next()
+        .unwrap_or_else(|| EvaluatorValue::from(JS_UNDEFINED))
+}
+
+// Resolving with a thenable follows it, in a job of its own so that its then method is never
+// called while the code that resolved is still running
+// https://tc39.es/ecma262/#sec-promise-resolve-functions
+fn resolve_promise(promise: &JsObject, resolution: EvaluatorValue) -> Result<(), EvaluatorError> {
+    if let EvaluatorValue::Object(obj) = &resolution {
+        if Rc::ptr_eq(obj, promise) {
+            let error = create_error_object(
+                "TypeError",
+                String::from("Chaining cycle detected for promise #<Promise>"),
+            );
+            settle(promise, PromiseState::Rejected(error));
+            return Ok(());
+        }
+    }
+    if let EvaluatorValue::Literal(_) = resolution {
+        settle(promise, PromiseState::Fulfilled(resolution));
+        return Ok(());
+    }
+    let then = match get_member(resolution.clone(), String::from("then")) {
+        Ok(then) => then,
+        Err(e) => {
+            settle(promise, PromiseState::Rejected(e.into_exception()?));
+            return Ok(());
+        }
+    };
+    if !then.is_callable() {
+        settle(promise, PromiseState::Fulfilled(resolution));
+        return Ok(());
+    }
+    // https://tc39.es/ecma262/#sec-promiseresolvethenablejob
+    let promise = Rc::clone(promise);
+    enqueue_job(move || {
+        let (resolve, reject) = create_resolving_functions(&promise);
+        if let Err(e) = call_value(then, resolution, vec![resolve, reject.clone()]) {
+            call_value(
+                reject,
+                EvaluatorValue::from(JS_UNDEFINED),
+                vec![e.into_exception()?],
+            )?;
+        }
+        Ok(())
+    });
+    Ok(())
+}
+
+// https://tc39.es/ecma262/#sec-fulfillpromise
+// https://tc39.es/ecma262/#sec-rejectpromise
+fn settle(promise: &JsObject, state: PromiseState) {
+    let Some(promise) = promise_slot(promise) else {
+        return;
+    };
+    let mut promise = promise.borrow_mut();
+    if !matches!(promise.state, PromiseState::Pending) {
+        return;
+    }
+    let fulfill_reactions = std::mem::take(&mut promise.fulfill_reactions);
+    let reject_reactions = std::mem::take(&mut promise.reject_reactions);
+    let reactions = match &state {
+        PromiseState::Fulfilled(_) => fulfill_reactions,
+        _ => reject_reactions,
+    };
+    promise.state = state.clone();
+    for reaction in reactions {
+        enqueue_reaction_job(reaction, state.clone());
+    }
+}
+
+// https://tc39.es/ecma262/#sec-newpromisereactionjob
+fn enqueue_reaction_job(reaction: Reaction, state: PromiseState) {
+    enqueue_job(move || {
+        let result = match (reaction.handler, state) {
+            (Some(handler), PromiseState::Fulfilled(value) | PromiseState::Rejected(value)) => {
+                call_value(handler, EvaluatorValue::from(JS_UNDEFINED), vec![value])
+            }
+            (None, PromiseState::Fulfilled(value)) => Ok(value),
+            (None, PromiseState::Rejected(reason)) => Err(EvaluatorError::Throw(reason)),
+            (_, PromiseState::Pending) => unreachable!("reactions run once a promise settles"),
+        };
+        match (reaction.capability, result) {
+            (Some(capability), Ok(value)) => capability.resolve(value),
+            (Some(capability), Err(e)) => capability.reject(e.into_exception()?),
+            (None, result) => result.map(drop),
+        }
+    });
+}
+
+// Runs the handlers once the promise settles, right away if it already has. Handlers that are not
+// functions pass the value on to the derived promise.
+// https://tc39.es/ecma262/#sec-performpromisethen
+pub fn perform_then(
+    promise: &Rc<RefCell<Promise>>,
+    on_fulfilled: EvaluatorValue,
+    on_rejected: EvaluatorValue,
+    capability: Option<Capability>,
+) {
+    let callable = |handler: EvaluatorValue| Some(handler).filter(|h| h.is_callable());
+    let fulfill_reaction = Reaction {
+        capability: capability.clone(),
+        handler: callable(on_fulfilled),
+    };
+    let reject_reaction = Reaction {
+        capability,
+        handler: callable(on_rejected),
+    };
+    let mut promise = promise.borrow_mut();
+    match promise.state.clone() {
+        PromiseState::Pending => {
+            promise.fulfill_reactions.push(fulfill_reaction);
+            promise.reject_reactions.push(reject_reaction);
+        }
+        state @ PromiseState::Fulfilled(_) => enqueue_reaction_job(fulfill_reaction, state),
+        state @ PromiseState::Rejected(_) => enqueue_reaction_job(reject_reaction, state),
+    }
+}
+
+// Promises are returned as they are, anything else is wrapped in a promise resolved with it
+// https://tc39.es/ecma262/#sec-promise-resolve
+pub fn promise_resolve(value: EvaluatorValue) -> Result<EvaluatorValue, EvaluatorError> {
+    if as_promise(&value).is_some() {
+        return Ok(value);
+    }
+    let capability = new_promise_capability();
+    capability.resolve(value)?;
+    Ok(capability.promise)
+}
@@ -0,0 +1,146 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
+
+use crate::evaluator::{Env, EvaluatorError};
+use crate::evaluator_value::JsArray;
+use crate::event_loop::Timers;
+use crate::module::{ModuleLoader, ModuleRef};
+use crate::object::{JsObject, Object, Symbol};
+
+// The objects every value created during an evaluation inherits from. They are shared by all of
+// the code in one evaluation, and created again for the next.
+// https://tc39.es/ecma262/#sec-well-known-intrinsic-objects
+pub struct Intrinsics {
+    pub object_prototype: JsObject,
+    pub function_prototype: JsObject,
+    pub array_prototype: JsObject,
+    pub string_prototype: JsObject,
+    // Inherited by the iterators of built in collections, iterating one returns itself
+    // https://tc39.es/ecma262/#sec-%iteratorprototype%-object
+    pub iterator_prototype: JsObject,
+    // Inherited through the prototype property of generator functions by the generators they
+    // return
+    // https://tc39.es/ecma262/#sec-properties-of-generator-prototype
+    pub generator_prototype: JsObject,
+    pub promise_prototype: JsObject,
+    pub regexp_prototype: JsObject,
+    // The prototypes of Error and of the native errors by name, the native error prototypes
+    // inherit from the Error prototype
+    // https://tc39.es/ecma262/#sec-properties-of-the-nativeerror-prototype-objects
+    pub error_prototypes: HashMap<&'static str, JsObject>,
+    // The key iterable objects keep their iterator method under, reachable as Symbol.iterator
+    // https://tc39.es/ecma262/#sec-well-known-symbols
+    pub symbol_iterator: Symbol,
+    // The strings arrays of the tagged templates evaluated so far, by template site
+    // https://tc39.es/ecma262/#sec-gettemplateobject
+    pub template_map: RefCell<HashMap<usize, JsArray>>,
+    // Promise reactions waiting to run, in the order they were queued
+    // https://tc39.es/ecma262/#sec-jobs
+    pub job_queue: RefCell<VecDeque<Job>>,
+    // Callbacks scheduled by setTimeout and setInterval, run by the event loop once the jobs are
+    // done
+    pub timers: RefCell<Timers>,
+    // Finds the modules imported by the program, programs run without one cannot import
+    pub module_loader: RefCell<Option<Rc<dyn ModuleLoader>>>,
+    // Every module loaded so far by its key, a module is only loaded and evaluated once
+    // https://tc39.es/ecma262/#sec-HostLoadImportedModule
+    pub module_map: RefCell<HashMap<String, ModuleRef>>,
+    // The module object of every CommonJS file required so far by its key, it is added before the
+    // file runs so that a cycle finds its partial exports
+    // https://nodejs.org/api/modules.html#caching
+    pub require_cache: RefCell<HashMap<String, JsObject>>,
+    // The outermost frame of the program, which the top level frames of modules extend
+    // https://tc39.es/ecma262/#sec-code-realms
+    pub global_env: RefCell<Option<Env>>,
+}
+
+pub const ERROR_NAMES: [&str; 6] = [
+    "Error",
+    "AggregateError",
+    "RangeError",
+    "ReferenceError",
+    "SyntaxError",
+    "TypeError",
+];
+
+pub type Job = Box<dyn FnOnce() -> Result<(), EvaluatorError>>;
+
+impl Intrinsics {
+    pub fn global_env(&self) -> Env {
+        let env = self.global_env.borrow();
+        Rc::clone(
+            env.as_ref()
+                .expect("the global frame is created with the realm"),
+        )
+    }
+
+    fn new() -> Self {
+        let object_prototype = Object::new(None);
+        let iterator_prototype = Object::new(Some(Rc::clone(&object_prototype)));
+        let error_prototype = Object::new(Some(Rc::clone(&object_prototype)));
+        let error_prototypes = ERROR_NAMES
+            .iter()
+            .map(|&name| match name {
+                "Error" => (name, Rc::clone(&error_prototype)),
+                _ => (name, Object::new(Some(Rc::clone(&error_prototype)))),
+            })
+            .collect();
+        Intrinsics {
+            function_prototype: Object::new(Some(Rc::clone(&object_prototype))),
+            array_prototype: Object::new(Some(Rc::clone(&object_prototype))),
+            string_prototype: Object::new(Some(Rc::clone(&object_prototype))),
+            generator_prototype: Object::new(Some(Rc::clone(&iterator_prototype))),
+            iterator_prototype,
+            promise_prototype: Object::new(Some(Rc::clone(&object_prototype))),
+            regexp_prototype: Object::new(Some(Rc::clone(&object_prototype))),
+            error_prototypes,
+            symbol_iterator: Symbol::new("Symbol.iterator"),
+            template_map: RefCell::new(HashMap::new()),
+            job_queue: RefCell::new(VecDeque::new()),
+            timers: RefCell::new(Timers::default()),
+            module_loader: RefCell::new(None),
+            module_map: RefCell::new(HashMap::new()),
+            require_cache: RefCell::new(HashMap::new()),
+            global_env: RefCell::new(None),
+            object_prototype,
+        }
+    }
+}
+
+thread_local! {
+    static INTRINSICS: RefCell<Option<Rc<Intrinsics>>> = const { RefCell::new(None) };
+}
+
+// Starts a new realm, so that nothing set on the intrinsics by one program is seen by the next
+pub fn create_realm() -> Rc<Intrinsics> {
+    let intrinsics = Rc::new(Intrinsics::new());
+    INTRINSICS.with(|realm| *realm.borrow_mut() = Some(Rc::clone(&intrinsics)));
+    intrinsics
+}
+
+pub fn intrinsics() -> Rc<Intrinsics> {
+    match INTRINSICS.with(|realm| realm.borrow().clone()) {
+        Some(intrinsics) => intrinsics,
+        None => create_realm(),
+    }
+}
+
+// https://tc39.es/ecma262/#sec-hostenqueuepromisejob
+pub fn enqueue_job(job: impl FnOnce() -> Result<(), EvaluatorError> + 'static) {
+    intrinsics().job_queue.borrow_mut().push_back(Box::new(job));
+}
+
+// Runs queued jobs, and the jobs they queue in turn, until there are none left. Only errors that
+// cannot be caught by the program stop it early.
+pub fn run_jobs() -> Result<(), EvaluatorError> {
+    loop {
+        let job = intrinsics().job_queue.borrow_mut().pop_front();
+        match job {
+            Some(job) => job()?,
+            None => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::rc::Rc;
+
+use lib_ir::ast::literal::JsNumber;
+use lib_ir::ast::literal_value::LiteralValue;
+use regress::{Flags, Match, Range, Regex};
+
+use crate::constants::{JS_NULL, JS_UNDEFINED};
+use crate::evaluator::{create_error_object, get_member, EvaluatorError, EvaluatorResult};
+use crate::evaluator_value::EvaluatorValue;
+use crate::object::{InternalSlot, JsObject, Object, PropertyKey};
+use crate::realm::intrinsics;
+
+// The flags in the order the flags property lists them
+// https://tc39.es/ecma262/#sec-get-regexp.prototype.flags
+const FLAGS: &str = "dgimsuvy";
+
+// The pattern and flags a RegExp object was created with, and the matcher compiled from them.
+// Strings are indexed by character, so lastIndex and the index of a match count characters
+// rather than the bytes the matcher works with.
+// https://tc39.es/ecma262/#sec-properties-of-regexp-instances
+pub struct RegExp {
+    pub source: String,
+    pub flags: String,
+    matcher: Regex,
+}
+
+impl RegExp {
+    pub fn has_flag(&self, flag: char) -> bool {
+        self.flags.contains(flag)
+    }
+
+    // lastIndex is only read and updated by global and sticky regular expressions
+    fn uses_last_index(&self) -> bool {
+        self.has_flag('g') || self.has_flag('y')
+    }
+
+    // The first match that starts at or after the character `start`
+    pub fn find_from(&self, s: &str, start: usize) -> Option<Match> {
+        self.matcher.find_from(s, byte_offset(s, start)).next()
+    }
+
+    // A sticky regular expression only matches right at `start`
+    pub fn find_sticky(&self, s: &str, start: usize) -> Option<Match> {
+        let found = self.find_from(s, start)?;
+        match !self.has_flag('y') || found.start() == byte_offset(s, start) {
+            true => Some(found),
+            false => None,
+        }
+    }
+}
+
+// https://tc39.es/ecma262/#sec-regexpcreate
+pub fn regexp_create(pattern: &str, flags: &str) -> EvaluatorResult {
+    let object = Object::new(Some(Rc::clone(&intrinsics().regexp_prototype)));
+    initialize_regexp(&object, pattern, flags)?;
+    Ok(EvaluatorValue::Object(object))
+}
+
+// An invalid pattern or flags throws a SyntaxError, which the program can catch
+// https://tc39.es/ecma262/#sec-regexpinitialize
+pub fn initialize_regexp(
+    object: &JsObject,
+    pattern: &str,
+    flags: &str,
+) -> Result<(), EvaluatorError> {
+    let invalid_flags = flags.chars().enumerate().any(|(i, flag)| {
+        !FLAGS.contains(flag) || flags.chars().skip(i + 1).any(|other| other == flag)
+    });
+    if invalid_flags || (flags.contains('u') && flags.contains('v')) {
+        return Err(EvaluatorError::Throw(create_error_object(
+            "SyntaxError",
+            format!("Invalid flags supplied to RegExp constructor '{}'", flags),
+        )));
+    }
+    let matcher = Regex::with_flags(pattern, Flags::from(flags)).map_err(|e| {
+        EvaluatorError::Throw(create_error_object(
+            "SyntaxError",
+            format!("Invalid regular expression: /{}/{}: {}", pattern, flags, e),
+        ))
+    })?;
+    let regexp = RegExp {
+        source: escape_pattern(pattern),
+        flags: FLAGS.chars().filter(|flag| flags.contains(*flag)).collect(),
+        matcher,
+    };
+    let mut object = object.borrow_mut();
+    object.internal_slot = Some(InternalSlot::RegExp(Rc::new(regexp)));
+    object.set(
+        String::from("lastIndex"),
+        EvaluatorValue::from(LiteralValue::from(0.0)),
+    );
+    Ok(())
+}
+
+// The source is written so that /source/ reads back as the same pattern, an empty pattern is an
+// empty group since // would start a comment
+// https://tc39.es/ecma262/#sec-escaperegexppattern
+fn escape_pattern(pattern: &str) -> String {
+    if pattern.is_empty() {
+        return String::from("(?:)");
+    }
+    let mut source = String::new();
+    let (mut escaped, mut in_class) = (false, false);
+    for c in pattern.chars() {
+        match c {
+            '/' if !escaped && !in_class => source.push_str("\\/"),
+            '\n' => source.push_str(if escaped { "n" } else { "\\n" }),
+            '\r' => source.push_str(if escaped { "r" } else { "\\r" }),
+            _ => source.push(c),
+        }
+        match c {
+            '[' if !escaped => in_class = true,
+            ']' if !escaped => in_class = false,
+            _ => {}
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    source
+}
+
+pub fn as_regexp(value: &EvaluatorValue) -> Option<(JsObject, Rc<RegExp>)> {
+    let EvaluatorValue::Object(object) = value else {
+        return None;
+    };
+    let regexp = match &object.borrow().internal_slot {
+        Some(InternalSlot::RegExp(regexp)) => Rc::clone(regexp),
+        _ => return None,
+    };
+    Some((Rc::clone(object), regexp))
+}
+
+// Matches the string from lastIndex when the regular expression is global or sticky, and from
+// the start otherwise, moving lastIndex past the match or back to 0 when there is none
+// https://tc39.es/ecma262/#sec-regexpbuiltinexec
+pub fn regexp_builtin_exec(
+    object: &JsObject,
+    regexp: &RegExp,
+    s: &str,
+) -> Result<Option<Match>, EvaluatorError> {
+    let start = match regexp.uses_last_index() {
+        true => last_index(object)?,
+        false => 0,
+    };
+    let found = match start <= s.chars().count() {
+        true => regexp.find_sticky(s, start),
+        false => None,
+    };
+    if regexp.uses_last_index() {
+        let last_index = found.as_ref().map_or(0, |found| char_index(s, found.end()));
+        set_last_index(object, last_index);
+    }
+    Ok(found)
+}
+
+// The match array of exec, holding the matched string and the captures, along with the index
+// the match starts at, the input and the named groups
+// https://tc39.es/ecma262/#sec-regexpbuiltinexec
+pub fn regexp_exec(object: &JsObject, regexp: &RegExp, s: &str) -> EvaluatorResult {
+    match regexp_builtin_exec(object, regexp, s)? {
+        Some(found) => Ok(match_array(&found, regexp, s)),
+        None => Ok(EvaluatorValue::from(JS_NULL)),
+    }
+}
+
+pub fn match_array(found: &Match, regexp: &RegExp, s: &str) -> EvaluatorValue {
+    let strings = found
+        .groups()
+        .map(|group| Some(capture_value(group, s)))
+        .collect::<Vec<_>>();
+    let result = EvaluatorValue::from(strings);
+    let EvaluatorValue::Array(array) = &result else {
+        unreachable!()
+    };
+    let mut array = array.borrow_mut();
+    array.properties.insert(
+        PropertyKey::from("index"),
+        index_value(char_index(s, found.start())),
+    );
+    array.properties.insert(
+        PropertyKey::from("input"),
+        EvaluatorValue::from(LiteralValue::from(s)),
+    );
+    array
+        .properties
+        .insert(PropertyKey::from("groups"), named_groups(found, s));
+    // The d flag adds the start and end index of the match and of every capture
+    if regexp.has_flag('d') {
+        let indices = found
+            .groups()
+            .map(|group| match group {
+                Some(range) => EvaluatorValue::from(vec![
+                    Some(index_value(char_index(s, range.start))),
+                    Some(index_value(char_index(s, range.end))),
+                ]),
+                None => EvaluatorValue::from(JS_UNDEFINED),
+            })
+            .map(Some)
+            .collect::<Vec<_>>();
+        array
+            .properties
+            .insert(PropertyKey::from("indices"), EvaluatorValue::from(indices));
+    }
+    drop(array);
+    result
+}
+
+// An object holding the named captures of the match, undefined when the pattern names none
+pub fn named_groups(found: &Match, s: &str) -> EvaluatorValue {
+    let mut names = found.named_groups().peekable();
+    if names.peek().is_none() {
+        return EvaluatorValue::from(JS_UNDEFINED);
+    }
+    let groups = Object::new(None);
+    for (name, range) in names {
+        groups
+            .borrow_mut()
+            .set(String::from(name), capture_value(range, s));
+    }
+    EvaluatorValue::Object(groups)
+}
+
+// https://tc39.es/ecma262/#sec-tolength
+pub fn last_index(object: &JsObject) -> Result<usize, EvaluatorError> {
+    let value = get_member(
+        EvaluatorValue::Object(Rc::clone(object)),
+        String::from("lastIndex"),
+    )?;
+    Ok(match value {
+        EvaluatorValue::Literal(literal) => match literal.value.into() {
+            JsNumber::Number(n) if n > 0.0 => n.min(u32::MAX as f64) as usize,
+            _ => 0,
+        },
+        _ => 0,
+    })
+}
+
+pub fn set_last_index(object: &JsObject, index: usize) {
+    object.borrow_mut().set(
+        String::from("lastIndex"),
+        EvaluatorValue::from(LiteralValue::from(index as f64)),
+    );
+}
+
+// The character index of a byte offset into the string
+pub fn char_index(s: &str, offset: usize) -> usize {
+    s[..offset].chars().count()
+}
+
+// The byte offset of a character index into the string, the length past its end
+pub fn byte_offset(s: &str, index: usize) -> usize {
+    s.char_indices()
+        .nth(index)
+        .map_or(s.len(), |(offset, _)| offset)
+}
+
+// The string a group captured, undefined when the group did not take part in the match
+pub fn capture_value(range: Option<Range>, s: &str) -> EvaluatorValue {
+    match range {
+        Some(range) => EvaluatorValue::from(LiteralValue::from(&s[range])),
+        None => EvaluatorValue::from(JS_UNDEFINED),
+    }
+}
+
+fn index_value(index: usize) -> EvaluatorValue {
+    EvaluatorValue::from(LiteralValue::from(index as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::assert_completion;
+
+    #[test]
+    pub fn regexp_exec_with_named_groups_and_last_index() {
+        assert_completion(
+            r#"
+                const re = /(\d+)-(?<word>[a-z]+)/g;
+                let out = "";
+                let m;
+                while ((m = re.exec("12-ab 7-cd")) !== null) {
+                    out += m[0] + ":" + m[1] + ":" + m.groups.word + ":" + m.index + ":" + re.lastIndex + "|";
+                }
+                out + re.lastIndex + "|" + re.test("xx 1-z") + "|" + re + "|" + re.flags;
+            "#,
+            "12-ab:12:ab:0:5|7-cd:7:cd:6:10|0|true|/(\\d+)-(?<word>[a-z]+)/g|g",
+        );
+    }
+
+    #[test]
+    pub fn regexp_lookaround_backreferences_and_flags() {
+        assert_completion(
+            r#"
+                const y = /foo/y;
+                y.lastIndex = 3;
+                const sticky = y.test("barfoo") + "," + y.lastIndex + "," + y.test("barfoo") + "," + y.lastIndex;
+                const back = /(\w)\1/.exec("abccd")[0];
+                const look = "$10 €20 $30".match(/(?<=\$)\d+/g);
+                const ahead = "price: 100px 20em".match(/\d+(?=px)/)[0];
+                const dot = /a.b/s.test("a\nb") + "," + /a.b/.test("a\nb");
+                const unicode = /^.$/u.test("😀");
+                const copy = new RegExp(/ab+c/i, "g");
+                sticky + "|" + back + "|" + look + "|" + ahead + "|" + dot + "|" + unicode + "|" + copy + "|" + new RegExp("a/b").source;
+            "#,
+            "true,6,false,0|cc|10,30|100|true,false|true|/ab+c/g|a\\/b",
+        );
+    }
+
+    #[test]
+    pub fn invalid_regexp_throws_syntax_error() {
+        assert_completion(
+            r#"
+                let name;
+                try { new RegExp("("); } catch (e) { name = e.name; }
+                let message;
+                try { RegExp("a", "gg"); } catch (e) { message = e.message; }
+                name + "|" + message;
+            "#,
+            "SyntaxError|Invalid flags supplied to RegExp constructor 'gg'",
+        );
+    }
+}
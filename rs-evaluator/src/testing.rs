@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use lib_ir::ast::{literal_value::LiteralValue, Node};
+use oxc_allocator::Allocator;
+use oxc_parser::{ParseOptions, Parser};
+use oxc_span::SourceType;
+use serde_json::{json, Value};
+
+use crate::evaluator::{self, EvaluatorResult, Host};
+use crate::evaluator_value::EvaluatorValue;
+use crate::module::ModuleLoader;
+
+// Parses the source the way the driver does: as a script that may return at the top level, or as
+// a module when it only parses as one
+pub fn parse(source: &str) -> Node {
+    let tree = parse_as(source, SourceType::cjs().with_module(false))
+        .or_else(|| parse_as(source, SourceType::mjs()))
+        .unwrap_or_else(|| panic!("Unable to parse {}", source));
+    lib_ir::serialize(tree.to_string()).expect("Unable to deserialize ast")
+}
+
+fn parse_as(source: &str, source_type: SourceType) -> Option<Value> {
+    let allocator = Allocator::default();
+    let options = ParseOptions {
+        allow_return_outside_function: !source_type.is_module(),
+        preserve_parens: false,
+        ..Default::default()
+    };
+    let parsed = Parser::new(&allocator, source, source_type)
+        .with_options(options)
+        .parse();
+    // oxc accepts import and export in scripts where acorn does not
+    if !parsed.errors.is_empty()
+        || (!source_type.is_module() && parsed.module_record.has_module_syntax)
+    {
+        return None;
+    }
+    let mut tree: Value = serde_json::from_str(&parsed.program.to_estree_js_json(false)).ok()?;
+    add_locations(&mut tree, source);
+    Some(tree)
+}
+
+// Runtime errors report the line and column they happened at, which acorn adds as loc
+fn add_locations(tree: &mut Value, source: &str) {
+    match tree {
+        Value::Object(node) => {
+            if let (Some(start), Some(end)) = (
+                node.get("start").and_then(Value::as_u64),
+                node.get("end").and_then(Value::as_u64),
+            ) {
+                let loc = json!({
+                    "start": position(source, start as usize),
+                    "end": position(source, end as usize),
+                });
+                node.insert(String::from("loc"), loc);
+            }
+            node.values_mut()
+                .for_each(|child| add_locations(child, source));
+        }
+        Value::Array(children) => children
+            .iter_mut()
+            .for_each(|child| add_locations(child, source)),
+        _ => {}
+    }
+}
+
+fn position(source: &str, offset: usize) -> Value {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |s| s.chars().count());
+    json!({ "line": line, "column": column })
+}
+
+pub fn eval(source: &str) -> EvaluatorResult {
+    evaluator::begin_eval(parse(source))
+}
+
+// Runs the source as ./main.js with a loader that finds the given modules by their keys, the
+// specifiers they are imported or required with
+pub fn eval_with_modules(
+    source: &str,
+    modules: &[(&'static str, &'static str)],
+) -> EvaluatorResult {
+    let host = Host {
+        loader: Some(Rc::new(TestLoader(modules.iter().copied().collect()))),
+        path: String::from("./main.js"),
+        ..Default::default()
+    };
+    evaluator::begin_eval_with_host(parse(source), host)
+}
+
+struct TestLoader(HashMap<&'static str, &'static str>);
+
+impl ModuleLoader for TestLoader {
+    fn resolve(&self, specifier: &str, _: &str) -> Result<String, String> {
+        Ok(specifier.to_owned())
+    }
+
+    fn load(&self, key: &str) -> Result<Node, String> {
+        let source = self
+            .0
+            .get(key)
+            .ok_or_else(|| format!("Cannot find module '{}'", key))?;
+        Ok(parse(source))
+    }
+}
+
+// The program completes with the primitive value `expected`
+pub fn assert_completion(source: &str, expected: impl Into<LiteralValue>) {
+    assert_value(eval(source), expected)
+}
+
+pub fn assert_value(result: EvaluatorResult, expected: impl Into<LiteralValue>) {
+    match result {
+        Ok(EvaluatorValue::Literal(literal)) => assert_eq!(literal.value, expected.into()),
+        Ok(value) => panic!("Expected a primitive value, got {}", value),
+        Err(e) => panic!("Unable to eval: {}", e.as_str()),
+    }
+}
+
+// The program ends with an error whose message contains `message`
+pub fn assert_throws(source: &str, message: &str) {
+    assert_error(eval(source), message)
+}
+
+pub fn assert_error(result: EvaluatorResult, message: &str) {
+    match result {
+        Err(e) => assert!(
+            e.as_str().contains(message),
+            "Expected an error containing {:?}, got {:?}",
+            message,
+            e.as_str()
+        ),
+        Ok(value) => panic!("Expected an error, got {}", value),
+    }
+}